@@ -0,0 +1,504 @@
+//! Structural diff of two CPI trees, e.g. the same scenario before and after
+//! a program upgrade. Siblings are aligned on the longest common
+//! subsequence of their program ids, so an inserted or dropped CPI shows up
+//! as one added or removed frame instead of shifting every later sibling.
+//! Unaligned frames left at the same spot pair up as a program change.
+
+use {
+    crate::{with_commas, CpiFrame, CpiOutcome, CONN_BRANCH, CONN_LAST, SPINE_CONTINUE, SPINE_END},
+    solana_address::Address,
+    std::fmt::Write,
+};
+
+/// A value on both sides of the diff, equal or not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change<T> {
+    pub before: T,
+    pub after: T,
+}
+
+impl<T: PartialEq> Change<T> {
+    pub fn is_changed(&self) -> bool {
+        self.before != self.after
+    }
+}
+
+/// One node of the merged tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameDiff {
+    /// A frame, with its subtree, only in `after`.
+    Added(CpiFrame),
+    /// A frame, with its subtree, only in `before`.
+    Removed(CpiFrame),
+    /// A frame at the same position in both trees.
+    Matched(FrameChange),
+}
+
+/// Two frames at the same position. Logs aren't compared: they're free text
+/// and change with every `msg!` edit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameChange {
+    pub program_id: Change<Address>,
+    pub instruction_name: Change<Option<String>>,
+    pub outcome: Change<CpiOutcome>,
+    /// Consumed CU, `None` on a side that reported none.
+    pub compute_units: Change<Option<u64>>,
+    pub children: Vec<FrameDiff>,
+}
+
+impl FrameChange {
+    /// `after - before` consumed CU, when both sides reported it.
+    pub fn compute_units_delta(&self) -> Option<i64> {
+        let (before, after) = (self.compute_units.before?, self.compute_units.after?);
+        Some(after as i64 - before as i64)
+    }
+
+    /// Whether this frame and its whole subtree are the same on both sides.
+    pub fn is_empty(&self) -> bool {
+        !self.program_id.is_changed()
+            && !self.instruction_name.is_changed()
+            && !self.outcome.is_changed()
+            && !self.compute_units.is_changed()
+            && self.children.iter().all(FrameDiff::is_empty)
+    }
+}
+
+impl FrameDiff {
+    fn is_empty(&self) -> bool {
+        match self {
+            FrameDiff::Matched(change) => change.is_empty(),
+            FrameDiff::Added(_) | FrameDiff::Removed(_) => false,
+        }
+    }
+}
+
+/// Differences between two CPI trees, as returned by [`diff`]. Holds every
+/// frame, changed or not, so renderers can show changes in context.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CpiTreeDiff {
+    pub frames: Vec<FrameDiff>,
+}
+
+impl CpiTreeDiff {
+    /// Whether the two trees are equivalent.
+    pub fn is_empty(&self) -> bool {
+        self.frames.iter().all(FrameDiff::is_empty)
+    }
+}
+
+/// Compares two transactions' CPI trees: added and removed CPIs, frames
+/// calling a different program, and per-frame changes in instruction name,
+/// outcome and consumed CU.
+pub fn diff(before: &[CpiFrame], after: &[CpiFrame]) -> CpiTreeDiff {
+    CpiTreeDiff {
+        frames: diff_siblings(before, after),
+    }
+}
+
+fn diff_siblings(before: &[CpiFrame], after: &[CpiFrame]) -> Vec<FrameDiff> {
+    let mut out = Vec::new();
+    let (mut b, mut a) = (0, 0);
+    for (next_b, next_a) in common_programs(before, after)
+        .into_iter()
+        .chain([(before.len(), after.len())])
+    {
+        // Frames between two anchors pair up in order; the surplus on
+        // either side was removed or added.
+        let (gap_before, gap_after) = (&before[b..next_b], &after[a..next_a]);
+        let paired = gap_before.len().min(gap_after.len());
+        for (x, y) in gap_before.iter().zip(gap_after) {
+            out.push(FrameDiff::Matched(diff_frame(x, y)));
+        }
+        out.extend(gap_before[paired..].iter().cloned().map(FrameDiff::Removed));
+        out.extend(gap_after[paired..].iter().cloned().map(FrameDiff::Added));
+
+        if next_b < before.len() {
+            out.push(FrameDiff::Matched(diff_frame(
+                &before[next_b],
+                &after[next_a],
+            )));
+        }
+        (b, a) = (next_b + 1, next_a + 1);
+    }
+    out
+}
+
+fn diff_frame(before: &CpiFrame, after: &CpiFrame) -> FrameChange {
+    FrameChange {
+        program_id: Change {
+            before: before.program_id,
+            after: after.program_id,
+        },
+        instruction_name: Change {
+            before: before.instruction_name.clone(),
+            after: after.instruction_name.clone(),
+        },
+        outcome: Change {
+            before: before.outcome.clone(),
+            after: after.outcome.clone(),
+        },
+        compute_units: Change {
+            before: before.compute_units.map(|cu| cu.consumed),
+            after: after.compute_units.map(|cu| cu.consumed),
+        },
+        children: diff_siblings(&before.children, &after.children),
+    }
+}
+
+/// Index pairs of the longest common subsequence of the two sides' program
+/// ids. Sibling lists are short, so the quadratic table is fine.
+fn common_programs(before: &[CpiFrame], after: &[CpiFrame]) -> Vec<(usize, usize)> {
+    let (n, m) = (before.len(), after.len());
+    // `lengths[i][j]`: LCS length of `before[i..]` and `after[j..]`.
+    let mut lengths = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i][j] = if before[i].program_id == after[j].program_id {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut pairs = Vec::with_capacity(lengths[0][0]);
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if before[i].program_id == after[j].program_id {
+            pairs.push((i, j));
+            (i, j) = (i + 1, j + 1);
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+/// `cargo tree`-style rendering of a diff under `header`, in the layout of
+/// [`crate::format_cpi_tree`]. Changed frames are marked `~`, added `+` and
+/// removed `-`, with `before -> after` for each changed field and the CU
+/// delta; unchanged frames are kept as context.
+pub fn format_cpi_tree_diff(header: &str, diff: &CpiTreeDiff) -> String {
+    format_cpi_tree_diff_with(header, diff, &|addr| addr.to_string())
+}
+
+/// Like [`format_cpi_tree_diff`], but the caller supplies how each program
+/// id is rendered, as in [`crate::format_cpi_tree_with`].
+pub fn format_cpi_tree_diff_with(
+    header: &str,
+    diff: &CpiTreeDiff,
+    program_label: &dyn Fn(&Address) -> String,
+) -> String {
+    let mut out = String::new();
+    writeln!(out, "{header}").unwrap();
+    if diff.is_empty() {
+        writeln!(out, "no differences").unwrap();
+        return out;
+    }
+    write_siblings(&mut out, &diff.frames, "", program_label);
+    out
+}
+
+fn write_siblings(
+    out: &mut String,
+    frames: &[FrameDiff],
+    prefix: &str,
+    program_label: &dyn Fn(&Address) -> String,
+) {
+    let last_idx = frames.len().saturating_sub(1);
+    for (i, frame) in frames.iter().enumerate() {
+        let is_last = i == last_idx;
+        let connector = if is_last { CONN_LAST } else { CONN_BRANCH };
+        let child_prefix = if is_last {
+            format!("{prefix}{SPINE_END}")
+        } else {
+            format!("{prefix}{SPINE_CONTINUE}")
+        };
+        match frame {
+            FrameDiff::Added(frame) => {
+                write_subtree(out, frame, '+', prefix, is_last, program_label)
+            }
+            FrameDiff::Removed(frame) => {
+                write_subtree(out, frame, '-', prefix, is_last, program_label)
+            }
+            FrameDiff::Matched(change) => {
+                write!(out, "{prefix}{connector}").unwrap();
+                write_change(out, change, program_label);
+                write_siblings(out, &change.children, &child_prefix, program_label);
+            }
+        }
+    }
+}
+
+fn write_change(
+    out: &mut String,
+    change: &FrameChange,
+    program_label: &dyn Fn(&Address) -> String,
+) {
+    let own_change = change.program_id.is_changed()
+        || change.instruction_name.is_changed()
+        || change.outcome.is_changed()
+        || change.compute_units.is_changed();
+    if own_change {
+        write!(out, "~ ").unwrap();
+    }
+
+    let Change { before, after } = &change.instruction_name;
+    match (before, after) {
+        (before, after) if before == after => {
+            if let Some(name) = after {
+                write!(out, "{name} ").unwrap();
+            }
+        }
+        (before, after) => write!(
+            out,
+            "{} -> {} ",
+            before.as_deref().unwrap_or("?"),
+            after.as_deref().unwrap_or("?")
+        )
+        .unwrap(),
+    }
+
+    if change.outcome.is_changed() {
+        write!(
+            out,
+            "[{} -> {}] ",
+            outcome_text(&change.outcome.before),
+            outcome_text(&change.outcome.after)
+        )
+        .unwrap();
+    } else if change.outcome.after != CpiOutcome::Success {
+        write!(out, "[{}] ", outcome_text(&change.outcome.after)).unwrap();
+    }
+
+    let Change { before, after } = change.compute_units;
+    match (before, after) {
+        (Some(before), Some(after)) if before == after => {
+            write!(out, "({} CU) ", with_commas(after)).unwrap();
+        }
+        (None, None) => {}
+        (before, after) => {
+            write!(out, "({} -> {} CU", cu_text(before), cu_text(after)).unwrap();
+            if let Some(delta) = change.compute_units_delta() {
+                let sign = if delta < 0 { '-' } else { '+' };
+                write!(out, ", {sign}{}", with_commas(delta.unsigned_abs())).unwrap();
+            }
+            write!(out, ") ").unwrap();
+        }
+    }
+
+    if change.program_id.is_changed() {
+        writeln!(
+            out,
+            "{} -> {}",
+            program_label(&change.program_id.before),
+            program_label(&change.program_id.after)
+        )
+        .unwrap();
+    } else {
+        writeln!(out, "{}", program_label(&change.program_id.after)).unwrap();
+    }
+}
+
+/// An added or removed frame and everything under it, every line marked.
+fn write_subtree(
+    out: &mut String,
+    frame: &CpiFrame,
+    marker: char,
+    prefix: &str,
+    is_last: bool,
+    program_label: &dyn Fn(&Address) -> String,
+) {
+    let connector = if is_last { CONN_LAST } else { CONN_BRANCH };
+    write!(out, "{prefix}{connector}{marker} ").unwrap();
+    if let Some(name) = &frame.instruction_name {
+        write!(out, "{name} ").unwrap();
+    }
+    if frame.outcome != CpiOutcome::Success {
+        write!(out, "[{}] ", outcome_text(&frame.outcome)).unwrap();
+    }
+    if let Some(cu) = frame.compute_units {
+        write!(out, "({} CU) ", with_commas(cu.consumed)).unwrap();
+    }
+    writeln!(out, "{}", program_label(&frame.program_id)).unwrap();
+
+    let child_prefix = if is_last {
+        format!("{prefix}{SPINE_END}")
+    } else {
+        format!("{prefix}{SPINE_CONTINUE}")
+    };
+    let last_idx = frame.children.len().saturating_sub(1);
+    for (i, child) in frame.children.iter().enumerate() {
+        write_subtree(
+            out,
+            child,
+            marker,
+            &child_prefix,
+            i == last_idx,
+            program_label,
+        );
+    }
+}
+
+fn outcome_text(outcome: &CpiOutcome) -> String {
+    match outcome {
+        CpiOutcome::Success => "success".to_string(),
+        CpiOutcome::Failed { message } => match message {
+            Some(message) => format!("FAILED: {message}"),
+            None => "FAILED".to_string(),
+        },
+        CpiOutcome::Truncated => "TRUNCATED".to_string(),
+    }
+}
+
+fn cu_text(cu: Option<u64>) -> String {
+    cu.map_or_else(|| "no".to_string(), with_commas)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::ComputeUnits, solana_address::address};
+
+    const PROG_A: Address = address!("GtdambwDgHWrDJdVPBkEHGhCwokqgAoch162teUjJse2");
+    const PROG_B: Address = address!("6Ng7PojJBe6XjbsR65ftKKBpHUe2erD7E5dgGdMUjcgg");
+    const TOKEN_PROG: Address = address!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+    const SYSTEM_PROG: Address = address!("11111111111111111111111111111111");
+
+    fn frame(program_id: Address, name: &str, cu: u64, children: Vec<CpiFrame>) -> CpiFrame {
+        CpiFrame {
+            program_id,
+            outcome: CpiOutcome::Success,
+            compute_units: Some(ComputeUnits {
+                consumed: cu,
+                available_at_start: 200_000,
+            }),
+            instruction_name: Some(name.to_string()),
+            logs: Vec::new(),
+            children,
+            instruction_data: None,
+        }
+    }
+
+    fn label(addr: &Address) -> String {
+        match *addr {
+            PROG_A => "amm",
+            PROG_B => "amm-v2",
+            TOKEN_PROG => "token",
+            SYSTEM_PROG => "system",
+            _ => unreachable!(),
+        }
+        .to_string()
+    }
+
+    #[test]
+    fn identical_trees_have_no_differences() {
+        let tree = vec![frame(
+            PROG_A,
+            "Swap",
+            20_000,
+            vec![frame(TOKEN_PROG, "Transfer", 4_645, vec![])],
+        )];
+        let diff = diff(&tree, &tree);
+        assert!(diff.is_empty());
+        assert_eq!(
+            format_cpi_tree_diff("CPI Tree diff:", &diff),
+            "CPI Tree diff:\nno differences\n"
+        );
+    }
+
+    #[test]
+    fn inserted_cpi_is_added_without_shifting_siblings() {
+        let before = vec![frame(
+            PROG_A,
+            "Swap",
+            20_000,
+            vec![
+                frame(TOKEN_PROG, "Transfer", 4_645, vec![]),
+                frame(TOKEN_PROG, "Transfer", 4_645, vec![]),
+            ],
+        )];
+        let after = vec![frame(
+            PROG_A,
+            "Swap",
+            23_000,
+            vec![
+                frame(TOKEN_PROG, "Transfer", 4_645, vec![]),
+                frame(SYSTEM_PROG, "Transfer", 150, vec![]),
+                frame(TOKEN_PROG, "Transfer", 4_645, vec![]),
+            ],
+        )];
+        let diff = diff(&before, &after);
+
+        let FrameDiff::Matched(root) = &diff.frames[0] else {
+            panic!("root should match: {diff:?}");
+        };
+        assert_eq!(root.compute_units_delta(), Some(3_000));
+        assert!(matches!(root.children[0], FrameDiff::Matched(ref c) if c.is_empty()));
+        assert!(matches!(root.children[1], FrameDiff::Added(ref f) if f.program_id == SYSTEM_PROG));
+        assert!(matches!(root.children[2], FrameDiff::Matched(ref c) if c.is_empty()));
+    }
+
+    #[test]
+    fn format_marks_changes_in_context() {
+        let before = vec![frame(
+            PROG_A,
+            "Swap",
+            20_000,
+            vec![
+                frame(TOKEN_PROG, "Transfer", 4_645, vec![]),
+                frame(TOKEN_PROG, "MintTo", 4_000, vec![]),
+                frame(SYSTEM_PROG, "Transfer", 150, vec![]),
+            ],
+        )];
+        let mut failed = frame(
+            PROG_B,
+            "Swap",
+            18_500,
+            vec![
+                frame(TOKEN_PROG, "Transfer", 4_645, vec![]),
+                frame(
+                    PROG_A,
+                    "Route",
+                    900,
+                    vec![frame(TOKEN_PROG, "Burn", 300, vec![])],
+                ),
+            ],
+        );
+        failed.outcome = CpiOutcome::Failed {
+            message: Some("custom program error: 0x1".to_string()),
+        };
+        let after = vec![failed];
+
+        let rendered = format_cpi_tree_diff_with("CPI Tree diff:", &diff(&before, &after), &label);
+        assert_eq!(
+            rendered,
+            "\
+CPI Tree diff:
+└── ~ Swap [success -> FAILED: custom program error: 0x1] (20,000 -> 18,500 CU, -1,500) amm -> amm-v2
+    ├── Transfer (4,645 CU) token
+    ├── ~ MintTo -> Route (4,000 -> 900 CU, -3,100) token -> amm
+    │   └── + Burn (300 CU) token
+    └── - Transfer (150 CU) system
+"
+        );
+    }
+
+    #[test]
+    fn missing_compute_units_render_without_delta() {
+        let mut before = frame(SYSTEM_PROG, "Transfer", 0, vec![]);
+        before.compute_units = None;
+        let after = frame(SYSTEM_PROG, "Transfer", 150, vec![]);
+
+        let diff = diff(&[before], &[after]);
+        let FrameDiff::Matched(change) = &diff.frames[0] else {
+            panic!("frame should match: {diff:?}");
+        };
+        assert_eq!(change.compute_units_delta(), None);
+        assert_eq!(
+            format_cpi_tree_diff_with("diff", &diff, &label),
+            "diff\n└── ~ Transfer (no -> 150 CU) system\n"
+        );
+    }
+}
@@ -6,9 +6,10 @@
 
 use {
     crate::{
-        format_cpi_tree, transaction_compute_budget, transaction_total_cu, with_commas, CpiFrame,
+        cpi_tree_from_trace, format_cpi_tree_with, transaction_compute_budget,
+        transaction_total_cu, with_commas, CpiFrame,
     },
-    litesvm::types::TransactionMetadata,
+    litesvm::{labels::Labels, types::TransactionMetadata},
 };
 
 /// CPI-tree access on a transaction's metadata. Bring it into scope
@@ -18,9 +19,18 @@ pub trait CpiTreeExt {
     /// Parse this transaction's logs into a tree of CPI frames.
     fn cpi_tree(&self) -> Vec<CpiFrame>;
 
+    /// Build the tree from this transaction's invocations and inner
+    /// instructions instead of its logs, so it stays exact when the logs
+    /// were truncated. See [`cpi_tree_from_trace`].
+    fn cpi_tree_from_trace(&self) -> Vec<CpiFrame>;
+
     /// Render the CPI tree as `cargo tree`-style box art under a header
     /// reporting the transaction's BPF CU and budget.
     fn pretty_cpi_tree(&self) -> String;
+
+    /// Like [`pretty_cpi_tree`](Self::pretty_cpi_tree), with each program
+    /// named by `labels` (pass `svm.labels()`) where it has a label.
+    fn pretty_cpi_tree_with_labels(&self, labels: &Labels) -> String;
 }
 
 impl CpiTreeExt for TransactionMetadata {
@@ -28,7 +38,15 @@ impl CpiTreeExt for TransactionMetadata {
         crate::cpi_tree(&self.logs)
     }
 
+    fn cpi_tree_from_trace(&self) -> Vec<CpiFrame> {
+        cpi_tree_from_trace(&self.invocations, &self.inner_instructions)
+    }
+
     fn pretty_cpi_tree(&self) -> String {
+        self.pretty_cpi_tree_with_labels(&Labels::empty())
+    }
+
+    fn pretty_cpi_tree_with_labels(&self, labels: &Labels) -> String {
         let frames = self.cpi_tree();
         // Same header agave's `solana logs --tree` builds: transaction-total
         // BPF CU and the budget, or an explicit no-data note. Never "0 CU":
@@ -45,13 +63,16 @@ impl CpiTreeExt for TransactionMetadata {
             ),
             _ => "CPI Tree (no compute units in logs):".to_string(),
         };
-        format_cpi_tree(&header, &frames)
+        format_cpi_tree_with(&header, &frames, &|addr| labels.name(addr))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use {super::CpiTreeExt, litesvm::types::TransactionMetadata};
+    use {
+        super::CpiTreeExt,
+        litesvm::{labels::Labels, types::TransactionMetadata},
+    };
 
     fn meta_with_logs(logs: Vec<String>) -> TransactionMetadata {
         TransactionMetadata {
@@ -90,4 +111,15 @@ mod tests {
             "unexpected header: {out}"
         );
     }
+
+    #[test]
+    fn pretty_cpi_tree_with_labels_names_programs() {
+        let meta = meta_with_logs(vec![
+            "Program 11111111111111111111111111111111 invoke [1]".to_string(),
+            "Program 11111111111111111111111111111111 success".to_string(),
+        ]);
+        let out = meta.pretty_cpi_tree_with_labels(&Labels::default());
+        assert!(out.contains("── system"), "unexpected tree: {out}");
+        assert!(!out.contains("1111111111"), "unexpected tree: {out}");
+    }
 }
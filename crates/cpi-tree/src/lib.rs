@@ -13,8 +13,9 @@
 //!
 //! # Layer 1: per-line classifier (FSA)
 //!
-//! `classify` plus the `strip_prefix` cascade maps one line to one token.
-//! No memory across lines; pure regular language.
+//! litesvm's `LogEvent::from_logs` maps one line to one token; the tree
+//! reads each `LogEvent` as below. No memory across lines; pure regular
+//! language.
 //!
 //! ```text
 //!              ┌─ "Program log: Instruction: <n>" -> Instruction(n)
@@ -60,12 +61,35 @@
 //! case for "stream ended mid-frame".
 
 use {
+    litesvm::types::{InvocationMetadata, LogEvent},
     solana_address::Address,
-    std::{fmt::Write, str::FromStr},
+    solana_message::inner_instruction::InnerInstructionsList,
+    std::fmt::Write,
 };
 
+mod diff;
 mod ext;
-pub use ext::CpiTreeExt;
+mod render;
+#[cfg(feature = "serde")]
+mod serde_address;
+mod summary;
+#[cfg(feature = "serde")]
+pub use render::format_cpi_tree_json;
+pub use {
+    diff::{
+        diff, format_cpi_tree_diff, format_cpi_tree_diff_with, Change, CpiTreeDiff, FrameChange,
+        FrameDiff,
+    },
+    ext::CpiTreeExt,
+    render::{
+        format_cpi_tree_html, format_cpi_tree_html_with, format_cpi_tree_mermaid,
+        format_cpi_tree_mermaid_with,
+    },
+    summary::{
+        format_compute_unit_summary, format_compute_unit_summary_with, ComputeUnitStats,
+        ComputeUnitSummary, Distribution,
+    },
+};
 
 // `cargo tree` glyphs. Connectors go on a child's line; spines continue
 // under a frame on lines that follow. 4 cols wide so nested frames align.
@@ -84,6 +108,7 @@ const LOG_SPINE_END: &str = "  ";
 /// `ComputeBudget`, `BpfLoader`, precompiles). The totals below depend on
 /// this native-program gap; see `transaction_total_cu`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ComputeUnits {
     /// CU consumed by this frame as reported by the SBPF VM. Cumulative
     /// over CPI children of the same frame (so descending the tree would
@@ -97,7 +122,10 @@ pub struct ComputeUnits {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CpiFrame {
+    /// Serialized as base58 so JSON output reads like the logs it came from.
+    #[cfg_attr(feature = "serde", serde(with = "serde_address"))]
     pub program_id: Address,
     pub outcome: CpiOutcome,
     pub compute_units: Option<ComputeUnits>,
@@ -106,9 +134,18 @@ pub struct CpiFrame {
     /// the stack, in arrival order. Survives every outcome.
     pub logs: Vec<FrameLog>,
     pub children: Vec<CpiFrame>,
+    /// Data of a CPI instruction. Only [`cpi_tree_from_trace`] fills it in,
+    /// and only for CPIs: the logs don't carry instruction data, and the
+    /// trace only records it for inner instructions.
+    pub instruction_data: Option<Vec<u8>>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "kind", content = "text", rename_all = "snake_case")
+)]
 pub enum FrameLog {
     /// `Msg(t)` and `Other(line)` tokens (see module-level FSA). `Other`
     /// lands here too: no destructured shape, and the renderer treats text
@@ -121,6 +158,8 @@ pub enum FrameLog {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "status", rename_all = "snake_case"))]
 pub enum CpiOutcome {
     Success,
     Failed {
@@ -137,39 +176,32 @@ pub fn cpi_tree(logs: &[String]) -> Vec<CpiFrame> {
     let mut roots: Vec<CpiFrame> = Vec::new();
     let mut stack: Vec<CpiFrame> = Vec::new();
 
-    for log in logs {
-        if let Some(name) = log.strip_prefix("Program log: Instruction: ") {
-            // `Instruction(n)`: dispatcher convention (Anchor, SPL Token).
-            // Drop any pre-handler `Msg` chatter; keep `Data`. First name
-            // wins.
-            if let Some(frame) = stack.last_mut() {
-                frame
-                    .logs
-                    .retain(|entry| !matches!(entry, FrameLog::Msg(_)));
-                if frame.instruction_name.is_none() {
-                    frame.instruction_name = Some(name.to_string());
+    for (log, event) in logs.iter().zip(LogEvent::from_logs(logs)) {
+        match event {
+            LogEvent::Log { message, .. } => {
+                let Some(frame) = stack.last_mut() else {
+                    continue;
+                };
+                if let Some(name) = message.strip_prefix("Instruction: ") {
+                    // `Instruction(n)`: dispatcher convention (Anchor, SPL
+                    // Token). Drop any pre-handler `Msg` chatter; keep
+                    // `Data`. First name wins.
+                    frame
+                        .logs
+                        .retain(|entry| !matches!(entry, FrameLog::Msg(_)));
+                    if frame.instruction_name.is_none() {
+                        frame.instruction_name = Some(name.to_string());
+                    }
+                } else {
+                    frame.logs.push(FrameLog::Msg(message));
                 }
             }
-            continue;
-        }
-        if let Some(text) = log.strip_prefix("Program log: ") {
-            if let Some(frame) = stack.last_mut() {
-                frame.logs.push(FrameLog::Msg(text.to_string()));
-            }
-            continue;
-        }
-        if let Some(payload) = log.strip_prefix("Program data: ") {
-            if let Some(frame) = stack.last_mut() {
-                frame.logs.push(FrameLog::Data(payload.to_string()));
+            LogEvent::Data { data, .. } => {
+                if let Some(frame) = stack.last_mut() {
+                    frame.logs.push(FrameLog::Data(data.join(" ")));
+                }
             }
-            continue;
-        }
-
-        match classify(log) {
-            LogLine::Invoke(program) => {
-                let Ok(program_id) = Address::from_str(&program) else {
-                    continue;
-                };
+            LogEvent::Invoke { program_id, .. } => {
                 // PUSH with `outcome: Truncated` pre-seeded; the EOF drain
                 // below leaves it untouched if no status line arrives.
                 stack.push(CpiFrame {
@@ -179,24 +211,42 @@ pub fn cpi_tree(logs: &[String]) -> Vec<CpiFrame> {
                     instruction_name: None,
                     logs: Vec::new(),
                     children: Vec::new(),
+                    instruction_data: None,
                 });
             }
-            LogLine::Consumed(cu) => {
+            LogEvent::Consumed {
+                consumed,
+                available,
+                ..
+            } => {
                 if let Some(frame) = stack.last_mut() {
-                    frame.compute_units = Some(cu);
+                    frame.compute_units = Some(ComputeUnits {
+                        consumed,
+                        available_at_start: available,
+                    });
                 }
             }
-            LogLine::Status(status) => {
+            LogEvent::Success { .. } | LogEvent::Failed { .. } => {
                 let Some(mut frame) = stack.pop() else {
                     continue;
                 };
-                frame.outcome = match status {
-                    Status::Success => CpiOutcome::Success,
-                    Status::Failed { message } => CpiOutcome::Failed { message },
+                frame.outcome = match event {
+                    LogEvent::Failed { error, .. } => {
+                        // Pass the message body through unmodified, whatever
+                        // whitespace the runtime used.
+                        let message = error.trim();
+                        CpiOutcome::Failed {
+                            message: (!message.is_empty()).then(|| message.to_string()),
+                        }
+                    }
+                    _ => CpiOutcome::Success,
                 };
                 push_into_parent_or_roots(frame, &mut stack, &mut roots);
             }
-            LogLine::Other => {
+            LogEvent::ComputeUnitsRemaining { .. }
+            | LogEvent::ReturnData { .. }
+            | LogEvent::Truncated
+            | LogEvent::Other { .. } => {
                 // `Other` bucketed as `Msg` per the PDA's payload-only row.
                 // No frame open: drop it (defined behavior, not a panic).
                 if let Some(frame) = stack.last_mut() {
@@ -214,6 +264,74 @@ pub fn cpi_tree(logs: &[String]) -> Vec<CpiFrame> {
     roots
 }
 
+/// Build the tree from the runtime's execution record instead of its logs.
+/// `invocations` give each frame's program, depth, CU and outcome, so the
+/// tree is exact even when the log bytes limit cut the logs short and
+/// [`cpi_tree`] would end in `Truncated` frames. `inner_instructions` add
+/// the data of each CPI.
+///
+/// Nothing here comes from the logs: `instruction_name` stays `None` and
+/// `logs` empty. Builtin programs and precompiles aren't metered, so their
+/// frames have no `compute_units`, as in the log-built tree.
+pub fn cpi_tree_from_trace(
+    invocations: &[InvocationMetadata],
+    inner_instructions: &InnerInstructionsList,
+) -> Vec<CpiFrame> {
+    // Invocations below the top level are the inner instructions, in the
+    // same order, as both are read from the instruction trace. Pair them up
+    // only if the depths agree throughout, in case they were built apart.
+    let cpis = inner_instructions.iter().flatten();
+    let aligned = cpis
+        .clone()
+        .map(|cpi| usize::from(cpi.stack_height))
+        .eq(invocations
+            .iter()
+            .map(|invocation| invocation.stack_height)
+            .filter(|height| *height > 1));
+    let mut cpi_data = cpis.map(|cpi| cpi.instruction.data.clone());
+
+    let mut roots: Vec<CpiFrame> = Vec::new();
+    let mut stack: Vec<CpiFrame> = Vec::new();
+    for invocation in invocations {
+        // A frame at height `h` is the child of the frame at height
+        // `h - 1`; anything at or above `h` has already returned.
+        while stack.len() >= invocation.stack_height.max(1) {
+            let frame = stack.pop().unwrap();
+            push_into_parent_or_roots(frame, &mut stack, &mut roots);
+        }
+        let instruction_data = if invocation.stack_height > 1 && aligned {
+            cpi_data.next()
+        } else {
+            None
+        };
+        stack.push(CpiFrame {
+            program_id: invocation.program_id,
+            outcome: match &invocation.error {
+                None => CpiOutcome::Success,
+                Some(message) => CpiOutcome::Failed {
+                    message: Some(message.clone()),
+                },
+            },
+            compute_units: invocation
+                .compute_units_consumed
+                .zip(invocation.compute_units_available)
+                .map(|(consumed, available_at_start)| ComputeUnits {
+                    consumed,
+                    available_at_start,
+                }),
+            instruction_name: None,
+            logs: Vec::new(),
+            children: Vec::new(),
+            instruction_data,
+        });
+    }
+    while let Some(frame) = stack.pop() {
+        push_into_parent_or_roots(frame, &mut stack, &mut roots);
+    }
+
+    roots
+}
+
 fn push_into_parent_or_roots(frame: CpiFrame, stack: &mut [CpiFrame], roots: &mut Vec<CpiFrame>) {
     if let Some(parent) = stack.last_mut() {
         parent.children.push(frame);
@@ -267,48 +385,6 @@ pub fn transaction_compute_budget(frames: &[CpiFrame]) -> Option<u64> {
         .find_map(|f| f.compute_units.as_ref().map(|cu| cu.available_at_start))
 }
 
-enum LogLine {
-    Invoke(String),
-    Consumed(ComputeUnits),
-    Status(Status),
-    Other,
-}
-
-enum Status {
-    Success,
-    Failed { message: Option<String> },
-}
-
-/// Layer-1 FSA: tokenize on spaces and match the slice shape at known
-/// indices. Malformed structural shapes degrade to `Other` rather than
-/// constructing partial tokens.
-fn classify(log: &str) -> LogLine {
-    let tokens: Vec<&str> = log.split(' ').collect();
-    match tokens.as_slice() {
-        ["Program", _name, "invoke", bracket] if parse_depth_bracket(bracket).is_some() => {
-            LogLine::Invoke(tokens[1].to_string())
-        }
-        ["Program", _, "success"] => LogLine::Status(Status::Success),
-        ["Program", _, "failed:", ..] => {
-            // Pass the message body through unmodified, whatever whitespace
-            // the runtime used.
-            let raw = log.splitn(4, ' ').nth(3).unwrap_or("").trim();
-            let message = (!raw.is_empty()).then(|| raw.to_string());
-            LogLine::Status(Status::Failed { message })
-        }
-        ["Program", _, "consumed", consumed, "of", available, "compute", "units"] => {
-            match (consumed.parse::<u64>(), available.parse::<u64>()) {
-                (Ok(consumed), Ok(available_at_start)) => LogLine::Consumed(ComputeUnits {
-                    consumed,
-                    available_at_start,
-                }),
-                _ => LogLine::Other,
-            }
-        }
-        _ => LogLine::Other,
-    }
-}
-
 /// `cargo tree`-style box-art under a synthetic header. The header acts as
 /// a visible parent so a transaction's multiple top-level frames read as
 /// siblings rather than flush-left strangers.
@@ -321,10 +397,8 @@ pub fn format_cpi_tree(header: &str, frames: &[CpiFrame]) -> String {
 /// tree structure; the consumer augments the label.
 ///
 /// ```text
-/// // swap raw pubkeys for human aliases, falling back to the pubkey:
-/// format_cpi_tree_with(header, frames, &|addr| {
-///     aliases.get(addr).cloned().unwrap_or_else(|| addr.to_string())
-/// })
+/// // swap raw pubkeys for the VM's labels, falling back to the pubkey:
+/// format_cpi_tree_with(header, frames, &|addr| svm.labels().name(addr))
 /// ```
 pub fn format_cpi_tree_with(
     header: &str,
@@ -398,17 +472,15 @@ fn write_frame(
     }
 }
 
-fn parse_depth_bracket(token: &str) -> Option<usize> {
-    token
-        .strip_prefix('[')?
-        .strip_suffix(']')?
-        .parse::<usize>()
-        .ok()
-}
-
 #[cfg(test)]
 mod tests {
-    use {super::*, solana_address::address};
+    use {
+        super::*,
+        solana_address::address,
+        solana_message::{
+            compiled_instruction::CompiledInstruction, inner_instruction::InnerInstruction,
+        },
+    };
 
     // ---- Address fixtures ----
     // Named program ids referenced across tests. The `address!` macro rejects
@@ -1148,4 +1220,91 @@ mod tests {
         );
         assert_eq!(transaction_total_cu(&tree), Some(1_500));
     }
+
+    fn invocation(program_id: Address, stack_height: usize) -> InvocationMetadata {
+        InvocationMetadata {
+            program_id,
+            stack_height,
+            ..Default::default()
+        }
+    }
+
+    fn inner(stack_height: u8, data: &[u8]) -> InnerInstruction {
+        InnerInstruction {
+            instruction: CompiledInstruction::new_from_raw_parts(0, data.to_vec(), vec![]),
+            stack_height,
+        }
+    }
+
+    #[test]
+    fn trace_nests_frames_by_stack_height() {
+        let invocations = [
+            InvocationMetadata {
+                compute_units_consumed: Some(1_500),
+                compute_units_available: Some(200_000),
+                ..invocation(PROG_A, 1)
+            },
+            invocation(TOKEN_PROG, 2),
+            invocation(PROG_B, 3),
+            invocation(SYSTEM_PROG, 2),
+            invocation(PROG_C, 1),
+        ];
+        let inner_instructions = vec![vec![inner(2, &[1]), inner(3, &[2]), inner(2, &[3])], vec![]];
+        let tree = cpi_tree_from_trace(&invocations, &inner_instructions);
+
+        assert_eq!(tree.len(), 2);
+        let root = &tree[0];
+        assert_eq!(
+            root.compute_units,
+            Some(ComputeUnits {
+                consumed: 1_500,
+                available_at_start: 200_000,
+            })
+        );
+        assert_eq!(root.instruction_data, None);
+        assert_eq!(root.children.len(), 2);
+        assert_eq!(root.children[0].program_id, TOKEN_PROG);
+        assert_eq!(root.children[0].instruction_data, Some(vec![1]));
+        assert_eq!(root.children[0].children[0].program_id, PROG_B);
+        assert_eq!(root.children[0].children[0].instruction_data, Some(vec![2]));
+        assert_eq!(root.children[1].program_id, SYSTEM_PROG);
+        assert_eq!(root.children[1].instruction_data, Some(vec![3]));
+        assert_eq!(tree[1].program_id, PROG_C);
+        assert!(tree[1].children.is_empty());
+    }
+
+    #[test]
+    fn trace_carries_failures_without_truncation() {
+        let invocations = [
+            InvocationMetadata {
+                error: Some("custom program error: 0x1".to_string()),
+                ..invocation(PROG_A, 1)
+            },
+            invocation(TOKEN_PROG, 2),
+            InvocationMetadata {
+                error: Some("custom program error: 0x1".to_string()),
+                ..invocation(TOKEN_PROG, 2)
+            },
+        ];
+        let tree = cpi_tree_from_trace(&invocations, &vec![vec![inner(2, &[]), inner(2, &[])]]);
+
+        let failed = CpiOutcome::Failed {
+            message: Some("custom program error: 0x1".to_string()),
+        };
+        assert_eq!(tree[0].outcome, failed);
+        assert_eq!(tree[0].children[0].outcome, CpiOutcome::Success);
+        assert_eq!(tree[0].children[1].outcome, failed);
+    }
+
+    #[test]
+    fn trace_skips_instruction_data_when_cpis_do_not_line_up() {
+        // A CPI rejected before it started is in the trace but was never
+        // invoked; pairing by position would hand out the wrong data.
+        let invocations = [invocation(PROG_A, 1), invocation(TOKEN_PROG, 2)];
+        let inner_instructions = vec![vec![inner(2, &[1]), inner(2, &[2])]];
+        let tree = cpi_tree_from_trace(&invocations, &inner_instructions);
+
+        assert_eq!(tree[0].children.len(), 1);
+        assert_eq!(tree[0].children[0].instruction_data, None);
+    }
 }
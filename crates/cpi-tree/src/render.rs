@@ -0,0 +1,427 @@
+//! Structured renderers for a parsed tree, next to the `cargo tree`-style
+//! text in the crate root: JSON for tooling, a Mermaid sequence diagram to
+//! paste into a PR, and a self-contained HTML page for browsing a CI
+//! artifact. All of them walk the same `CpiFrame`s; none re-parse logs.
+
+use {
+    crate::{
+        transaction_compute_budget, transaction_total_cu, with_commas, CpiFrame, CpiOutcome,
+        FrameLog,
+    },
+    solana_address::Address,
+    std::fmt::Write,
+};
+
+/// Pretty-printed JSON array of the root frames. Program ids are base58
+/// strings, outcomes are tagged by `status` and log entries by `kind`, so
+/// the output deserializes back into `Vec<CpiFrame>`.
+#[cfg(feature = "serde")]
+pub fn format_cpi_tree_json(frames: &[CpiFrame]) -> String {
+    // Plain data with string map keys only; serialization can't fail.
+    serde_json::to_string_pretty(frames).unwrap()
+}
+
+/// Mermaid `sequenceDiagram` of the tree: a synthetic `Transaction`
+/// participant calls each root frame, every CPI is a call arrow from its
+/// parent, and the reply arrow carries the outcome. Logs are left out; a
+/// diagram with every `msg!` line stops being readable.
+pub fn format_cpi_tree_mermaid(frames: &[CpiFrame]) -> String {
+    format_cpi_tree_mermaid_with(frames, &|addr| addr.to_string())
+}
+
+/// Like [`format_cpi_tree_mermaid`], but the caller supplies each
+/// participant's label, as in [`crate::format_cpi_tree_with`].
+pub fn format_cpi_tree_mermaid_with(
+    frames: &[CpiFrame],
+    program_label: &dyn Fn(&Address) -> String,
+) -> String {
+    // One participant per distinct program, in first-invocation order, so
+    // the columns read left to right the way the calls happen.
+    let mut participants = Vec::new();
+    collect_programs(frames, &mut participants);
+
+    let mut out = String::new();
+    writeln!(out, "sequenceDiagram").unwrap();
+    writeln!(out, "    participant tx as Transaction").unwrap();
+    for (i, program_id) in participants.iter().enumerate() {
+        writeln!(
+            out,
+            "    participant p{i} as {}",
+            mermaid_text(&program_label(program_id))
+        )
+        .unwrap();
+    }
+    for frame in frames {
+        write_mermaid_frame(&mut out, frame, "tx", &participants);
+    }
+    out
+}
+
+fn collect_programs(frames: &[CpiFrame], out: &mut Vec<Address>) {
+    for frame in frames {
+        if !out.contains(&frame.program_id) {
+            out.push(frame.program_id);
+        }
+        collect_programs(&frame.children, out);
+    }
+}
+
+fn write_mermaid_frame(out: &mut String, frame: &CpiFrame, caller: &str, participants: &[Address]) {
+    // `collect_programs` visited every frame, so the lookup can't miss.
+    let index = participants
+        .iter()
+        .position(|program_id| *program_id == frame.program_id)
+        .unwrap();
+    let callee = format!("p{index}");
+
+    let mut call = frame
+        .instruction_name
+        .clone()
+        .unwrap_or_else(|| "invoke".to_string());
+    if let Some(cu) = frame.compute_units {
+        write!(call, " ({} CU)", with_commas(cu.consumed)).unwrap();
+    }
+    writeln!(out, "    {caller}->>{callee}: {}", mermaid_text(&call)).unwrap();
+    writeln!(out, "    activate {callee}").unwrap();
+    for child in &frame.children {
+        write_mermaid_frame(out, child, &callee, participants);
+    }
+    match &frame.outcome {
+        CpiOutcome::Success => writeln!(out, "    {callee}-->>{caller}: success").unwrap(),
+        CpiOutcome::Failed { message } => {
+            let text = match message {
+                Some(message) => format!("FAILED: {message}"),
+                None => "FAILED".to_string(),
+            };
+            writeln!(out, "    {callee}--x{caller}: {}", mermaid_text(&text)).unwrap();
+        }
+        // No reply arrow: the frame never returned as far as the logs know.
+        CpiOutcome::Truncated => writeln!(out, "    Note over {callee}: TRUNCATED").unwrap(),
+    }
+    writeln!(out, "    deactivate {callee}").unwrap();
+}
+
+/// Mermaid ends a statement at `;` or a newline and reads `#...;` as an
+/// entity, so those become entity codes.
+fn mermaid_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '#' => out.push_str("#35;"),
+            ';' => out.push_str("#59;"),
+            '\r' | '\n' => out.push(' '),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+const HTML_STYLE: &str = "\
+body { font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; margin: 2em; }
+h1 { font-size: 1.2em; }
+details { margin: 0.2em 0; }
+details details { margin-left: 1.5em; padding-left: 0.5em; border-left: 1px solid #ccc; }
+summary { cursor: pointer; white-space: nowrap; }
+.name { font-weight: bold; }
+.outcome { color: #fff; padding: 0 0.3em; border-radius: 3px; }
+.failed > summary .outcome { background: #c62828; }
+.truncated > summary .outcome { background: #ef6c00; }
+.cu { color: #555; }
+.bar { display: inline-block; width: 10em; height: 0.7em; margin-left: 0.5em; background: #eee; }
+.bar > span { display: block; height: 100%; background: #1e88e5; }
+.logs { margin: 0.2em 0 0.2em 1.5em; padding: 0; list-style: none; color: #444; }
+.logs .data { color: #6a1b9a; }
+";
+
+/// Self-contained HTML page: one collapsible `<details>` per frame, with
+/// the frame's logs and a CU bar scaled against the transaction's BPF CU
+/// (see [`transaction_total_cu`]). Inline CSS, no scripts, so the file can
+/// be uploaded as a CI artifact and opened as is.
+pub fn format_cpi_tree_html(title: &str, frames: &[CpiFrame]) -> String {
+    format_cpi_tree_html_with(title, frames, &|addr| addr.to_string())
+}
+
+/// Like [`format_cpi_tree_html`], but the caller supplies each frame's
+/// program label, as in [`crate::format_cpi_tree_with`].
+pub fn format_cpi_tree_html_with(
+    title: &str,
+    frames: &[CpiFrame],
+    program_label: &dyn Fn(&Address) -> String,
+) -> String {
+    let title = html_escape(title);
+    let total = transaction_total_cu(frames);
+
+    let mut out = String::new();
+    writeln!(out, "<!DOCTYPE html>").unwrap();
+    writeln!(out, "<html lang=\"en\">").unwrap();
+    writeln!(out, "<head>").unwrap();
+    writeln!(out, "<meta charset=\"utf-8\">").unwrap();
+    writeln!(out, "<title>{title}</title>").unwrap();
+    writeln!(out, "<style>\n{HTML_STYLE}</style>").unwrap();
+    writeln!(out, "</head>").unwrap();
+    writeln!(out, "<body>").unwrap();
+    writeln!(out, "<h1>{title}</h1>").unwrap();
+    match (total, transaction_compute_budget(frames)) {
+        (Some(total), Some(budget)) => writeln!(
+            out,
+            "<p>{} BPF CU / {} budget</p>",
+            with_commas(total),
+            with_commas(budget)
+        )
+        .unwrap(),
+        _ => writeln!(out, "<p>No compute units in logs</p>").unwrap(),
+    }
+    for frame in frames {
+        write_html_frame(&mut out, frame, total, program_label);
+    }
+    writeln!(out, "</body>").unwrap();
+    writeln!(out, "</html>").unwrap();
+    out
+}
+
+fn write_html_frame(
+    out: &mut String,
+    frame: &CpiFrame,
+    total: Option<u64>,
+    program_label: &dyn Fn(&Address) -> String,
+) {
+    let class = match frame.outcome {
+        CpiOutcome::Success => "success",
+        CpiOutcome::Failed { .. } => "failed",
+        CpiOutcome::Truncated => "truncated",
+    };
+    writeln!(out, "<details open class=\"{class}\">").unwrap();
+    write!(out, "<summary>").unwrap();
+    match &frame.outcome {
+        CpiOutcome::Success => {}
+        CpiOutcome::Failed { message } => {
+            write!(
+                out,
+                "<span class=\"outcome\">FAILED: {}</span> ",
+                html_escape(message.as_deref().unwrap_or(""))
+            )
+            .unwrap();
+        }
+        CpiOutcome::Truncated => write!(out, "<span class=\"outcome\">TRUNCATED</span> ").unwrap(),
+    }
+    if let Some(name) = &frame.instruction_name {
+        write!(out, "<span class=\"name\">{}</span> ", html_escape(name)).unwrap();
+    }
+    write!(
+        out,
+        "<code>{}</code>",
+        html_escape(&program_label(&frame.program_id))
+    )
+    .unwrap();
+    if let Some(cu) = frame.compute_units {
+        write!(
+            out,
+            " <span class=\"cu\">{} / {} CU</span>",
+            with_commas(cu.consumed),
+            with_commas(cu.available_at_start)
+        )
+        .unwrap();
+        if let Some(total) = total.filter(|total| *total > 0) {
+            // A CPI child can't out-consume the roots, but a hand-built
+            // tree can; clamp so the bar stays inside its track.
+            let percent = (cu.consumed as f64 * 100.0 / total as f64).min(100.0);
+            write!(
+                out,
+                "<span class=\"bar\" title=\"{percent:.1}% of transaction CU\">\
+                 <span style=\"width: {percent:.1}%\"></span></span>"
+            )
+            .unwrap();
+        }
+    }
+    writeln!(out, "</summary>").unwrap();
+
+    if !frame.logs.is_empty() {
+        writeln!(out, "<ul class=\"logs\">").unwrap();
+        for entry in &frame.logs {
+            match entry {
+                FrameLog::Msg(text) => {
+                    writeln!(out, "<li class=\"msg\">log: {}</li>", html_escape(text)).unwrap();
+                }
+                FrameLog::Data(payload) => {
+                    writeln!(
+                        out,
+                        "<li class=\"data\">data: {}</li>",
+                        html_escape(payload)
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        writeln!(out, "</ul>").unwrap();
+    }
+    for child in &frame.children {
+        write_html_frame(out, child, total, program_label);
+    }
+    writeln!(out, "</details>").unwrap();
+}
+
+fn html_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::cpi_tree, solana_address::address};
+
+    const PROG_A: Address = address!("GtdambwDgHWrDJdVPBkEHGhCwokqgAoch162teUjJse2");
+    const TOKEN_PROG: Address = address!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+    // Root `PROG_A` calls the token program twice; the second call fails
+    // and takes the root down with it.
+    fn failing_tree() -> Vec<CpiFrame> {
+        let logs = [
+            format!("Program {PROG_A} invoke [1]"),
+            "Program log: Instruction: Swap".to_string(),
+            "Program log: routing <a & b>".to_string(),
+            format!("Program {TOKEN_PROG} invoke [2]"),
+            "Program log: Instruction: Transfer".to_string(),
+            format!("Program {TOKEN_PROG} consumed 4645 of 190000 compute units"),
+            format!("Program {TOKEN_PROG} success"),
+            format!("Program {TOKEN_PROG} invoke [2]"),
+            "Program log: Instruction: Transfer".to_string(),
+            "Program log: Error: insufficient funds".to_string(),
+            format!("Program {TOKEN_PROG} consumed 3000 of 180000 compute units"),
+            format!("Program {TOKEN_PROG} failed: custom program error: 0x1"),
+            format!("Program {PROG_A} consumed 20000 of 200000 compute units"),
+            format!("Program {PROG_A} failed: custom program error: 0x1"),
+        ];
+        cpi_tree(&logs)
+    }
+
+    #[test]
+    fn mermaid_draws_calls_and_replies() {
+        let rendered = format_cpi_tree_mermaid_with(&failing_tree(), &|addr| {
+            if *addr == TOKEN_PROG {
+                "token".to_string()
+            } else {
+                "amm".to_string()
+            }
+        });
+        assert_eq!(
+            rendered,
+            "sequenceDiagram
+    participant tx as Transaction
+    participant p0 as amm
+    participant p1 as token
+    tx->>p0: Swap (20,000 CU)
+    activate p0
+    p0->>p1: Transfer (4,645 CU)
+    activate p1
+    p1-->>p0: success
+    deactivate p1
+    p0->>p1: Transfer (3,000 CU)
+    activate p1
+    p1--xp0: FAILED: custom program error: 0x1
+    deactivate p1
+    p0--xtx: FAILED: custom program error: 0x1
+    deactivate p0
+"
+        );
+    }
+
+    #[test]
+    fn mermaid_notes_truncated_frames_and_escapes_text() {
+        let frames = vec![CpiFrame {
+            program_id: PROG_A,
+            outcome: CpiOutcome::Truncated,
+            compute_units: None,
+            instruction_name: Some("Pay #1; then exit".to_string()),
+            logs: Vec::new(),
+            children: Vec::new(),
+            instruction_data: None,
+        }];
+        let rendered = format_cpi_tree_mermaid(&frames);
+        assert!(
+            rendered.contains("tx->>p0: Pay #35;1#59; then exit\n"),
+            "unexpected call line: {rendered}"
+        );
+        assert!(
+            rendered.contains("    Note over p0: TRUNCATED\n    deactivate p0\n"),
+            "missing truncation note: {rendered}"
+        );
+    }
+
+    #[test]
+    fn html_nests_frames_with_logs_and_cu_bars() {
+        let rendered = format_cpi_tree_html("swap <test>", &failing_tree());
+        assert!(rendered.starts_with("<!DOCTYPE html>\n"));
+        assert!(rendered.contains("<title>swap &lt;test&gt;</title>"));
+        assert!(rendered.contains("<p>20,000 BPF CU / 200,000 budget</p>"));
+        assert_eq!(rendered.matches("<details open").count(), 3);
+        assert_eq!(rendered.matches("</details>").count(), 3);
+        assert_eq!(
+            rendered.matches("<details open class=\"failed\">").count(),
+            2
+        );
+        assert!(rendered.contains("<li class=\"msg\">log: routing &lt;a &amp; b&gt;</li>"));
+        assert!(rendered.contains("<li class=\"msg\">log: Error: insufficient funds</li>"));
+        // Bars are scaled against the transaction's BPF CU.
+        assert!(rendered.contains("<span style=\"width: 100.0%\">"));
+        assert!(rendered.contains("<span style=\"width: 23.2%\">"));
+        assert!(rendered.contains("<span style=\"width: 15.0%\">"));
+        // The second transfer closes right before the root does.
+        assert!(rendered.ends_with("</details>\n</details>\n</body>\n</html>\n"));
+    }
+
+    #[test]
+    fn html_without_cu_has_no_bars() {
+        let frames = vec![CpiFrame {
+            program_id: PROG_A,
+            outcome: CpiOutcome::Success,
+            compute_units: None,
+            instruction_name: None,
+            logs: Vec::new(),
+            children: Vec::new(),
+            instruction_data: None,
+        }];
+        let rendered = format_cpi_tree_html("tx", &frames);
+        assert!(rendered.contains("<p>No compute units in logs</p>"));
+        assert!(!rendered.contains("class=\"bar\""));
+        assert!(rendered.contains(&format!("<code>{PROG_A}</code>")));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trips_with_readable_fields() {
+        use crate::ComputeUnits;
+
+        let mut frames = failing_tree();
+        frames.push(CpiFrame {
+            program_id: TOKEN_PROG,
+            outcome: CpiOutcome::Truncated,
+            compute_units: Some(ComputeUnits {
+                consumed: 1,
+                available_at_start: 2,
+            }),
+            instruction_name: None,
+            logs: vec![FrameLog::Data("AQID".to_string())],
+            children: Vec::new(),
+            instruction_data: Some(vec![1, 2, 3]),
+        });
+        let json = format_cpi_tree_json(&frames);
+        assert!(json.contains(&format!("\"program_id\": \"{PROG_A}\"")));
+        assert!(json.contains("\"status\": \"failed\""));
+        assert!(json.contains("\"message\": \"custom program error: 0x1\""));
+        assert!(json.contains("\"status\": \"truncated\""));
+        assert!(json.contains("\"kind\": \"data\""));
+
+        let parsed: Vec<CpiFrame> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, frames);
+    }
+}
@@ -0,0 +1,17 @@
+//! `#[serde(with = "serde_address")]` for `Address` fields: base58 strings
+//! in both directions, the same text the runtime prints in `invoke` lines.
+
+use {
+    serde::{de::Error, Deserialize, Deserializer, Serializer},
+    solana_address::Address,
+    std::str::FromStr,
+};
+
+pub fn serialize<S: Serializer>(address: &Address, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(address)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Address, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    Address::from_str(&s).map_err(Error::custom)
+}
@@ -0,0 +1,353 @@
+//! Where the compute units go. A frame's `consumed` is inclusive of its
+//! CPIs, so summing it over a tree counts every nested call once per
+//! ancestor. Self CU (inclusive minus the children's inclusive) counts each
+//! unit once, which is what a hotspot table across many transactions needs.
+
+use {
+    crate::{with_commas, CpiFrame},
+    solana_address::Address,
+    std::{cmp::Reverse, collections::BTreeMap, fmt::Write},
+};
+
+impl CpiFrame {
+    /// CU consumed by this frame, including its CPIs. `None` if the program
+    /// didn't report CU (builtins).
+    pub fn inclusive_compute_units(&self) -> Option<u64> {
+        self.compute_units.map(|cu| cu.consumed)
+    }
+
+    /// CU consumed by this frame's own code: inclusive CU minus the
+    /// inclusive CU of its children. Builtin children don't report CU, so
+    /// their cost stays in the caller's self CU.
+    pub fn self_compute_units(&self) -> Option<u64> {
+        let children: u64 = self
+            .children
+            .iter()
+            .filter_map(CpiFrame::inclusive_compute_units)
+            .sum();
+        Some(self.inclusive_compute_units()?.saturating_sub(children))
+    }
+}
+
+/// Total and nearest-rank percentiles of a set of CU samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Distribution {
+    pub total: u64,
+    pub p50: u64,
+    pub p99: u64,
+}
+
+impl Distribution {
+    fn from_samples(mut samples: Vec<u64>) -> Self {
+        samples.sort_unstable();
+        let percentile = |p: usize| {
+            // Nearest rank: the smallest sample with at least p% of the
+            // samples at or below it.
+            let rank = (samples.len() * p).div_ceil(100).max(1);
+            samples.get(rank - 1).copied().unwrap_or_default()
+        };
+        Self {
+            total: samples.iter().sum(),
+            p50: percentile(50),
+            p99: percentile(99),
+        }
+    }
+}
+
+/// One row of a [`ComputeUnitSummary`]: a program, or one instruction of a
+/// program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComputeUnitStats {
+    pub program_id: Address,
+    /// `None` in per-program rows, and for frames whose logs carry no
+    /// instruction name in per-instruction rows.
+    pub instruction_name: Option<String>,
+    /// Invocations that reported CU.
+    pub count: usize,
+    pub self_cu: Distribution,
+    /// Per-invocation inclusive CU. The total counts a recursive call once
+    /// per enclosing frame of the same program.
+    pub inclusive_cu: Distribution,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Samples {
+    self_cu: Vec<u64>,
+    inclusive_cu: Vec<u64>,
+}
+
+impl Samples {
+    fn extend(&mut self, other: &Samples) {
+        self.self_cu.extend(&other.self_cu);
+        self.inclusive_cu.extend(&other.inclusive_cu);
+    }
+}
+
+/// CU per program and per instruction name, accumulated over any number of
+/// transactions' CPI trees: add each test's tree, then print the table at
+/// the end to see which program dominates the suite's cost.
+///
+/// ```text
+/// let mut summary = ComputeUnitSummary::default();
+/// summary.add(&meta.cpi_tree());
+/// println!("{}", format_compute_unit_summary(&summary));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ComputeUnitSummary {
+    samples: BTreeMap<(Address, Option<String>), Samples>,
+}
+
+impl ComputeUnitSummary {
+    /// Adds every frame of one transaction's tree. Frames without CU are
+    /// skipped.
+    pub fn add(&mut self, frames: &[CpiFrame]) {
+        for frame in frames {
+            if let (Some(self_cu), Some(inclusive_cu)) =
+                (frame.self_compute_units(), frame.inclusive_compute_units())
+            {
+                let samples = self
+                    .samples
+                    .entry((frame.program_id, frame.instruction_name.clone()))
+                    .or_default();
+                samples.self_cu.push(self_cu);
+                samples.inclusive_cu.push(inclusive_cu);
+            }
+            self.add(&frame.children);
+        }
+    }
+
+    /// One row per program, highest total self CU first.
+    pub fn by_program(&self) -> Vec<ComputeUnitStats> {
+        let mut programs: BTreeMap<Address, Samples> = BTreeMap::new();
+        for ((program_id, _), samples) in &self.samples {
+            programs.entry(*program_id).or_default().extend(samples);
+        }
+        sorted_rows(
+            programs
+                .into_iter()
+                .map(|(program_id, samples)| stats(program_id, None, samples)),
+        )
+    }
+
+    /// One row per program and instruction name, highest total self CU
+    /// first.
+    pub fn by_instruction(&self) -> Vec<ComputeUnitStats> {
+        sorted_rows(
+            self.samples.iter().map(|((program_id, name), samples)| {
+                stats(*program_id, name.clone(), samples.clone())
+            }),
+        )
+    }
+}
+
+fn stats(
+    program_id: Address,
+    instruction_name: Option<String>,
+    samples: Samples,
+) -> ComputeUnitStats {
+    ComputeUnitStats {
+        program_id,
+        instruction_name,
+        count: samples.self_cu.len(),
+        self_cu: Distribution::from_samples(samples.self_cu),
+        inclusive_cu: Distribution::from_samples(samples.inclusive_cu),
+    }
+}
+
+fn sorted_rows(rows: impl Iterator<Item = ComputeUnitStats>) -> Vec<ComputeUnitStats> {
+    let mut rows: Vec<_> = rows.collect();
+    // Stable sort: ties keep the map's program id order.
+    rows.sort_by_key(|row| Reverse(row.self_cu.total));
+    rows
+}
+
+/// Per-program and per-instruction tables of `summary`, hottest first.
+pub fn format_compute_unit_summary(summary: &ComputeUnitSummary) -> String {
+    format_compute_unit_summary_with(summary, &|addr| addr.to_string())
+}
+
+/// Like [`format_compute_unit_summary`], but the caller supplies how each
+/// program id is rendered, as in [`crate::format_cpi_tree_with`].
+pub fn format_compute_unit_summary_with(
+    summary: &ComputeUnitSummary,
+    program_label: &dyn Fn(&Address) -> String,
+) -> String {
+    let programs = summary.by_program();
+    let program_names = programs
+        .iter()
+        .map(|row| program_label(&row.program_id))
+        .collect();
+    let instructions = summary.by_instruction();
+    let instruction_names = instructions
+        .iter()
+        .map(|row| {
+            let name = row.instruction_name.as_deref().unwrap_or("(unnamed)");
+            format!("{} {name}", program_label(&row.program_id))
+        })
+        .collect();
+
+    let mut out = String::new();
+    write_table(&mut out, "program", &programs, program_names);
+    writeln!(out).unwrap();
+    write_table(&mut out, "instruction", &instructions, instruction_names);
+    out
+}
+
+/// `names` holds the first column of each row.
+fn write_table(out: &mut String, title: &str, rows: &[ComputeUnitStats], names: Vec<String>) {
+    let width = names
+        .iter()
+        .map(|name| name.chars().count())
+        .chain([title.len()])
+        .max()
+        .unwrap_or_default();
+
+    writeln!(
+        out,
+        "{title:<width$}  {:>7}  {:>12}  {:>10}  {:>10}  {:>12}",
+        "count", "self total", "self p50", "self p99", "incl. total"
+    )
+    .unwrap();
+    for (name, row) in names.iter().zip(rows) {
+        writeln!(
+            out,
+            "{name:<width$}  {:>7}  {:>12}  {:>10}  {:>10}  {:>12}",
+            row.count,
+            with_commas(row.self_cu.total),
+            with_commas(row.self_cu.p50),
+            with_commas(row.self_cu.p99),
+            with_commas(row.inclusive_cu.total),
+        )
+        .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{ComputeUnits, CpiOutcome},
+        solana_address::address,
+    };
+
+    const PROG_A: Address = address!("GtdambwDgHWrDJdVPBkEHGhCwokqgAoch162teUjJse2");
+    const TOKEN_PROG: Address = address!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+    const SYSTEM_PROG: Address = address!("11111111111111111111111111111111");
+
+    fn frame(
+        program_id: Address,
+        name: Option<&str>,
+        cu: Option<u64>,
+        children: Vec<CpiFrame>,
+    ) -> CpiFrame {
+        CpiFrame {
+            program_id,
+            outcome: CpiOutcome::Success,
+            compute_units: cu.map(|consumed| ComputeUnits {
+                consumed,
+                available_at_start: 200_000,
+            }),
+            instruction_name: name.map(str::to_string),
+            logs: Vec::new(),
+            children,
+            instruction_data: None,
+        }
+    }
+
+    // A swap that transfers twice and creates an account through the
+    // system program, which reports no CU.
+    fn swap(transfer_cu: u64) -> CpiFrame {
+        frame(
+            PROG_A,
+            Some("Swap"),
+            Some(20_000),
+            vec![
+                frame(TOKEN_PROG, Some("Transfer"), Some(transfer_cu), vec![]),
+                frame(SYSTEM_PROG, None, None, vec![]),
+                frame(TOKEN_PROG, Some("Transfer"), Some(transfer_cu), vec![]),
+            ],
+        )
+    }
+
+    #[test]
+    fn self_cu_subtracts_children_with_cu() {
+        let root = swap(4_000);
+        assert_eq!(root.inclusive_compute_units(), Some(20_000));
+        assert_eq!(root.self_compute_units(), Some(12_000));
+        assert_eq!(root.children[0].self_compute_units(), Some(4_000));
+        assert_eq!(root.children[1].self_compute_units(), None);
+    }
+
+    #[test]
+    fn summary_aggregates_across_transactions() {
+        let mut summary = ComputeUnitSummary::default();
+        summary.add(&[swap(4_000)]);
+        summary.add(&[swap(5_000)]);
+        summary.add(&[frame(TOKEN_PROG, Some("MintTo"), Some(3_000), vec![])]);
+
+        let programs = summary.by_program();
+        assert_eq!(programs.len(), 2);
+        assert_eq!(programs[0].program_id, PROG_A);
+        assert_eq!(programs[0].self_cu.total, 12_000 + 10_000);
+        assert_eq!(programs[0].inclusive_cu.total, 40_000);
+        assert_eq!(programs[1].program_id, TOKEN_PROG);
+        assert_eq!(programs[1].count, 5);
+        assert_eq!(
+            programs[1].self_cu,
+            Distribution {
+                total: 21_000,
+                p50: 4_000,
+                p99: 5_000,
+            }
+        );
+
+        let instructions = summary.by_instruction();
+        let transfer = instructions
+            .iter()
+            .find(|row| row.instruction_name.as_deref() == Some("Transfer"))
+            .unwrap();
+        assert_eq!(transfer.count, 4);
+        assert_eq!(transfer.self_cu.total, 18_000);
+        assert_eq!(instructions.len(), 3);
+    }
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let samples = (1..=200).collect();
+        assert_eq!(
+            Distribution::from_samples(samples),
+            Distribution {
+                total: 20_100,
+                p50: 100,
+                p99: 198,
+            }
+        );
+        assert_eq!(
+            Distribution::from_samples(vec![7]),
+            Distribution {
+                total: 7,
+                p50: 7,
+                p99: 7,
+            }
+        );
+    }
+
+    #[test]
+    fn format_renders_hottest_rows_first() {
+        let mut summary = ComputeUnitSummary::default();
+        summary.add(&[swap(4_000)]);
+        let label = |addr: &Address| if *addr == PROG_A { "amm" } else { "token" }.to_string();
+        assert_eq!(
+            format_compute_unit_summary_with(&summary, &label),
+            "\
+program    count    self total    self p50    self p99   incl. total
+amm            1        12,000      12,000      12,000        20,000
+token          2         8,000       4,000       4,000         8,000
+
+instruction       count    self total    self p50    self p99   incl. total
+amm Swap              1        12,000      12,000      12,000        20,000
+token Transfer        2         8,000       4,000       4,000         8,000
+"
+        );
+    }
+}
@@ -13,8 +13,11 @@ use {
     solana_clock::Clock,
     solana_keypair::Keypair,
     solana_message::{Instruction, Message},
+    solana_program_pack::Pack,
     solana_signer::Signer,
     solana_transaction::Transaction,
+    spl_associated_token_account_interface::instruction::create_associated_token_account,
+    spl_token_interface::state::Mint,
     std::path::PathBuf,
 };
 
@@ -151,3 +154,69 @@ fn fixture_clock_example() {
     assert_eq!(frames[0].outcome, CpiOutcome::Success);
     dump("clock-example (success)", tree);
 }
+
+#[test]
+fn trace_tree_survives_log_truncation() {
+    // The bundled ATA program CPIs into the token and system programs, so
+    // no fixture build is needed. The log limit only lets the first
+    // instruction's lines through.
+    let mut svm = LiteSVM::new().with_log_bytes_limit(Some(100));
+    let payer = Keypair::new();
+    let mint = Keypair::new();
+    let ata_program_id = address!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+    svm.airdrop(&payer.pubkey(), 1_000_000_000).unwrap();
+    let instructions = [
+        solana_system_interface::instruction::create_account(
+            &payer.pubkey(),
+            &mint.pubkey(),
+            svm.minimum_balance_for_rent_exemption(Mint::LEN),
+            Mint::LEN as u64,
+            &spl_token_interface::ID,
+        ),
+        spl_token_interface::instruction::initialize_mint2(
+            &spl_token_interface::ID,
+            &mint.pubkey(),
+            &payer.pubkey(),
+            None,
+            6,
+        )
+        .unwrap(),
+        create_associated_token_account(
+            &payer.pubkey(),
+            &payer.pubkey(),
+            &mint.pubkey(),
+            &spl_token_interface::ID,
+        ),
+    ];
+    let tx = Transaction::new(
+        &[&payer, &mint],
+        Message::new(&instructions, Some(&payer.pubkey())),
+        svm.latest_blockhash(),
+    );
+    let meta = svm.send_transaction(tx).unwrap();
+
+    // The logs stop after the first instruction.
+    assert_eq!(meta.cpi_tree().len(), 1);
+
+    let frames = meta.cpi_tree_from_trace();
+    let programs: Vec<_> = frames.iter().map(|frame| frame.program_id).collect();
+    assert_eq!(
+        programs,
+        [
+            solana_system_interface::program::ID,
+            spl_token_interface::ID,
+            ata_program_id
+        ]
+    );
+    let ata = &frames[2];
+    assert_eq!(ata.children.len(), 4);
+    assert!(ata.compute_units.is_some());
+    for (child, cpi) in ata.children.iter().zip(&meta.inner_instructions[2]) {
+        assert_eq!(child.outcome, CpiOutcome::Success);
+        assert_eq!(child.instruction_data.as_ref(), Some(&cpi.instruction.data));
+    }
+    dump(
+        "create ATA (from trace)",
+        litesvm_cpi_tree::format_cpi_tree("CPI Tree:", &frames),
+    );
+}
@@ -5,7 +5,7 @@ use std::collections::HashMap;
 use {
     crate::error::{InvalidSysvarDataError, LiteSVMError},
     log::error,
-    solana_account::{AccountSharedData, ReadableAccount, WritableAccount},
+    solana_account::{Account, AccountSharedData, ReadableAccount, WritableAccount},
     solana_address::Address,
     solana_address_lookup_table_interface::{error::AddressLookupError, state::AddressLookupTable},
     solana_clock::Clock,
@@ -39,7 +39,11 @@ use {
     solana_system_program::{get_system_account_kind, SystemAccountKind},
     solana_sysvar::Sysvar,
     solana_transaction_error::{AddressLoaderError, TransactionError},
-    std::sync::Arc,
+    std::{
+        collections::HashSet,
+        ops::Range,
+        sync::{Arc, OnceLock},
+    },
     wincode::DeserializeOwned,
 };
 
@@ -69,17 +73,93 @@ where
     Ok(())
 }
 
+/// Accounts that are indexed up front but whose data stays in a shared byte
+/// buffer (typically a memory-mapped snapshot) until it is first read.
+///
+/// Caches are rebuilt from the eagerly stored accounts only, so sysvar and
+/// program accounts must not be registered here.
+pub struct LazyAccounts {
+    bytes: Box<dyn AsRef<[u8]> + Send + Sync>,
+    index: HashMap<Address, LazyAccount>,
+}
+
+struct LazyAccount {
+    /// Everything but the data, which is `bytes[data]`.
+    header: Account,
+    data: Range<usize>,
+    materialized: OnceLock<AccountSharedData>,
+}
+
+#[cfg(feature = "persistence-internal")]
+impl LazyAccounts {
+    pub fn new(bytes: impl AsRef<[u8]> + Send + Sync + 'static) -> Self {
+        Self {
+            bytes: Box::new(bytes),
+            index: HashMap::default(),
+        }
+    }
+
+    /// Registers the account at `address`, whose data is `bytes[data]`. The
+    /// data of `header` is ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is out of bounds of the buffer.
+    pub fn insert(&mut self, address: Address, mut header: Account, data: Range<usize>) {
+        assert!(
+            data.start <= data.end && data.end <= (*self.bytes).as_ref().len(),
+            "lazy account data out of bounds"
+        );
+        header.data = Vec::new();
+        self.index.insert(
+            address,
+            LazyAccount {
+                header,
+                data,
+                materialized: OnceLock::new(),
+            },
+        );
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+}
+
+impl LazyAccounts {
+    fn get(&self, address: &Address) -> Option<&AccountSharedData> {
+        let entry = self.index.get(address)?;
+        Some(entry.materialized.get_or_init(|| {
+            let mut account = AccountSharedData::from(entry.header.clone());
+            account.set_data_from_slice(&(*self.bytes).as_ref()[entry.data.clone()]);
+            account
+        }))
+    }
+}
+
 pub struct AccountsDb {
-    pub inner: HashMap<Address, AccountSharedData>,
+    /// Accounts held in memory. With [`LazyAccounts`] attached this only
+    /// holds the accounts written or read mutably since loading, so it is
+    /// read through [`AccountsDb::get_account_ref`] and [`AccountsDb::iter`].
+    inner: HashMap<Address, AccountSharedData>,
     pub programs_cache: ProgramCacheForTxBatch,
     pub sysvar_cache: SysvarCache,
     pub environments: ProgramRuntimeEnvironments,
+    lazy: Option<Arc<LazyAccounts>>,
+    /// Lazily loaded accounts that have since been closed.
+    removed_lazy: HashSet<Address>,
 }
 
 impl Clone for AccountsDb {
     fn clone(&self) -> Self {
         Self {
             inner: self.inner.clone(),
+            lazy: self.lazy.clone(),
+            removed_lazy: self.removed_lazy.clone(),
             programs_cache: self.programs_cache.clone(),
             sysvar_cache: self.sysvar_cache.clone(),
             environments: ProgramRuntimeEnvironments::new(
@@ -98,6 +178,8 @@ impl Default for AccountsDb {
 
         Self {
             inner: HashMap::default(),
+            lazy: None,
+            removed_lazy: HashSet::new(),
             programs_cache: ProgramCacheForTxBatch::new(0),
             sysvar_cache: SysvarCache::default(),
             environments: ProgramRuntimeEnvironments::new(env.clone(), env),
@@ -107,7 +189,64 @@ impl Default for AccountsDb {
 
 impl AccountsDb {
     pub fn get_account_ref(&self, pubkey: &Address) -> Option<&AccountSharedData> {
-        self.inner.get(pubkey)
+        self.inner.get(pubkey).or_else(|| self.get_lazy(pubkey))
+    }
+
+    fn get_lazy(&self, pubkey: &Address) -> Option<&AccountSharedData> {
+        if self.removed_lazy.contains(pubkey) {
+            return None;
+        }
+        self.lazy.as_ref()?.get(pubkey)
+    }
+
+    /// Lazily loaded accounts that are not shadowed by `inner` or removed,
+    /// with their headers.
+    fn lazy_headers(&self) -> impl Iterator<Item = (&Address, &Account)> {
+        self.lazy
+            .iter()
+            .flat_map(|lazy| lazy.index.iter())
+            .filter(|(address, _)| {
+                !self.inner.contains_key(*address) && !self.removed_lazy.contains(*address)
+            })
+            .map(|(address, entry)| (address, &entry.header))
+    }
+
+    /// Whether the account at `pubkey` is held in memory, as opposed to
+    /// missing or only present in the attached [`LazyAccounts`].
+    #[cfg(feature = "persistence-internal")]
+    pub fn is_materialized(&self, pubkey: &Address) -> bool {
+        self.inner.contains_key(pubkey)
+    }
+
+    /// Iterates over all accounts, materializing lazily loaded ones.
+    pub fn iter(&self) -> impl Iterator<Item = (&Address, &AccountSharedData)> {
+        self.inner.iter().chain(
+            self.lazy_headers()
+                .filter_map(|(address, _)| Some((address, self.get_lazy(address)?))),
+        )
+    }
+
+    /// Iterates over the accounts owned by `owner`. Lazily loaded accounts
+    /// are only materialized if they match.
+    pub fn iter_owned_by<'a>(
+        &'a self,
+        owner: &'a Address,
+    ) -> impl Iterator<Item = (&'a Address, &'a AccountSharedData)> {
+        self.inner
+            .iter()
+            .filter(move |(_, account)| account.owner() == owner)
+            .chain(
+                self.lazy_headers()
+                    .filter(move |(_, header)| header.owner == *owner)
+                    .filter_map(|(address, _)| Some((address, self.get_lazy(address)?))),
+            )
+    }
+
+    /// Attaches lazily loaded accounts, replacing any attached before.
+    #[cfg(feature = "persistence-internal")]
+    pub(crate) fn set_lazy_accounts(&mut self, lazy: LazyAccounts) {
+        self.lazy = Some(Arc::new(lazy));
+        self.removed_lazy.clear();
     }
 
     pub fn get_account(&self, pubkey: &Address) -> Option<AccountSharedData> {
@@ -137,6 +276,13 @@ impl AccountsDb {
         }
         if account.lamports() == 0 {
             self.inner.remove(&pubkey);
+            if self
+                .lazy
+                .as_ref()
+                .is_some_and(|lazy| lazy.index.contains_key(&pubkey))
+            {
+                self.removed_lazy.insert(pubkey);
+            }
         } else {
             self.add_account_no_checks(pubkey, account);
         }
@@ -282,10 +428,13 @@ impl AccountsDb {
         &mut self,
         mut accounts: Vec<(Address, AccountSharedData)>,
     ) -> Result<(), LiteSVMError> {
-        // need to add programdata accounts first if there are any
+        // Add programdata and buffer accounts before program accounts, so
+        // programs are loaded against their new programdata. This includes
+        // programdata just closed by the loader: it no longer starts with the
+        // `ProgramData` tag, and loading the program before removing it would
+        // leave the old code in the cache.
         itertools::partition(&mut accounts, |x| {
-            x.1.owner() == &bpf_loader_upgradeable::id()
-                && x.1.data().first().is_some_and(|byte| *byte == 3)
+            x.1.owner() == &bpf_loader_upgradeable::id() && !x.1.executable()
         });
         for (address, acc) in accounts {
             self.add_account(address, acc)?;
@@ -420,6 +569,11 @@ impl AccountsDb {
         address: &Address,
         lamports: u64,
     ) -> solana_transaction_error::TransactionResult<()> {
+        if !self.inner.contains_key(address) {
+            if let Some(account) = self.get_lazy(address).cloned() {
+                self.inner.insert(*address, account);
+            }
+        }
         match self.inner.get_mut(address) {
             Some(account) => {
                 let min_balance = match get_system_account_kind(account) {
@@ -0,0 +1,196 @@
+//! Command-line tools for register traces written by
+//! `DefaultRegisterTracingCallback`.
+
+use {
+    litesvm::{
+        register_tracing::DefaultRegisterTracingCallback,
+        register_tracing_filter::{eval, expr},
+        register_tracing_format::{read_manifest, read_registers, ManifestEntry},
+        LiteSVM,
+    },
+    solana_address::Address,
+    solana_program_runtime::invoke_context::Executable,
+    std::{
+        error::Error,
+        io::Write,
+        path::{Path, PathBuf},
+        process::ExitCode,
+    },
+};
+
+const USAGE: &str = "\
+usage: litesvm-trace <command> [--dir <trace dir>] [args]
+
+The trace directory defaults to $SBF_TRACE_DIR, or target/sbf/trace.
+
+commands:
+    list [--filter <expr>]
+        list the traces in the order they were written, optionally filtered
+        with the SBF_TRACE_FILTER language
+    registers <trace>
+        print the registers of every step of a trace, named by its file
+    disassemble <trace>
+        disassemble a trace with the copy of its program kept alongside it
+";
+
+const DEFAULT_DIR: &str = "target/sbf/trace";
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let dir = match args.iter().position(|arg| *arg == "--dir") {
+        Some(index) if index + 1 < args.len() => {
+            let dir = PathBuf::from(args[index + 1]);
+            args.drain(index..index + 2);
+            dir
+        }
+        Some(_) => return usage(),
+        None => std::env::var("SBF_TRACE_DIR")
+            .unwrap_or(DEFAULT_DIR.to_string())
+            .into(),
+    };
+    let result = match args.as_slice() {
+        ["list"] => list(&dir, None),
+        ["list", "--filter", filter] => list(&dir, Some(filter)),
+        ["registers", trace] => registers(&dir, trace),
+        ["disassemble", trace] => disassemble(&dir, trace),
+        ["-h" | "--help" | "help"] => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        _ => return usage(),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn usage() -> ExitCode {
+    eprint!("{USAGE}");
+    ExitCode::from(2)
+}
+
+fn list(dir: &Path, filter: Option<&str>) -> Result<()> {
+    let filter = filter
+        .map(|filter| expr(filter).map_err(|err| format!("invalid filter: {err}")))
+        .transpose()?;
+    let mut rows = vec![[
+        "signature",
+        "ix",
+        "depth",
+        "program",
+        "caller",
+        "outcome",
+        "steps",
+        "test",
+        "file",
+    ]
+    .map(str::to_string)];
+    for entry in read_manifest(dir)? {
+        if filter
+            .as_ref()
+            .is_some_and(|filter| !eval(filter, &entry.filter_row()))
+        {
+            continue;
+        }
+        // Programs are shown by label when they have one.
+        let name = |address: &Address, label: &Option<String>| {
+            label.clone().unwrap_or_else(|| address.to_string())
+        };
+        rows.push([
+            entry.signature.clone(),
+            entry.instruction_index.to_string(),
+            entry.depth.to_string(),
+            name(&entry.program_id, &entry.program_label),
+            entry.caller.map_or_else(
+                || "-".to_string(),
+                |caller| name(&caller, &entry.caller_label),
+            ),
+            entry.outcome().unwrap_or("-").to_string(),
+            entry.steps.to_string(),
+            entry.test.clone().unwrap_or_else(|| "-".to_string()),
+            entry.file,
+        ]);
+    }
+
+    let mut widths = [0; 9];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut stdout = std::io::stdout().lock();
+    for row in &rows {
+        let [signature, ix, depth, program, caller, outcome, steps, test, file] = row;
+        writeln!(
+            stdout,
+            "{signature:<w0$}  {ix:<w1$}  {depth:<w2$}  {program:<w3$}  {caller:<w4$}  \
+             {outcome:<w5$}  {steps:>w6$}  {test:<w7$}  {file}",
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+            w3 = widths[3],
+            w4 = widths[4],
+            w5 = widths[5],
+            w6 = widths[6],
+            w7 = widths[7],
+        )?;
+    }
+    Ok(())
+}
+
+fn registers(dir: &Path, trace: &str) -> Result<()> {
+    let entry = find_entry(dir, trace)?;
+    let mut stdout = std::io::stdout().lock();
+    for (step, registers) in read_registers(&dir.join(&entry.file))?.iter().enumerate() {
+        write!(stdout, "{step:>8}")?;
+        for register in registers {
+            write!(stdout, " {register:016x}")?;
+        }
+        writeln!(stdout)?;
+    }
+    Ok(())
+}
+
+fn disassemble(dir: &Path, trace: &str) -> Result<()> {
+    let entry = find_entry(dir, trace)?;
+    let program_path = entry
+        .program_path(dir)
+        .ok_or_else(|| format!("no copy of program {} was kept", entry.program_id))?;
+    let elf = std::fs::read(&program_path)
+        .map_err(|err| format!("can't read {}: {err}", program_path.display()))?;
+    // Load the program as register tracing does, so its text matches the
+    // program counters of the trace.
+    let svm = LiteSVM::new_debuggable(true);
+    let environment = svm.accounts_db().environments.get_env_for_execution();
+    let executable = Executable::load(&elf, (**environment).clone())
+        .map_err(|err| format!("can't load {}: {err}", program_path.display()))?;
+    let registers = read_registers(&dir.join(&entry.file))?;
+    DefaultRegisterTracingCallback::default().disassemble_register_trace(
+        &mut std::io::stdout().lock(),
+        &entry.program_id,
+        &executable,
+        &registers,
+    );
+    Ok(())
+}
+
+/// The manifest entry of the trace named `trace`, by file name, with or
+/// without its extension.
+fn find_entry(dir: &Path, trace: &str) -> Result<ManifestEntry> {
+    read_manifest(dir)?
+        .into_iter()
+        .find(|entry| {
+            entry.file == trace
+                || Path::new(&entry.file)
+                    .file_stem()
+                    .is_some_and(|stem| stem == trace)
+        })
+        .ok_or_else(|| format!("no trace `{trace}` in {}", dir.display()).into())
+}
@@ -1,15 +1,79 @@
-use {ansi_term::Colour, std::fmt::Write};
+//! Colouring and layout of [`TransactionMetadata::pretty_logs`]. Pass
+//! [`PrettyLogsOptions`] to
+//! [`TransactionMetadata::pretty_logs_with`] to pick another theme, add
+//! importance rules, indent CPIs or label programs.
+//!
+//! [`TransactionMetadata::pretty_logs`]: crate::types::TransactionMetadata::pretty_logs
+//! [`TransactionMetadata::pretty_logs_with`]: crate::types::TransactionMetadata::pretty_logs_with
+
+use {
+    crate::{labels::Labels, types::LogEvent, utils::log_events::log_events},
+    ansi_term::Colour,
+    std::fmt::Write,
+};
 
 const PROGRAM_LOG: &str = "Program log:";
+const INDENT: &str = "  ";
 
-#[derive(Debug)]
-enum Importance {
+/// How much a log line matters, from background noise to an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Importance {
     Low,
     High,
     VeryHigh,
     Error,
 }
 
+/// How lines are marked up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Theme {
+    /// Plain text, for CI logs that don't render ANSI escapes.
+    None,
+    /// ANSI colours for terminals.
+    #[default]
+    Ansi,
+    /// A `<pre>` block with one coloured `<span>` per line.
+    Html,
+}
+
+/// Gives `importance` to every line containing `pattern`, ignoring ASCII
+/// case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportanceRule {
+    pub pattern: String,
+    pub importance: Importance,
+}
+
+impl ImportanceRule {
+    pub fn new(pattern: impl Into<String>, importance: Importance) -> Self {
+        Self {
+            pattern: pattern.into(),
+            importance,
+        }
+    }
+
+    fn matches(&self, line: &str) -> bool {
+        line.to_ascii_lowercase()
+            .contains(&self.pattern.to_ascii_lowercase())
+    }
+}
+
+/// Options of [`TransactionMetadata::pretty_logs_with`](crate::types::TransactionMetadata::pretty_logs_with).
+/// The default is what [`pretty_logs`](crate::types::TransactionMetadata::pretty_logs)
+/// prints.
+#[derive(Debug, Clone, Default)]
+pub struct PrettyLogsOptions<'a> {
+    pub theme: Theme,
+    /// Checked in order before the built-in rules, which mark errors and
+    /// program logs. The first rule matching a line decides its importance.
+    pub rules: Vec<ImportanceRule>,
+    /// Indent each line by the CPI depth of the invocation that wrote it.
+    pub indent: bool,
+    /// Replace labeled addresses with their labels. Pass
+    /// [`LiteSVM::labels`](crate::LiteSVM::labels).
+    pub labels: Option<&'a Labels>,
+}
+
 fn get_importance(program_source: &str, program_log: &str) -> Importance {
     let log = program_log.to_lowercase();
     if log.contains("error: ")
@@ -33,17 +97,44 @@ fn get_importance(program_source: &str, program_log: &str) -> Importance {
     }
 }
 
-fn colourise(importance: Importance, log: &str) -> String {
-    match importance {
-        Importance::Error => Colour::Fixed(9).bold().paint(log),
-        Importance::VeryHigh => Colour::Green.paint(log),
-        Importance::High => Colour::Fixed(243).bold().paint(log),
-        Importance::Low => Colour::Fixed(239).paint(log),
+fn render(theme: Theme, importance: Importance, log: &str) -> String {
+    match theme {
+        Theme::None => log.to_string(),
+        Theme::Ansi => match importance {
+            Importance::Error => Colour::Fixed(9).bold().paint(log),
+            Importance::VeryHigh => Colour::Green.paint(log),
+            Importance::High => Colour::Fixed(243).bold().paint(log),
+            Importance::Low => Colour::Fixed(239).paint(log),
+        }
+        .to_string(),
+        // The same colours as the ANSI theme.
+        Theme::Html => {
+            let style = match importance {
+                Importance::Error => "color:#ff0000;font-weight:bold",
+                Importance::VeryHigh => "color:#008000",
+                Importance::High => "color:#767676;font-weight:bold",
+                Importance::Low => "color:#4e4e4e",
+            };
+            format!("<span style=\"{style}\">{}</span>", html_escape(log))
+        }
+    }
+}
+
+fn html_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
     }
-    .to_string()
+    out
 }
 
-fn format_line(line: &str) -> String {
+fn format_line(line: &str, options: &PrettyLogsOptions) -> String {
     const PROGRAM: &str = "Program";
     const PROCESS_INSTRUCTION: &str = "process_instruction:";
     const SOLANA_RUNTIME: &str = "solana_runtime:";
@@ -60,23 +151,78 @@ fn format_line(line: &str) -> String {
         }
         s => ("", s),
     };
-    let importance = get_importance(program_source, program_log);
+    // Judge importance on the raw line so a label can't change it.
+    let importance = options
+        .rules
+        .iter()
+        .find(|rule| rule.matches(line))
+        .map(|rule| rule.importance)
+        .unwrap_or_else(|| get_importance(program_source, program_log));
+    let program_log = match options.labels {
+        Some(labels) => labels.relabel(program_log),
+        None => program_log.to_string(),
+    };
     let log = if ["", PROGRAM_LOG].contains(&program_source) {
-        program_log.to_string()
+        program_log
     } else {
         format!("{program_source} {program_log}")
     };
-    colourise(importance, &log)
+    render(options.theme, importance, &log)
 }
 
-pub(crate) fn format_logs(logs: &[String]) -> String {
+/// The indentation level of each line: an invocation's runtime lines sit
+/// at its depth minus one, the lines its program writes one level deeper.
+fn indent_levels(logs: &[String]) -> Vec<usize> {
+    // Depth of the innermost running invocation.
+    let mut depth = 0usize;
+    log_events(logs)
+        .into_iter()
+        .map(|event| match event {
+            LogEvent::Invoke { depth: d, .. } => {
+                depth = d;
+                d.saturating_sub(1)
+            }
+            LogEvent::Log { depth: d, .. }
+            | LogEvent::Data { depth: d, .. }
+            | LogEvent::ComputeUnitsRemaining { depth: d, .. } => d,
+            LogEvent::Consumed { .. } | LogEvent::ReturnData { .. } => depth.saturating_sub(1),
+            LogEvent::Success { .. } | LogEvent::Failed { .. } => {
+                let level = depth.saturating_sub(1);
+                depth = level;
+                level
+            }
+            LogEvent::Truncated | LogEvent::Other { .. } => depth,
+        })
+        .collect()
+}
+
+pub(crate) fn format_logs(logs: &[String], options: &PrettyLogsOptions) -> String {
+    let mut levels = if options.indent {
+        indent_levels(logs)
+    } else {
+        Vec::new()
+    };
+    // One level per line; anything else would silently drop lines when
+    // zipped, so leave the logs unindented instead.
+    if levels.len() != logs.len() {
+        levels = vec![0; logs.len()];
+    }
     let mut out: String = String::new();
-    for line in logs {
+    if options.theme == Theme::Html {
+        writeln!(&mut out, "<pre class=\"litesvm-logs\">").unwrap();
+    }
+    for (line, level) in logs.iter().zip(levels) {
         if !line.is_empty() {
-            let formatted = format_line(line);
-            writeln!(&mut out, "{formatted}").unwrap();
+            let formatted = format_line(line, options);
+            let indent = INDENT.repeat(level);
+            // A multi-line log keeps its continuation lines at its level.
+            let formatted = formatted.replace('\n', &format!("\n{indent}"));
+            writeln!(&mut out, "{indent}{formatted}").unwrap();
         }
     }
+    if options.theme == Theme::Html {
+        writeln!(&mut out, "</pre>").unwrap();
+    }
     out
 }
 
@@ -95,13 +241,13 @@ mod tests {
     #[test]
     fn test_format_line() {
         let line = "Program 11111111111111111111111111111111 failed: Computational budget exceeded";
-        let formatted = format_line(line);
+        let formatted = format_line(line, &PrettyLogsOptions::default());
         assert_eq!(
             formatted,
             "\u{1b}[1;38;5;9mProgram 11111111111111111111111111111111 failed: Computational budget exceeded\u{1b}[0m"
         );
         let line = "Program log: static string";
-        let formatted = format_line(line);
+        let formatted = format_line(line, &PrettyLogsOptions::default());
         eprintln!("{formatted}");
         assert_eq!(formatted, "\u{1b}[32mstatic string\u{1b}[0m");
     }
@@ -109,10 +255,98 @@ mod tests {
     #[test]
     fn test_format_logs() {
         let logs = ["Program 1111111QLbz7JHiBTspS962RLKV8GndWFwiEaqKM invoke [1]", "Program log: panicked at clock-example/src/lib.rs:17:5:\nassertion failed: got_clock.unix_timestamp < 100", "Program 1111111QLbz7JHiBTspS962RLKV8GndWFwiEaqKM consumed 1751 of 200000 compute units", "Program 1111111QLbz7JHiBTspS962RLKV8GndWFwiEaqKM failed: SBF program panicked"].map(ToString::to_string);
-        let formatted = format_logs(&logs);
+        let formatted = format_logs(&logs, &PrettyLogsOptions::default());
         assert_eq!(
             formatted,
             "\u{1b}[38;5;239mProgram 1111111QLbz7JHiBTspS962RLKV8GndWFwiEaqKM invoke [1]\u{1b}[0m\n\u{1b}[1;38;5;9mpanicked at clock-example/src/lib.rs:17:5:\nassertion failed: got_clock.unix_timestamp < 100\u{1b}[0m\n\u{1b}[38;5;239mProgram 1111111QLbz7JHiBTspS962RLKV8GndWFwiEaqKM consumed 1751 of 200000 compute units\u{1b}[0m\n\u{1b}[1;38;5;9mProgram 1111111QLbz7JHiBTspS962RLKV8GndWFwiEaqKM failed: SBF program panicked\u{1b}[0m\n"
         );
     }
+
+    fn cpi_logs() -> Vec<String> {
+        [
+            "Program GtdambwDgHWrDJdVPBkEHGhCwokqgAoch162teUjJse2 invoke [1]",
+            "Program log: Instruction: Swap",
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA invoke [2]",
+            "Program log: Instruction: Transfer",
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA consumed 4000 of 190000 compute units",
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA success",
+            "Program log: slippage <1%",
+            "Program GtdambwDgHWrDJdVPBkEHGhCwokqgAoch162teUjJse2 success",
+        ]
+        .map(ToString::to_string)
+        .to_vec()
+    }
+
+    #[test]
+    fn test_plain_indented_labeled() {
+        let mut labels = Labels::default();
+        labels.insert(
+            solana_address::address!("GtdambwDgHWrDJdVPBkEHGhCwokqgAoch162teUjJse2"),
+            "amm",
+        );
+        let formatted = format_logs(
+            &cpi_logs(),
+            &PrettyLogsOptions {
+                theme: Theme::None,
+                indent: true,
+                labels: Some(&labels),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            formatted,
+            "\
+Program amm invoke [1]
+  Instruction: Swap
+  Program token invoke [2]
+    Instruction: Transfer
+  Program token consumed 4000 of 190000 compute units
+  Program token success
+  slippage <1%
+Program amm success
+"
+        );
+    }
+
+    #[test]
+    fn test_indent_multiline_and_truncated() {
+        let logs = [
+            "Program 1111111QLbz7JHiBTspS962RLKV8GndWFwiEaqKM invoke [1]",
+            "Program log: panicked at clock-example/src/lib.rs:17:5:\nassertion failed: got_clock.unix_timestamp < 100",
+            "Log truncated",
+        ]
+        .map(ToString::to_string);
+        let formatted = format_logs(
+            &logs,
+            &PrettyLogsOptions {
+                theme: Theme::None,
+                indent: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            formatted,
+            "\
+Program 1111111QLbz7JHiBTspS962RLKV8GndWFwiEaqKM invoke [1]
+  panicked at clock-example/src/lib.rs:17:5:
+  assertion failed: got_clock.unix_timestamp < 100
+  Log truncated
+"
+        );
+    }
+
+    #[test]
+    fn test_rules_and_html_theme() {
+        let options = PrettyLogsOptions {
+            theme: Theme::Html,
+            rules: vec![ImportanceRule::new("SLIPPAGE", Importance::Error)],
+            ..Default::default()
+        };
+        let formatted = format_logs(&cpi_logs(), &options);
+        assert!(formatted.starts_with("<pre class=\"litesvm-logs\">\n"));
+        assert!(formatted.ends_with("</pre>\n"));
+        assert!(formatted
+            .contains("<span style=\"color:#ff0000;font-weight:bold\">slippage &lt;1%</span>\n"));
+        assert!(formatted.contains("<span style=\"color:#008000\">Instruction: Swap</span>\n"));
+    }
 }
@@ -0,0 +1,219 @@
+//! Human-readable names for addresses. Logs, errors and traces print base58
+//! addresses; a [`Labels`] registry swaps in a name wherever it knows one.
+
+#[cfg(feature = "hashbrown")]
+use hashbrown::HashMap;
+#[cfg(not(feature = "hashbrown"))]
+use std::collections::HashMap;
+use {
+    solana_address::{address, Address},
+    solana_sdk_ids::{
+        address_lookup_table, bpf_loader, bpf_loader_deprecated, bpf_loader_upgradeable,
+        compute_budget, config, ed25519_program, feature, incinerator, loader_v4, native_loader,
+        secp256k1_program, secp256r1_program, stake, system_program, sysvar, vote,
+    },
+    std::str::FromStr,
+};
+
+// Base58 addresses are 32 to 44 characters long.
+const MIN_ADDRESS_LEN: usize = 32;
+const MAX_ADDRESS_LEN: usize = 44;
+
+/// Names of the programs and sysvars every [`LiteSVM`](crate::LiteSVM) knows.
+const BUILTIN_LABELS: &[(Address, &str)] = &[
+    (system_program::ID, "system"),
+    (
+        address!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"),
+        "token",
+    ),
+    (
+        address!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb"),
+        "token-2022",
+    ),
+    (
+        address!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"),
+        "associated-token",
+    ),
+    (
+        address!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr"),
+        "memo",
+    ),
+    (
+        address!("Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo"),
+        "memo-v1",
+    ),
+    (stake::ID, "stake"),
+    (address_lookup_table::ID, "address-lookup-table"),
+    (compute_budget::ID, "compute-budget"),
+    (vote::ID, "vote"),
+    (config::ID, "config"),
+    (feature::ID, "feature"),
+    (incinerator::ID, "incinerator"),
+    (bpf_loader::ID, "bpf-loader"),
+    (bpf_loader_deprecated::ID, "bpf-loader-deprecated"),
+    (bpf_loader_upgradeable::ID, "bpf-loader-upgradeable"),
+    (loader_v4::ID, "loader-v4"),
+    (native_loader::ID, "native-loader"),
+    (ed25519_program::ID, "ed25519"),
+    (secp256k1_program::ID, "secp256k1"),
+    (secp256r1_program::ID, "secp256r1"),
+    (sysvar::ID, "sysvar"),
+    (sysvar::clock::ID, "sysvar-clock"),
+    (sysvar::epoch_rewards::ID, "sysvar-epoch-rewards"),
+    (sysvar::epoch_schedule::ID, "sysvar-epoch-schedule"),
+    (sysvar::fees::ID, "sysvar-fees"),
+    (sysvar::instructions::ID, "sysvar-instructions"),
+    (sysvar::last_restart_slot::ID, "sysvar-last-restart-slot"),
+    (sysvar::recent_blockhashes::ID, "sysvar-recent-blockhashes"),
+    (sysvar::rent::ID, "sysvar-rent"),
+    (sysvar::slot_hashes::ID, "sysvar-slot-hashes"),
+    (sysvar::slot_history::ID, "sysvar-slot-history"),
+    (sysvar::stake_history::ID, "sysvar-stake-history"),
+];
+
+/// A registry of address labels.
+///
+/// [`Labels::default`] starts with the system, token, associated token,
+/// memo, stake, address lookup table and other builtin programs, and the
+/// sysvars. Add your own with [`LiteSVM::label`](crate::LiteSVM::label) or
+/// [`Labels::insert`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Labels {
+    labels: HashMap<Address, String>,
+}
+
+impl Default for Labels {
+    fn default() -> Self {
+        let mut labels = Self::empty();
+        for (address, name) in BUILTIN_LABELS {
+            labels.insert(*address, *name);
+        }
+        labels
+    }
+}
+
+impl Labels {
+    /// A registry without the builtin labels.
+    pub fn empty() -> Self {
+        Self {
+            labels: HashMap::new(),
+        }
+    }
+
+    /// Labels `address`, returning its previous label if it had one.
+    pub fn insert(&mut self, address: Address, name: impl Into<String>) -> Option<String> {
+        self.labels.insert(address, name.into())
+    }
+
+    /// Removes the label of `address`, returning it if there was one.
+    pub fn remove(&mut self, address: &Address) -> Option<String> {
+        self.labels.remove(address)
+    }
+
+    /// The label of `address`, if it has one.
+    pub fn get(&self, address: &Address) -> Option<&str> {
+        self.labels.get(address).map(String::as_str)
+    }
+
+    /// Every label, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&Address, &str)> {
+        self.labels
+            .iter()
+            .map(|(address, name)| (address, name.as_str()))
+    }
+
+    /// The label of `address`, or the address in base58 if it has none.
+    pub fn name(&self, address: &Address) -> String {
+        self.get(address)
+            .map_or_else(|| address.to_string(), str::to_string)
+    }
+
+    /// The label of `address` followed by the address, or just the address
+    /// if it has no label. For error messages, where the address is still
+    /// needed to look the account up.
+    pub fn describe(&self, address: &Address) -> String {
+        match self.get(address) {
+            Some(name) => format!("{name} ({address})"),
+            None => address.to_string(),
+        }
+    }
+
+    /// Replaces every labeled base58 address in `text` with its label.
+    pub fn relabel(&self, text: &str) -> String {
+        if self.labels.is_empty() {
+            return text.to_string();
+        }
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find(is_base58_char) {
+            let (before, word) = rest.split_at(start);
+            out.push_str(before);
+            let end = word.find(|c| !is_base58_char(c)).unwrap_or(word.len());
+            let (word, after) = word.split_at(end);
+            match self.lookup(word) {
+                Some(name) => out.push_str(name),
+                None => out.push_str(word),
+            }
+            rest = after;
+        }
+        out.push_str(rest);
+        out
+    }
+
+    fn lookup(&self, word: &str) -> Option<&str> {
+        if !(MIN_ADDRESS_LEN..=MAX_ADDRESS_LEN).contains(&word.len()) {
+            return None;
+        }
+        self.get(&Address::from_str(word).ok()?)
+    }
+}
+
+fn is_base58_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() && !matches!(c, '0' | 'O' | 'I' | 'l')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROGRAM: Address = address!("GtdambwDgHWrDJdVPBkEHGhCwokqgAoch162teUjJse2");
+
+    #[test]
+    fn relabel_replaces_only_labeled_addresses() {
+        let mut labels = Labels::default();
+        labels.insert(PROGRAM, "counter");
+        assert_eq!(
+            labels.relabel(&format!(
+                "Program {PROGRAM} invoke [1]; account {} is {}, not {}",
+                Address::new_from_array([7; 32]),
+                sysvar::clock::id(),
+                system_program::id(),
+            )),
+            format!(
+                "Program counter invoke [1]; account {} is sysvar-clock, not system",
+                Address::new_from_array([7; 32]),
+            )
+        );
+        // Words that merely look like base58 are left alone.
+        assert_eq!(
+            labels.relabel("Program log: Instruction: Increment"),
+            "Program log: Instruction: Increment"
+        );
+    }
+
+    #[test]
+    fn builtin_labels_can_be_overridden() {
+        let mut labels = Labels::default();
+        assert_eq!(labels.name(&stake::id()), "stake");
+        assert_eq!(
+            labels.insert(stake::id(), "native stake"),
+            Some("stake".to_string())
+        );
+        assert_eq!(
+            labels.describe(&stake::id()),
+            format!("native stake ({})", stake::id())
+        );
+        assert_eq!(labels.name(&PROGRAM), PROGRAM.to_string());
+        assert_eq!(Labels::empty().get(&system_program::id()), None);
+    }
+}
@@ -263,8 +263,41 @@ breakpoints, and step through SBPF execution.
 
 The `SBF_TRACE_FILTER` environment variable can be used to narrow which
 traces are collected and what is going to be debugged. It supports filtering
-by `txsig` and `program_id` with the `==`, `!=`, `||`, and `&&` operators.
-For example: `SBF_TRACE_FILTER="txsig == A && (program_id == B || program_id == C)"`.
+by these fields:
+
+* `txsig`: a signature of the transaction.
+* `program_id`: the invoked program, by address or [label](LiteSVM::label).
+* `caller`: the program that invoked it through a CPI, by address or label.
+  Top-level instructions have no caller.
+* `instruction_index`: the index of the top-level instruction the invocation
+  belongs to.
+* `depth`: 1 for a top-level instruction, one more per level of CPI.
+* `outcome`: `success` or `failed`, as recorded when the invocation ran, so it
+  doesn't depend on the logs or the log bytes limit.
+* `test`: the name of the running test.
+
+Values are compared with `==` and `!=`, matched against a glob with `~=`
+(`*` and `?` wildcards) or against a regular expression with `=~`. Values
+containing spaces or operators can be quoted (`"..."`). Conditions combine
+with `&&`, `||`, `!` and parentheses. A field that doesn't apply, like the
+`caller` of a top-level instruction, never equals anything.
+For example: `SBF_TRACE_FILTER="txsig == A && (program_id == B || program_id == C)"`,
+or `SBF_TRACE_FILTER="outcome == failed && !depth == 1 && test ~= swap_*"`.
+
+Every trace gets a line in the `manifest.tsv` file of the trace directory,
+recording the transaction signature, instruction index, CPI depth, program and
+caller with their labels, outcome and test of the invocation along with the
+trace file. Traces are written as raw `.regs`/`.insns` files by default; with
+`SBF_TRACE_FORMAT=compact`, or a callback built with
+[`with_trace_format`](register_tracing::DefaultRegisterTracingCallback::with_trace_format),
+they are written as zstd-compressed `.sbftrace` files that only store the
+registers each step changes, which are many times smaller. Either way, the
+executable of every traced program is kept once under `programs/`. The `litesvm-trace` command,
+built with the `register-tracing` feature, lists and filters traces with the
+`SBF_TRACE_FILTER` language and disassembles them after the run:
+`litesvm-trace list --filter "outcome == failed"`, then
+`litesvm-trace disassemble <trace>`. See [`register_tracing_format`] for the
+formats.
 
 Once enabled register tracing can't be changed afterwards because in nature
 it's baked into the program executables at load time. Yet a user may want a
@@ -330,6 +363,7 @@ use {
         error::LiteSVMError,
         features::MAINNET_ACTIVE_FEATURES,
         history::TransactionHistory,
+        labels::Labels,
         message_processor::process_message,
         programs::load_default_programs,
         types::{
@@ -356,6 +390,7 @@ use {
     solana_fee::FeeFeatures,
     solana_fee_structure::FeeStructure,
     solana_hash::Hash,
+    solana_instruction_error::InstructionError,
     solana_keypair::Keypair,
     solana_last_restart_slot::LastRestartSlot,
     solana_loader_v3_interface::state::UpgradeableLoaderState,
@@ -369,7 +404,7 @@ use {
         loaded_programs::{ProgramRuntimeEnvironment, ProgramRuntimeEnvironments},
         program_cache_entry::{ProgramCacheEntry, DELAY_VISIBILITY_SLOT_OFFSET},
         program_metrics::LoadProgramMetrics,
-        solana_sbpf::program::BuiltinProgram,
+        solana_sbpf::program::{BuiltinFunctionDefinition, BuiltinProgram},
     },
     solana_rent::Rent,
     solana_runtime_transaction::transaction_meta::TransactionConfiguration,
@@ -403,6 +438,7 @@ use {
     utils::{
         construct_instructions_account,
         inner_instructions::inner_instructions_list_from_instruction_trace,
+        invocations::{invocations_from_instruction_trace, start_metering, MeteredEntrypoint},
     },
     wincode::{DeserializeOwned, Serialize},
 };
@@ -411,12 +447,15 @@ pub mod error;
 pub mod types;
 
 mod accounts_db;
+#[cfg(feature = "persistence-internal")]
+pub use accounts_db::LazyAccounts;
 mod callback;
 #[cfg(feature = "sbpf-debugger")]
 pub mod debugger;
 pub mod features;
-mod format_logs;
+pub mod format_logs;
 mod history;
+pub mod labels;
 mod message_processor;
 #[cfg(feature = "precompiles")]
 mod precompiles;
@@ -425,6 +464,8 @@ mod programs;
 pub mod register_tracing;
 #[cfg(feature = "register-tracing")]
 pub mod register_tracing_filter;
+#[cfg(feature = "register-tracing")]
+pub mod register_tracing_format;
 mod utils;
 
 /// Latest slot at which mainnet-beta activated a feature in [`MAINNET_ACTIVE_FEATURES`]; LiteSVM's default starting slot.
@@ -456,6 +497,7 @@ pub struct LiteSVM {
     custom_syscalls: Vec<(String, BuiltinFunctionRegisterer)>,
     epoch_total_stake: u64,
     epoch_vote_stakes: HashMap<Address, u64>,
+    labels: Labels,
     /// The callback which can be used to inspect invoke_context
     /// and extract low-level information such as bpf traces, transaction
     /// context, detailed timings, etc.
@@ -501,6 +543,7 @@ impl LiteSVM {
             custom_syscalls: Vec::new(),
             epoch_total_stake: 0,
             epoch_vote_stakes: HashMap::new(),
+            labels: Labels::default(),
             #[cfg(feature = "invocation-inspect-callback")]
             enable_register_tracing: _enable_register_tracing,
             #[cfg(feature = "invocation-inspect-callback")]
@@ -713,8 +756,20 @@ impl LiteSVM {
                 .enable_feature_id
                 .is_none_or(|x| self.feature_set.is_active(&x))
             {
+                // The SBF loaders meter the programs they run, for
+                // `TransactionMetadata::invocations`.
+                let sbf_loaders = [
+                    bpf_loader::id(),
+                    bpf_loader_deprecated::id(),
+                    bpf_loader_upgradeable::id(),
+                ];
+                let register_fn = if sbf_loaders.contains(&builtint.program_id) {
+                    MeteredEntrypoint::register
+                } else {
+                    builtint.register_fn
+                };
                 let loaded_program =
-                    ProgramCacheEntry::new_builtin(0, builtint.name.len(), builtint.register_fn);
+                    ProgramCacheEntry::new_builtin(0, builtint.name.len(), register_fn);
                 self.accounts
                     .programs_cache
                     .replenish(builtint.program_id, Arc::new(loaded_program));
@@ -824,9 +879,7 @@ impl LiteSVM {
     /// Returns all accounts owned by the given program, together with their addresses.
     pub fn get_program_accounts(&self, program_id: &Address) -> Vec<(Address, Account)> {
         self.accounts
-            .inner
-            .iter()
-            .filter(|(_, account)| account.owner() == program_id)
+            .iter_owned_by(program_id)
             .map(|(address, account)| (*address, account.clone().into()))
             .collect()
     }
@@ -987,6 +1040,20 @@ impl LiteSVM {
         self.history.get_transaction(signature)
     }
 
+    /// Gives `address` a human-readable name, replacing any previous one.
+    ///
+    /// The name stands in for the address in [`TransactionMetadata::pretty_logs_with_labels`],
+    /// [`FailedTransactionMetadata::describe`], loader errors and register trace file names.
+    /// The builtin programs and sysvars are labeled already; see [`Labels`].
+    pub fn label(&mut self, address: Address, name: impl Into<String>) {
+        self.labels.insert(address, name);
+    }
+
+    /// The address labels of this VM.
+    pub fn labels(&self) -> &Labels {
+        &self.labels
+    }
+
     /// Returns the pubkey of the internal airdrop account.
     pub fn airdrop_pubkey(&self) -> Address {
         Keypair::try_from(self.airdrop_kp.as_slice())
@@ -1114,7 +1181,8 @@ impl LiteSVM {
             program_len
         } else {
             return Err(LiteSVMError::InvalidLoader(format!(
-                "Unsupported loader: {loader_id}"
+                "Unsupported loader: {}",
+                self.labels.describe(loader_id)
             )));
         };
 
@@ -1179,6 +1247,101 @@ impl LiteSVM {
         self.add_program_internal::<false>(program_id, program_bytes, &loader_id)
     }
 
+    /// Replaces the code of an already-deployed upgradeable program without
+    /// sending loader transactions.
+    ///
+    /// The programdata account keeps its address and upgrade authority, its
+    /// deployment slot is set to the current slot, and the new code can be
+    /// invoked right away. Unlike [`add_program`](Self::add_program), the
+    /// rest of the program's setup is left untouched.
+    ///
+    /// As with the loader's `Upgrade`, the programdata account keeps its size
+    /// and the bytes after the new code are zeroed. It only grows, with its
+    /// rent topped up, when the new code doesn't fit.
+    pub fn upgrade_program_in_place(
+        &mut self,
+        program_id: impl Into<Address>,
+        program_bytes: &[u8],
+    ) -> Result<(), LiteSVMError> {
+        let program_id = program_id.into();
+        let program_account = self
+            .accounts
+            .get_account(&program_id)
+            .ok_or(InstructionError::MissingAccount)?;
+        if !bpf_loader_upgradeable::check_id(program_account.owner()) {
+            return Err(LiteSVMError::InvalidLoader(format!(
+                "Program {} is not owned by the upgradeable loader",
+                self.labels.describe(&program_id)
+            )));
+        }
+        let Ok(UpgradeableLoaderState::Program {
+            programdata_address,
+        }) = UpgradeableLoaderState::deserialize_from(program_account.data())
+        else {
+            return Err(InstructionError::InvalidAccountData.into());
+        };
+        let mut programdata_account = self
+            .accounts
+            .get_account(&programdata_address)
+            .ok_or(InstructionError::MissingAccount)?;
+        let Ok(UpgradeableLoaderState::ProgramData {
+            upgrade_authority_address,
+            ..
+        }) = UpgradeableLoaderState::deserialize_from(programdata_account.data())
+        else {
+            return Err(InstructionError::InvalidAccountData.into());
+        };
+
+        let current_slot = self
+            .accounts
+            .sysvar_cache
+            .get_clock()
+            .unwrap_or_default()
+            .slot;
+        let programdata_metadata_len = UpgradeableLoaderState::size_of_programdata_metadata();
+        let programdata_len = programdata_account
+            .data()
+            .len()
+            .max(programdata_metadata_len + program_bytes.len());
+        let mut loaded_program = ProgramCacheEntry::new(
+            &bpf_loader_upgradeable::id(),
+            self.accounts.environments.get_env_for_deployment().clone(),
+            current_slot,
+            current_slot.saturating_add(DELAY_VISIBILITY_SLOT_OFFSET),
+            program_bytes,
+            programdata_len,
+            &mut LoadProgramMetrics::default(),
+        )
+        .map_err(|e| LiteSVMError::ProgramLoad(e.to_string()))?;
+        loaded_program.effective_slot = current_slot;
+
+        let mut programdata_data = vec![0u8; programdata_len];
+        UpgradeableLoaderState::serialize_into(
+            &mut programdata_data[..programdata_metadata_len],
+            &UpgradeableLoaderState::ProgramData {
+                slot: current_slot,
+                upgrade_authority_address,
+            },
+        )
+        .expect("UpgradeableLoaderState::ProgramData serialization should never fail");
+        programdata_data[programdata_metadata_len..][..program_bytes.len()]
+            .copy_from_slice(program_bytes);
+        programdata_account.set_lamports(
+            programdata_account
+                .lamports()
+                .max(self.minimum_balance_for_rent_exemption(programdata_len)),
+        );
+        programdata_account.set_data_from_slice(&programdata_data);
+
+        self.accounts
+            .add_account_no_checks(programdata_address, programdata_account);
+        self.accounts
+            .programs_cache
+            .replenish(program_id, Arc::new(loaded_program));
+
+        Ok(())
+    }
+
     /// Adds an SBF program that is known-good and already verified.
     pub(crate) fn add_program_preverified(
         &mut self,
@@ -1474,6 +1637,7 @@ impl LiteSVM {
                     self.enable_register_tracing,
                 );
 
+                start_metering();
                 let mut tx_result = process_message(
                     message,
                     &program_indices,
@@ -1683,6 +1847,7 @@ impl LiteSVM {
             signature,
             compute_units_consumed,
             inner_instructions,
+            invocations,
             return_data,
             included,
             fee,
@@ -1701,6 +1866,7 @@ impl LiteSVM {
             return_data,
             signature,
             fee,
+            invocations,
         };
 
         if let Err(tx_err) = tx_result {
@@ -1738,6 +1904,7 @@ impl LiteSVM {
             signature,
             compute_units_consumed,
             inner_instructions,
+            invocations,
             return_data,
             fee,
             ..
@@ -1756,6 +1923,7 @@ impl LiteSVM {
             compute_units_consumed,
             return_data,
             fee,
+            invocations,
         };
 
         if let Err(tx_err) = tx_result {
@@ -1988,6 +2156,13 @@ impl LiteSVM {
         self.accounts.add_account_no_checks(pubkey, account);
     }
 
+    /// Attaches accounts whose data is only copied out of their backing
+    /// buffer when first read. Accounts set afterwards take precedence.
+    #[cfg(feature = "persistence-internal")]
+    pub fn set_lazy_accounts(&mut self, accounts: LazyAccounts) {
+        self.accounts.set_lazy_accounts(accounts);
+    }
+
     #[cfg(feature = "persistence-internal")]
     pub fn restore_transaction_history(
         &mut self,
@@ -2037,6 +2212,7 @@ fn execution_result_if_context(
     compute_units_consumed: u64,
     fee: u64,
 ) -> ExecutionResult {
+    let invocations = invocations_from_instruction_trace(&ctx, &result);
     let (signature, return_data, inner_instructions, post_accounts) =
         execute_tx_helper(sanitized_tx, ctx);
     ExecutionResult {
@@ -2044,6 +2220,7 @@ fn execution_result_if_context(
         signature,
         post_accounts,
         inner_instructions,
+        invocations,
         compute_units_consumed,
         return_data,
         included: true,
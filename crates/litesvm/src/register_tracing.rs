@@ -2,7 +2,12 @@
 use solana_account::ReadableAccount;
 use {
     crate::{
-        register_tracing_filter::{eval, expr},
+        register_tracing_filter::{eval, expr, Expr},
+        register_tracing_format::{
+            append_manifest, persist_program, write_compact, ManifestEntry, TraceFormat,
+            COMPACT_EXTENSION,
+        },
+        utils::invocations::metered_success,
         InvocationInspectCallback, LiteSVM,
     },
     sha2::{Digest, Sha256},
@@ -11,11 +16,108 @@ use {
     solana_svm_transaction::svm_message::SVMMessage,
     solana_transaction::sanitized::SanitizedTransaction,
     solana_transaction_context::{instruction::InstructionContext, IndexOfAccount},
-    std::{collections::HashMap, fs::File, io::Write},
+    std::{
+        collections::HashMap,
+        fs::File,
+        io::{BufWriter, Write},
+        sync::{Mutex, OnceLock, PoisonError},
+    },
 };
 
 const DEFAULT_PATH: &str = "target/sbf/trace";
 
+/// Where an invocation sits in its transaction and how it ended: the
+/// `SBF_TRACE_FILTER` fields besides `txsig`, `program_id` and `test`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InvocationFields {
+    /// Index of the top-level instruction the invocation belongs to
+    /// (`instruction_index`).
+    pub instruction_index: usize,
+    /// 1 for a top-level instruction, one more per level of CPI (`depth`).
+    pub depth: usize,
+    /// The program that invoked this one, `None` for top-level instructions
+    /// (`caller`).
+    pub caller: Option<Address>,
+    /// Whether the invocation succeeded, as metered by the SBF loaders while
+    /// it ran (`outcome`, `success` or `failed`). `None` for builtin programs
+    /// and precompiles, and for instructions that never ran.
+    pub success: Option<bool>,
+}
+
+/// The [`InvocationFields`] of every invocation in the instruction trace of
+/// `invoke_context`, by index in the trace. Call it from
+/// [`InvocationInspectCallback::after_invocation`], once the transaction has
+/// run and while its metering is still at hand.
+pub fn invocation_fields(invoke_context: &InvokeContext) -> Vec<InvocationFields> {
+    let transaction_context = &invoke_context.transaction_context;
+    // Program, stack height and caller index of each traced instruction.
+    let trace: Vec<Option<(Address, usize, usize)>> = (0..transaction_context
+        .get_instruction_trace_length())
+        .map(|index| {
+            let instruction_context = transaction_context
+                .get_instruction_context_at_index_in_trace(index)
+                .ok()?;
+            Some((
+                *instruction_context.get_program_key().ok()?,
+                instruction_context.get_stack_height(),
+                instruction_context.get_index_of_caller(),
+            ))
+        })
+        .collect();
+    // The top-level instructions come first in the trace, in order; CPIs
+    // follow in execution order and point at their caller.
+    let root = |mut index: usize| {
+        for _ in 0..trace.len() {
+            match trace[index] {
+                Some((_, depth, caller)) if depth > 1 && caller < trace.len() => index = caller,
+                _ => break,
+            }
+        }
+        index
+    };
+
+    trace
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| match entry {
+            Some((_, depth, caller)) => InvocationFields {
+                instruction_index: root(index),
+                depth: *depth,
+                caller: (*depth > 1)
+                    .then(|| trace.get(*caller).copied().flatten())
+                    .flatten()
+                    .map(|(program_id, ..)| program_id),
+                success: metered_success(index),
+            },
+            None => InvocationFields::default(),
+        })
+        .collect()
+}
+
+/// `filter` parsed, `None` if it's malformed. Each distinct filter is parsed
+/// once, since compiling its regular expressions costs far more than
+/// evaluating it for every invocation.
+fn parsed_filter(filter: &str) -> Option<&'static Expr<'static>> {
+    static PARSED: OnceLock<Mutex<HashMap<String, Option<&'static Expr<'static>>>>> =
+        OnceLock::new();
+    let mut parsed = PARSED
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    *parsed.entry(filter.to_string()).or_insert_with(|| {
+        // Kept for the rest of the run, like the filter itself.
+        let filter: &'static str = Box::leak(filter.into());
+        expr(filter).ok().map(|ast| &*Box::leak(Box::new(ast)))
+    })
+}
+
+/// The filter values of an address: the address and its label, if any.
+fn address_values(svm: &LiteSVM, address: &Address) -> Vec<String> {
+    std::iter::once(address.to_string())
+        .chain(svm.labels().get(address).map(str::to_string))
+        .collect()
+}
+
 pub struct DefaultRegisterTracingCallback {
     pub sbf_trace_dir: String,
     pub sbf_trace_disassemble: bool,
@@ -64,11 +166,23 @@ impl DefaultRegisterTracingCallback {
     }
 
     pub fn match_filter(&self, tx_signatures: Vec<String>, program_ids: Vec<String>) -> bool {
-        let Ok(ast) = expr(&self.sbf_trace_filter) else {
+        self.match_filter_row(HashMap::from([
+            ("txsig", tx_signatures),
+            ("program_id", program_ids),
+        ]))
+    }
+
+    /// Evaluates the filter against `row`, a map from field name to the
+    /// field's values. The `test` field, the name of the current thread,
+    /// which the test harness names after the running test, is added.
+    pub fn match_filter_row(&self, mut row: HashMap<&str, Vec<String>>) -> bool {
+        let Some(ast) = parsed_filter(&self.sbf_trace_filter) else {
             return true;
         };
-        let row = HashMap::from([("txsig", tx_signatures), ("program_id", program_ids)]);
-        eval(&ast, &row)
+        if let Some(test) = std::thread::current().name() {
+            row.insert("test", vec![test.to_string()]);
+        }
+        eval(ast, &row)
     }
 
     #[cfg_attr(not(feature = "sbpf-debugger"), expect(unused_variables))]
@@ -138,6 +252,12 @@ impl DefaultRegisterTracingCallback {
         }
     }
 
+    /// Like [`post_handler_with_fields`](Self::post_handler_with_fields),
+    /// with only the fields the instruction context tells: the depth and
+    /// outcome of the invocation, and the instruction index of a top-level
+    /// instruction. The instruction index of a CPI and its caller take the
+    /// whole instruction trace (see [`invocation_fields`]), so they're left
+    /// at 0 and `None`.
     pub fn post_handler(
         &self,
         svm: &LiteSVM,
@@ -145,6 +265,66 @@ impl DefaultRegisterTracingCallback {
         instruction_context: InstructionContext,
         executable: &Executable,
         register_trace: RegisterTrace,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let index_in_trace = instruction_context.get_index_in_trace();
+        let depth = instruction_context.get_stack_height();
+        let fields = InvocationFields {
+            instruction_index: if depth > 1 { 0 } else { index_in_trace },
+            depth,
+            caller: None,
+            success: metered_success(index_in_trace),
+        };
+        self.post_handler_with_fields(
+            svm,
+            tx,
+            instruction_context,
+            executable,
+            register_trace,
+            &fields,
+        )
+    }
+
+    /// Writes the trace of an invocation if the filter matches it, with
+    /// `fields` from [`invocation_fields`] for the filter and the manifest.
+    /// The trace is written in the format `SBF_TRACE_FORMAT` selects.
+    pub fn post_handler_with_fields(
+        &self,
+        svm: &LiteSVM,
+        tx: &SanitizedTransaction,
+        instruction_context: InstructionContext,
+        executable: &Executable,
+        register_trace: RegisterTrace,
+        fields: &InvocationFields,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.write_trace(
+            svm,
+            tx,
+            instruction_context,
+            executable,
+            register_trace,
+            fields,
+            env_trace_format(),
+        )
+    }
+
+    /// Writes its traces in `format`, whatever `SBF_TRACE_FORMAT` selects.
+    pub fn with_trace_format(self, format: TraceFormat) -> FormattedRegisterTracingCallback {
+        FormattedRegisterTracingCallback {
+            callback: self,
+            format,
+        }
+    }
+
+    #[expect(clippy::too_many_arguments)]
+    fn write_trace(
+        &self,
+        svm: &LiteSVM,
+        tx: &SanitizedTransaction,
+        instruction_context: InstructionContext,
+        executable: &Executable,
+        register_trace: RegisterTrace,
+        fields: &InvocationFields,
+        format: TraceFormat,
     ) -> Result<(), Box<dyn std::error::Error>> {
         if register_trace.is_empty() {
             // Can't do much with an empty trace.
@@ -154,7 +334,23 @@ impl DefaultRegisterTracingCallback {
         // Get program_id.
         let program_id = instruction_context.get_program_key()?;
         let signatures: Vec<_> = tx.signatures().iter().map(|sig| sig.to_string()).collect();
-        if !self.match_filter(signatures, vec![program_id.to_string()]) {
+        let mut row = HashMap::from([
+            ("txsig", signatures),
+            ("program_id", address_values(svm, program_id)),
+            (
+                "instruction_index",
+                vec![fields.instruction_index.to_string()],
+            ),
+            ("depth", vec![fields.depth.to_string()]),
+        ]);
+        if let Some(caller) = &fields.caller {
+            row.insert("caller", address_values(svm, caller));
+        }
+        if let Some(success) = fields.success {
+            let outcome = if success { "success" } else { "failed" };
+            row.insert("outcome", vec![outcome.to_string()]);
+        }
+        if !self.match_filter_row(row) {
             // Skip this one since no filter has matched.
             return Ok(());
         }
@@ -164,10 +360,10 @@ impl DefaultRegisterTracingCallback {
         std::fs::create_dir_all(&sbf_trace_dir)?;
 
         let trace_digest = compute_hash(as_bytes(register_trace));
-        let base_fname = sbf_trace_dir.join(&trace_digest[..16]);
-        let mut regs_file = File::create(base_fname.with_extension("regs"))?;
-        let mut insns_file = File::create(base_fname.with_extension("insns"))?;
-        let mut program_id_file = File::create(base_fname.with_extension("program_id"))?;
+        let base_fname = sbf_trace_dir.join(trace_file_stem(
+            svm.labels().get(program_id),
+            &trace_digest[..16],
+        ));
 
         // Persist a full trace disassembly if requested.
         if self.sbf_trace_disassemble {
@@ -180,29 +376,83 @@ impl DefaultRegisterTracingCallback {
             );
         }
 
-        // Persist the program id.
-        let _ = program_id_file.write(program_id.to_string().as_bytes());
+        // Keep a copy of the executable, so the trace can be disassembled later.
+        let exec_sha256 = match svm.accounts_db().try_program_elf_bytes(program_id) {
+            Ok(elf_data) => {
+                let exec_sha256 = compute_hash(elf_data);
+                persist_program(&sbf_trace_dir, &exec_sha256, elf_data)?;
+                Some(exec_sha256)
+            }
+            Err(_) => None,
+        };
 
-        if let Ok(elf_data) = svm.accounts_db().try_program_elf_bytes(program_id) {
-            // Persist the preload hash of the executable.
-            let mut so_hash_file = File::create(base_fname.with_extension("exec.sha256"))?;
-            let _ = so_hash_file.write(compute_hash(elf_data).as_bytes());
-        }
+        let trace_file = match format {
+            TraceFormat::Raw => {
+                let mut regs_file = File::create(base_fname.with_extension("regs"))?;
+                let mut insns_file = File::create(base_fname.with_extension("insns"))?;
+                let mut program_id_file = File::create(base_fname.with_extension("program_id"))?;
 
-        // Get the relocated executable.
-        let (_, program) = executable.get_text_bytes();
-        for regs in register_trace.iter() {
-            // The program counter is stored in r11.
-            let pc = regs[11];
-            // From the executable fetch the instruction this program counter points to.
-            let insn =
-                solana_program_runtime::solana_sbpf::ebpf::get_insn_unchecked(program, pc as usize)
+                // Persist the program id.
+                let _ = program_id_file.write(program_id.to_string().as_bytes());
+
+                if let Some(exec_sha256) = &exec_sha256 {
+                    // Persist the preload hash of the executable.
+                    let mut so_hash_file = File::create(base_fname.with_extension("exec.sha256"))?;
+                    let _ = so_hash_file.write(exec_sha256.as_bytes());
+                }
+
+                // Get the relocated executable.
+                let (_, program) = executable.get_text_bytes();
+                for regs in register_trace.iter() {
+                    // The program counter is stored in r11.
+                    let pc = regs[11];
+                    // From the executable fetch the instruction this program counter points to.
+                    let insn = solana_program_runtime::solana_sbpf::ebpf::get_insn_unchecked(
+                        program,
+                        pc as usize,
+                    )
                     .to_array();
 
-            // Persist them in files.
-            let _ = regs_file.write(as_bytes(regs.as_slice()))?;
-            let _ = insns_file.write(insn.as_slice())?;
-        }
+                    // Persist them in files.
+                    let _ = regs_file.write(as_bytes(regs.as_slice()))?;
+                    let _ = insns_file.write(insn.as_slice())?;
+                }
+                base_fname.with_extension("regs")
+            }
+            TraceFormat::Compact => {
+                let trace_file = base_fname.with_extension(COMPACT_EXTENSION);
+                write_compact(
+                    BufWriter::new(File::create(&trace_file)?),
+                    program_id,
+                    register_trace,
+                )?;
+                trace_file
+            }
+        };
+
+        append_manifest(
+            &sbf_trace_dir,
+            &ManifestEntry {
+                signature: tx.signature().to_string(),
+                instruction_index: fields.instruction_index,
+                depth: fields.depth,
+                program_id: *program_id,
+                program_label: svm.labels().get(program_id).map(str::to_string),
+                caller: fields.caller,
+                caller_label: fields
+                    .caller
+                    .and_then(|caller| svm.labels().get(&caller).map(str::to_string)),
+                success: fields.success,
+                test: std::thread::current().name().map(str::to_string),
+                exec_sha256,
+                steps: register_trace.len(),
+                file: trace_file
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned(),
+            },
+        )?;
 
         Ok(())
     }
@@ -270,18 +520,118 @@ impl InvocationInspectCallback for DefaultRegisterTracingCallback {
     ) {
         if register_tracing_enabled {
             // Only read the register traces if they were actually enabled.
-            invoke_context.iterate_vm_traces(
-                &|instruction_context: InstructionContext,
-                  executable: &Executable,
-                  register_trace: RegisterTrace| {
-                    if let Err(e) =
-                        self.post_handler(svm, tx, instruction_context, executable, register_trace)
-                    {
-                        eprintln!("Error collecting the register tracing: {e}");
+            self.write_traces(svm, tx, invoke_context, env_trace_format());
+        }
+    }
+}
+
+impl DefaultRegisterTracingCallback {
+    fn write_traces(
+        &self,
+        svm: &LiteSVM,
+        tx: &SanitizedTransaction,
+        invoke_context: &InvokeContext,
+        format: TraceFormat,
+    ) {
+        let fields = invocation_fields(invoke_context);
+        invoke_context.iterate_vm_traces(
+            &|instruction_context: InstructionContext,
+              executable: &Executable,
+              register_trace: RegisterTrace| {
+                let fields = fields
+                    .get(instruction_context.get_index_in_trace())
+                    .cloned()
+                    .unwrap_or_default();
+                if let Err(e) = self.write_trace(
+                    svm,
+                    tx,
+                    instruction_context,
+                    executable,
+                    register_trace,
+                    &fields,
+                    format,
+                ) {
+                    eprintln!("Error collecting the register tracing: {e}");
+                }
+            },
+        );
+    }
+}
+
+/// A [`DefaultRegisterTracingCallback`] writing its traces in a chosen
+/// [`TraceFormat`], built with
+/// [`DefaultRegisterTracingCallback::with_trace_format`].
+pub struct FormattedRegisterTracingCallback {
+    callback: DefaultRegisterTracingCallback,
+    format: TraceFormat,
+}
+
+impl InvocationInspectCallback for FormattedRegisterTracingCallback {
+    fn before_invocation(
+        &self,
+        svm: &LiteSVM,
+        tx: &SanitizedTransaction,
+        program_indices: &[IndexOfAccount],
+        invoke_context: &mut InvokeContext,
+        register_tracing_enabled: bool,
+    ) {
+        self.callback.before_invocation(
+            svm,
+            tx,
+            program_indices,
+            invoke_context,
+            register_tracing_enabled,
+        );
+    }
+
+    fn after_invocation(
+        &self,
+        svm: &LiteSVM,
+        tx: &SanitizedTransaction,
+        _: &[IndexOfAccount],
+        invoke_context: &InvokeContext,
+        register_tracing_enabled: bool,
+    ) {
+        if register_tracing_enabled {
+            self.callback
+                .write_traces(svm, tx, invoke_context, self.format);
+        }
+    }
+}
+
+/// The format `SBF_TRACE_FORMAT` selects, read once.
+fn env_trace_format() -> TraceFormat {
+    static FORMAT: OnceLock<TraceFormat> = OnceLock::new();
+    *FORMAT.get_or_init(|| {
+        std::env::var("SBF_TRACE_FORMAT")
+            .map(|format| {
+                format.parse().unwrap_or_else(|e| {
+                    eprintln!("Ignoring SBF_TRACE_FORMAT: {e}");
+                    TraceFormat::default()
+                })
+            })
+            .unwrap_or_default()
+    })
+}
+
+/// Prefixes the digest with the program's label, if it has one, so the files
+/// of a labeled program sort together and say whose trace they hold.
+fn trace_file_stem(label: Option<&str>, digest: &str) -> String {
+    match label {
+        Some(label) => {
+            let label: String = label
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                        c
+                    } else {
+                        '_'
                     }
-                },
-            );
+                })
+                .collect();
+            format!("{label}-{digest}")
         }
+        None => digest.to_string(),
     }
 }
 
@@ -293,3 +643,17 @@ pub(crate) fn as_bytes<T>(slice: &[T]) -> &[u8] {
 pub fn compute_hash(slice: &[u8]) -> String {
     hex::encode(Sha256::digest(slice).as_slice())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::trace_file_stem;
+
+    #[test]
+    fn trace_file_stem_sanitizes_labels() {
+        assert_eq!(trace_file_stem(None, "0123abcd"), "0123abcd");
+        assert_eq!(
+            trace_file_stem(Some("my amm/v2"), "0123abcd"),
+            "my_amm_v2-0123abcd"
+        );
+    }
+}
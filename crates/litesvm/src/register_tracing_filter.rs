@@ -1,12 +1,13 @@
 use {
     nom::{
         branch::alt,
-        bytes::complete::{tag, take_while1},
-        character::complete::multispace0,
+        bytes::complete::{tag, take_while, take_while1},
+        character::complete::{char, multispace0},
         multi::separated_list1,
-        sequence::delimited,
+        sequence::{delimited, preceded},
         IResult, Parser,
     },
+    regex::Regex,
     std::collections::HashMap,
 };
 
@@ -16,6 +17,12 @@ use {
 pub enum Op {
     Eq,
     Neq,
+    /// `~=`: the value is a glob, where `*` matches any run of characters
+    /// and `?` any one character.
+    Glob,
+    /// `=~`: the value is a regular expression, matched anywhere in the
+    /// field unless anchored.
+    Regex(Regex),
 }
 
 #[derive(Debug, Clone)]
@@ -29,6 +36,7 @@ pub struct Cond<'a> {
 pub enum Expr<'a> {
     True, // always matches
     Cond(Cond<'a>),
+    Not(Box<Expr<'a>>),
     And(Vec<Expr<'a>>),
     Or(Vec<Expr<'a>>),
 }
@@ -43,8 +51,8 @@ where
     delimited(multispace0, inner, multispace0)
 }
 
-/// Parses a field name or value: alphanumeric, `_`, `-`, `.` (for example - dot
-/// for struct.field).
+/// Parses a field name: alphanumeric, `_`, `-`, `.` (for example - dot for
+/// struct.field).
 fn ident(input: &str) -> IResult<&str, &str> {
     ws(take_while1(|c: char| {
         c.is_alphanumeric() || "_-.".contains(c)
@@ -52,21 +60,46 @@ fn ident(input: &str) -> IResult<&str, &str> {
     .parse(input)
 }
 
-/// Parses a comparison operator: `==` or `!=`.
-fn op(input: &str) -> IResult<&str, Op> {
-    ws(alt((tag("!=").map(|_| Op::Neq), tag("==").map(|_| Op::Eq)))).parse(input)
+/// Parses a value: a double-quoted string, or an unquoted word that may
+/// also hold glob wildcards and the `:` and `/` of test paths.
+fn value(input: &str) -> IResult<&str, &str> {
+    ws(alt((
+        delimited(char('"'), take_while(|c: char| c != '"'), char('"')),
+        take_while1(|c: char| c.is_alphanumeric() || "_-.*?:/".contains(c)),
+    )))
+    .parse(input)
+}
+
+/// Parses a comparison operator: `==`, `!=`, `~=` or `=~`.
+fn op(input: &str) -> IResult<&str, &str> {
+    ws(alt((tag("!="), tag("=="), tag("~="), tag("=~")))).parse(input)
 }
 
-/// Parses a single condition: `field op value`.
+/// Parses a single condition: `field op value`. A `=~` value must be a
+/// valid regular expression.
 fn cond(input: &str) -> IResult<&str, Expr<'_>> {
-    (ident, op, ident)
-        .map(|(field, op, value)| Expr::Cond(Cond { field, op, value }))
+    (ident, op, value)
+        .map_res(|(field, op, value)| {
+            let op = match op {
+                "==" => Op::Eq,
+                "!=" => Op::Neq,
+                "~=" => Op::Glob,
+                _ => Op::Regex(Regex::new(value)?),
+            };
+            Ok::<_, regex::Error>(Expr::Cond(Cond { field, op, value }))
+        })
         .parse(input)
 }
 
-/// Parses an atomic expression: a parenthesized group or a single condition.
+/// Parses an atomic expression: a negated factor, a parenthesized group or
+/// a single condition.
 fn factor(input: &str) -> IResult<&str, Expr<'_>> {
-    alt((delimited(ws(tag("(")), expr_inner, ws(tag(")"))), cond)).parse(input)
+    alt((
+        preceded(ws(char('!')), factor).map(|x| Expr::Not(Box::new(x))),
+        delimited(ws(tag("(")), expr_inner, ws(tag(")"))),
+        cond,
+    ))
+    .parse(input)
 }
 
 /// Parses one or more factors joined by `&&`.
@@ -107,8 +140,9 @@ fn expr_inner(input: &str) -> IResult<&str, Expr<'_>> {
 /// Parses a filter expression string into an AST.
 /// Returns `Ok(Expr::True)` if the input is empty (matches everything).
 /// Returns `Err` if the input is malformed or has trailing garbage.
-/// Supports syntax: `field == value`, `field != value`, `&&`, `||`, and `()`
-/// for grouping.
+/// Supports syntax: `field == value`, `field != value`, `field ~= glob`,
+/// `field =~ "regex"`, `!` for negation, `&&`, `||`, and `()` for grouping.
+/// Values holding other characters than `[A-Za-z0-9_-.*?:/]` must be quoted.
 /// Example: `program_id == A || (program_id == B || program_id != C)`
 pub fn expr(input: &str) -> Result<Expr<'_>, String> {
     match expr_inner(input) {
@@ -124,6 +158,9 @@ pub fn expr(input: &str) -> Result<Expr<'_>, String> {
 /// Each field maps to a vector of string values (multi-value support).
 /// For `==`: returns true if the field contains the value.
 /// For `!=`: returns true if ALL field values are different from the value.
+/// For `~=` and `=~`: returns true if any field value matches the pattern.
+/// For `!`: returns the opposite of the negated expression, so `!(f == v)`
+/// is `f != v`.
 /// For `&&`: detects contradictory `==` on the same field (e.g.
 /// `field == A && field == B` where A != B) and short-circuits to false.
 pub fn eval(expr: &Expr, row: &HashMap<&str, Vec<String>>) -> bool {
@@ -131,11 +168,14 @@ pub fn eval(expr: &Expr, row: &HashMap<&str, Vec<String>>) -> bool {
         Expr::True => true,
         Expr::Cond(c) => {
             let vals = row.get(c.field).map(Vec::as_slice).unwrap_or(&[]);
-            match c.op {
+            match &c.op {
                 Op::Eq => vals.contains(&c.value.to_string()),
                 Op::Neq => vals.iter().all(|v| *v != c.value),
+                Op::Glob => vals.iter().any(|v| glob_match(c.value, v)),
+                Op::Regex(regex) => vals.iter().any(|v| regex.is_match(v)),
             }
         }
+        Expr::Not(x) => !eval(x, row),
         Expr::And(xs) => {
             let mut eq_by_field: HashMap<&str, &str> = HashMap::new();
             for x in xs {
@@ -156,6 +196,38 @@ pub fn eval(expr: &Expr, row: &HashMap<&str, Vec<String>>) -> bool {
     }
 }
 
+/// Matches `text` against a glob where `*` is any run of characters and `?`
+/// any one character. Backtracks only to the last `*`, which is enough
+/// since a later `*` can absorb anything an earlier one could.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` seen, and the text position it resumes at.
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -313,4 +385,59 @@ mod tests {
         let row_no_match = HashMap::from([("account.owner", vec![SYSTEM_PROGRAM.to_string()])]);
         assert!(!eval(&ast, &row_no_match));
     }
+
+    #[test]
+    fn test_negation() {
+        let filter = format!("!(program_id == {TOKEN_PROGRAM}) && !depth == 1");
+        let ast = expr(&filter).unwrap();
+        let row = HashMap::from([
+            ("program_id", vec![SYSTEM_PROGRAM.to_string()]),
+            ("depth", vec!["2".to_string()]),
+        ]);
+        assert!(eval(&ast, &row));
+
+        let row_no_match = HashMap::from([
+            ("program_id", vec![SYSTEM_PROGRAM.to_string()]),
+            ("depth", vec!["1".to_string()]),
+        ]);
+        assert!(!eval(&ast, &row_no_match));
+    }
+
+    #[test]
+    fn test_glob() {
+        let ast = expr("test ~= tests::swap_* && program_id ~= Token*").unwrap();
+        let row = HashMap::from([
+            ("test", vec!["tests::swap_exact_in".to_string()]),
+            ("program_id", vec![TOKEN_PROGRAM.to_string()]),
+        ]);
+        assert!(eval(&ast, &row));
+
+        let row_no_match = HashMap::from([
+            ("test", vec!["tests::deposit".to_string()]),
+            ("program_id", vec![TOKEN_PROGRAM.to_string()]),
+        ]);
+        assert!(!eval(&ast, &row_no_match));
+
+        assert!(glob_match("a*b?d*", "axxbcd"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("*a*a", "banana"));
+        assert!(!glob_match("a?", "a"));
+        assert!(!glob_match("*a*a", "bananas"));
+    }
+
+    #[test]
+    fn test_regex() {
+        let ast = expr(r#"outcome == failed && caller =~ "^(Token|Memo)""#).unwrap();
+        let row = HashMap::from([
+            ("outcome", vec!["failed".to_string()]),
+            ("caller", vec![SPL_MEMO.to_string(), "memo".to_string()]),
+        ]);
+        assert!(eval(&ast, &row));
+
+        // Top-level instructions have no caller.
+        let row_no_match = HashMap::from([("outcome", vec!["failed".to_string()])]);
+        assert!(!eval(&ast, &row_no_match));
+
+        assert!(expr(r#"caller =~ "(unclosed""#).is_err());
+    }
 }
@@ -0,0 +1,437 @@
+//! On-disk formats of register traces and the manifest indexing them.
+//!
+//! [`DefaultRegisterTracingCallback`](crate::register_tracing::DefaultRegisterTracingCallback)
+//! writes every trace in one of the [`TraceFormat`]s and appends a line
+//! describing it to the [`MANIFEST_FILE`] of the trace directory. The
+//! executable of every traced program is kept once, as
+//! `programs/<sha256>.so`, so traces can be disassembled after the run. The
+//! `litesvm-trace` command lists, filters and disassembles them.
+
+use {
+    solana_address::Address,
+    std::{
+        collections::HashMap,
+        fmt,
+        fs::{File, OpenOptions},
+        io::{self, BufRead, BufReader, Read, Write},
+        path::{Path, PathBuf},
+        str::FromStr,
+    },
+};
+
+/// The manifest, one [`ManifestEntry`] per line, in the trace directory.
+pub const MANIFEST_FILE: &str = "manifest.tsv";
+/// The directory, inside the trace directory, of the program executables.
+pub const PROGRAMS_DIR: &str = "programs";
+/// The extension of [`TraceFormat::Compact`] files.
+pub const COMPACT_EXTENSION: &str = "sbftrace";
+
+const MAGIC: &[u8; 8] = b"SBFTRACE";
+const VERSION: u8 = 1;
+const ZSTD_LEVEL: i32 = 3;
+const REGISTERS: usize = 12;
+
+/// How register traces are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// `.regs` and `.insns` files holding the registers and the instruction
+    /// of every step, next to `.program_id` and `.exec.sha256` files.
+    #[default]
+    Raw,
+    /// A single zstd-compressed `.sbftrace` file per trace, which only stores
+    /// the registers each step changes. Instructions are read back from the
+    /// program executable.
+    Compact,
+}
+
+impl FromStr for TraceFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "raw" => Ok(Self::Raw),
+            "compact" => Ok(Self::Compact),
+            _ => Err(format!(
+                "unknown trace format `{s}`, expected `raw` or `compact`"
+            )),
+        }
+    }
+}
+
+/// Writes `register_trace` of `program_id` in the compact format.
+///
+/// Each step stores a bit mask of the registers that differ from the
+/// previous step, followed by their new values; the whole file is then
+/// compressed with zstd.
+pub fn write_compact<W: Write>(
+    writer: W,
+    program_id: &Address,
+    register_trace: &[[u64; REGISTERS]],
+) -> io::Result<()> {
+    let mut payload = Vec::with_capacity(MAGIC.len() + 41 + register_trace.len() * 18);
+    payload.extend_from_slice(MAGIC);
+    payload.push(VERSION);
+    payload.extend_from_slice(program_id.as_ref());
+    payload.extend_from_slice(&(register_trace.len() as u64).to_le_bytes());
+    let mut previous = [0u64; REGISTERS];
+    for registers in register_trace {
+        let mask = registers
+            .iter()
+            .zip(&previous)
+            .enumerate()
+            .filter(|(_, (register, previous))| register != previous)
+            .fold(0u16, |mask, (index, _)| mask | 1 << index);
+        payload.extend_from_slice(&mask.to_le_bytes());
+        for (index, register) in registers.iter().enumerate() {
+            if mask & 1 << index != 0 {
+                payload.extend_from_slice(&register.to_le_bytes());
+            }
+        }
+        previous = *registers;
+    }
+    zstd::stream::copy_encode(payload.as_slice(), writer, ZSTD_LEVEL)
+}
+
+/// A trace read back from the compact format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactTrace {
+    pub program_id: Address,
+    pub registers: Vec<[u64; REGISTERS]>,
+}
+
+/// Reads a trace written by [`write_compact`].
+pub fn read_compact<R: Read>(reader: R) -> io::Result<CompactTrace> {
+    let payload = zstd::stream::decode_all(reader)?;
+    let mut payload = payload.as_slice();
+    if take(&mut payload, MAGIC.len())? != MAGIC {
+        return Err(invalid_data("not a compact register trace"));
+    }
+    let version = take(&mut payload, 1)?[0];
+    if version != VERSION {
+        return Err(invalid_data(format!(
+            "unsupported compact trace version {version}"
+        )));
+    }
+    let program_id = Address::try_from(take(&mut payload, 32)?)
+        .map_err(|_| invalid_data("invalid program id"))?;
+    let steps = u64::from_le_bytes(take_array(&mut payload)?);
+    let mut registers = Vec::new();
+    let mut current = [0u64; REGISTERS];
+    for _ in 0..steps {
+        let mask = u16::from_le_bytes(take_array(&mut payload)?);
+        for (index, register) in current.iter_mut().enumerate() {
+            if mask & 1 << index != 0 {
+                *register = u64::from_le_bytes(take_array(&mut payload)?);
+            }
+        }
+        registers.push(current);
+    }
+    if !payload.is_empty() {
+        return Err(invalid_data("trailing bytes after the last step"));
+    }
+    Ok(CompactTrace {
+        program_id,
+        registers,
+    })
+}
+
+/// Reads the registers of a trace file in either format: a compact
+/// `.sbftrace` file, or a raw `.regs` file.
+pub fn read_registers(path: &Path) -> io::Result<Vec<[u64; REGISTERS]>> {
+    if path
+        .extension()
+        .is_some_and(|extension| extension == COMPACT_EXTENSION)
+    {
+        return Ok(read_compact(BufReader::new(File::open(path)?))?.registers);
+    }
+    let bytes = std::fs::read(path)?;
+    let step_size = REGISTERS * size_of::<u64>();
+    if bytes.len() % step_size != 0 {
+        return Err(invalid_data("raw trace is not a whole number of steps"));
+    }
+    Ok(bytes
+        .chunks_exact(step_size)
+        .map(|step| {
+            std::array::from_fn(|index| {
+                let register = &step[index * size_of::<u64>()..][..size_of::<u64>()];
+                u64::from_ne_bytes(register.try_into().unwrap())
+            })
+        })
+        .collect())
+}
+
+fn take<'a>(payload: &mut &'a [u8], len: usize) -> io::Result<&'a [u8]> {
+    if payload.len() < len {
+        return Err(invalid_data("compact trace is truncated"));
+    }
+    let (head, rest) = payload.split_at(len);
+    *payload = rest;
+    Ok(head)
+}
+
+fn take_array<const N: usize>(payload: &mut &[u8]) -> io::Result<[u8; N]> {
+    Ok(take(payload, N)?.try_into().unwrap())
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// A trace and the invocation it was taken from: one line of the manifest.
+///
+/// Lines hold the fields in declaration order, separated by tabs, with `-`
+/// for a missing value and the outcome written as `success` or `failed`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEntry {
+    /// The first signature of the transaction.
+    pub signature: String,
+    pub instruction_index: usize,
+    pub depth: usize,
+    pub program_id: Address,
+    /// The [label](crate::LiteSVM::label) of the program, if it has one.
+    pub program_label: Option<String>,
+    pub caller: Option<Address>,
+    /// The label of the caller, if it has one.
+    pub caller_label: Option<String>,
+    pub success: Option<bool>,
+    /// The name of the thread that ran the transaction, which the test
+    /// harness names after the running test.
+    pub test: Option<String>,
+    /// SHA-256 of the program's ELF, the name of its copy in
+    /// [`PROGRAMS_DIR`]. `None` if the ELF couldn't be read.
+    pub exec_sha256: Option<String>,
+    /// The number of instructions the trace holds.
+    pub steps: usize,
+    /// The trace file, relative to the trace directory.
+    pub file: String,
+}
+
+impl ManifestEntry {
+    /// The fields of the entry, named as in the `SBF_TRACE_FILTER` language,
+    /// for [`register_tracing_filter::eval`](crate::register_tracing_filter::eval).
+    pub fn filter_row(&self) -> HashMap<&'static str, Vec<String>> {
+        let mut row = HashMap::from([
+            ("txsig", vec![self.signature.clone()]),
+            (
+                "program_id",
+                address_values(&self.program_id, self.program_label.as_deref()),
+            ),
+            (
+                "instruction_index",
+                vec![self.instruction_index.to_string()],
+            ),
+            ("depth", vec![self.depth.to_string()]),
+        ]);
+        if let Some(caller) = &self.caller {
+            row.insert(
+                "caller",
+                address_values(caller, self.caller_label.as_deref()),
+            );
+        }
+        if let Some(outcome) = self.outcome() {
+            row.insert("outcome", vec![outcome.to_string()]);
+        }
+        if let Some(test) = &self.test {
+            row.insert("test", vec![test.clone()]);
+        }
+        row
+    }
+
+    /// `success` or `failed`, if known.
+    pub fn outcome(&self) -> Option<&'static str> {
+        self.success
+            .map(|success| if success { "success" } else { "failed" })
+    }
+
+    /// Where the copy of the program executable is kept, if it was.
+    pub fn program_path(&self, trace_dir: &Path) -> Option<PathBuf> {
+        let exec_sha256 = self.exec_sha256.as_ref()?;
+        Some(
+            trace_dir
+                .join(PROGRAMS_DIR)
+                .join(exec_sha256)
+                .with_extension("so"),
+        )
+    }
+}
+
+/// An address and its label, if any: the values an address field matches.
+fn address_values(address: &Address, label: Option<&str>) -> Vec<String> {
+    std::iter::once(address.to_string())
+        .chain(label.map(str::to_string))
+        .collect()
+}
+
+impl fmt::Display for ManifestEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn or_dash<T: fmt::Display>(value: Option<T>) -> String {
+            value.map_or_else(|| "-".to_string(), |value| value.to_string())
+        }
+        // Tabs and newlines would split the line.
+        fn text(value: &Option<String>) -> String {
+            or_dash(value.as_ref().map(|value| value.replace(['\t', '\n'], " ")))
+        }
+        write!(
+            f,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.signature,
+            self.instruction_index,
+            self.depth,
+            self.program_id,
+            text(&self.program_label),
+            or_dash(self.caller.as_ref()),
+            text(&self.caller_label),
+            or_dash(self.outcome()),
+            text(&self.test),
+            or_dash(self.exec_sha256.as_ref()),
+            self.steps,
+            self.file,
+        )
+    }
+}
+
+impl FromStr for ManifestEntry {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [signature, instruction_index, depth, program_id, program_label, caller, caller_label, outcome, test, exec_sha256, steps, file] =
+            fields.as_slice()
+        else {
+            return Err(format!("expected 12 fields, found {}", fields.len()));
+        };
+        let optional = |value: &str| (value != "-").then(|| value.to_string());
+        let address = |value: &str| {
+            Address::from_str(value).map_err(|err| format!("invalid address `{value}`: {err}"))
+        };
+        let number = |value: &str| {
+            value
+                .parse()
+                .map_err(|err| format!("invalid number `{value}`: {err}"))
+        };
+        Ok(Self {
+            signature: signature.to_string(),
+            instruction_index: number(instruction_index)?,
+            depth: number(depth)?,
+            program_id: address(program_id)?,
+            program_label: optional(program_label),
+            caller: optional(caller)
+                .map(|caller| address(&caller))
+                .transpose()?,
+            caller_label: optional(caller_label),
+            success: match *outcome {
+                "success" => Some(true),
+                "failed" => Some(false),
+                "-" => None,
+                _ => return Err(format!("invalid outcome `{outcome}`")),
+            },
+            test: optional(test),
+            exec_sha256: optional(exec_sha256),
+            steps: number(steps)?,
+            file: file.to_string(),
+        })
+    }
+}
+
+/// Appends `entry` to the manifest of `trace_dir`. The line is written at
+/// once, so tests tracing in parallel don't interleave their entries.
+pub fn append_manifest(trace_dir: &Path, entry: &ManifestEntry) -> io::Result<()> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(trace_dir.join(MANIFEST_FILE))?
+        .write_all(format!("{entry}\n").as_bytes())
+}
+
+/// Reads the manifest of `trace_dir`, in the order the traces were written.
+pub fn read_manifest(trace_dir: &Path) -> io::Result<Vec<ManifestEntry>> {
+    let file = File::open(trace_dir.join(MANIFEST_FILE))?;
+    BufReader::new(file)
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.as_ref().is_ok_and(|line| line.is_empty()))
+        .map(|(index, line)| {
+            line?
+                .parse()
+                .map_err(|err| invalid_data(format!("{MANIFEST_FILE} line {}: {err}", index + 1)))
+        })
+        .collect()
+}
+
+/// Keeps a copy of a program executable in `trace_dir`, unless there is one
+/// already.
+pub(crate) fn persist_program(trace_dir: &Path, exec_sha256: &str, elf: &[u8]) -> io::Result<()> {
+    let programs_dir = trace_dir.join(PROGRAMS_DIR);
+    std::fs::create_dir_all(&programs_dir)?;
+    match File::create_new(programs_dir.join(exec_sha256).with_extension("so")) {
+        Ok(mut file) => file.write_all(elf),
+        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_address::address};
+
+    const PROGRAM: Address = address!("GtdambwDgHWrDJdVPBkEHGhCwokqgAoch162teUjJse2");
+
+    #[test]
+    fn compact_traces_roundtrip() {
+        let registers: Vec<[u64; REGISTERS]> = (0..100u64)
+            .map(|step| {
+                let mut registers = [7u64; REGISTERS];
+                registers[1] = step / 3;
+                registers[11] = step;
+                registers
+            })
+            .collect();
+        let mut bytes = Vec::new();
+        write_compact(&mut bytes, &PROGRAM, &registers).unwrap();
+        assert!(bytes.len() < size_of_val(registers.as_slice()) / 10);
+        assert_eq!(
+            read_compact(bytes.as_slice()).unwrap(),
+            CompactTrace {
+                program_id: PROGRAM,
+                registers,
+            }
+        );
+        assert!(read_compact(&bytes[..bytes.len() / 2]).is_err());
+
+        let mut empty = Vec::new();
+        write_compact(&mut empty, &PROGRAM, &[]).unwrap();
+        assert!(read_compact(empty.as_slice()).unwrap().registers.is_empty());
+    }
+
+    #[test]
+    fn manifest_lines_roundtrip() {
+        let entry = ManifestEntry {
+            signature: "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW".to_string(),
+            instruction_index: 2,
+            depth: 2,
+            program_id: PROGRAM,
+            program_label: Some("amm".to_string()),
+            caller: Some(address!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL")),
+            caller_label: Some("associated token".to_string()),
+            success: Some(false),
+            test: Some("tests::swap".to_string()),
+            exec_sha256: Some("0123abcd".to_string()),
+            steps: 1234,
+            file: "amm-0123abcd.sbftrace".to_string(),
+        };
+        assert_eq!(entry.to_string().parse(), Ok(entry.clone()));
+        let top_level = ManifestEntry {
+            program_label: None,
+            caller: None,
+            caller_label: None,
+            success: None,
+            test: None,
+            exec_sha256: None,
+            ..entry
+        };
+        let line = top_level.to_string();
+        assert_eq!(line.matches("\t-").count(), 6);
+        assert_eq!(line.parse(), Ok(top_level));
+        assert!("a\tb".parse::<ManifestEntry>().is_err());
+    }
+}
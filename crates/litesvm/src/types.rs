@@ -1,5 +1,9 @@
 use {
-    crate::format_logs::format_logs,
+    crate::{
+        format_logs::{format_logs, PrettyLogsOptions},
+        labels::Labels,
+        utils::log_events::log_events,
+    },
     solana_account::AccountSharedData,
     solana_address::Address,
     solana_instruction_error::InstructionError,
@@ -20,11 +24,154 @@ pub struct TransactionMetadata {
     pub compute_units_consumed: u64,
     pub return_data: TransactionReturnData,
     pub fee: u64,
+    /// Every program invocation the runtime ran, recorded while it ran, so
+    /// they're complete even when `logs` was truncated by the log bytes limit.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub invocations: Vec<InvocationMetadata>,
 }
 
 impl TransactionMetadata {
+    /// `logs` parsed into typed events, one per line.
+    pub fn log_events(&self) -> Vec<LogEvent> {
+        LogEvent::from_logs(&self.logs)
+    }
+
     pub fn pretty_logs(&self) -> String {
-        format_logs(&self.logs)
+        format_logs(&self.logs, &PrettyLogsOptions::default())
+    }
+
+    /// The logs rendered with another theme, extra importance rules, CPI
+    /// indentation or program labels:
+    ///
+    /// ```text
+    /// meta.pretty_logs_with(&PrettyLogsOptions {
+    ///     theme: Theme::None,
+    ///     indent: true,
+    ///     labels: Some(svm.labels()),
+    ///     ..Default::default()
+    /// })
+    /// ```
+    pub fn pretty_logs_with(&self, options: &PrettyLogsOptions) -> String {
+        format_logs(&self.logs, options)
+    }
+
+    /// Like [`pretty_logs`](Self::pretty_logs), with every labeled address
+    /// replaced by its label. Pass [`LiteSVM::labels`](crate::LiteSVM::labels).
+    pub fn pretty_logs_with_labels(&self, labels: &Labels) -> String {
+        self.pretty_logs_with(&PrettyLogsOptions {
+            labels: Some(labels),
+            ..Default::default()
+        })
+    }
+}
+
+/// One program invocation, in execution order: each top-level instruction
+/// followed by its CPIs, the same order as `inner_instructions`. The
+/// instructions after a failed one never ran, so they have no entry.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InvocationMetadata {
+    #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_with_str"))]
+    pub program_id: Address,
+    /// 1 for a top-level instruction, one more for each level of CPI.
+    pub stack_height: usize,
+    /// Compute units consumed by the invocation, including its CPIs. `None`
+    /// for builtin programs and precompiles, which aren't metered.
+    pub compute_units_consumed: Option<u64>,
+    /// Compute units left in the transaction when the invocation started.
+    /// `None` whenever `compute_units_consumed` is.
+    pub compute_units_available: Option<u64>,
+    /// The error the invocation failed with, as the runtime logs it, or
+    /// `None` if it succeeded.
+    pub error: Option<String>,
+}
+
+/// One line of a transaction's logs, parsed.
+///
+/// Lines written by a program (`Log`, `Data`, `ComputeUnitsRemaining`) carry
+/// the program and depth of the invocation that was running, so a test can
+/// assert that a program logged something at a given CPI depth:
+///
+/// ```text
+/// assert!(meta.log_events().contains(&LogEvent::Log {
+///     program_id,
+///     depth: 2,
+///     message: "Instruction: Transfer".to_string(),
+/// }));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "kind", rename_all = "snake_case")
+)]
+pub enum LogEvent {
+    /// `Program <program_id> invoke [<depth>]`. Depth 1 is a top-level
+    /// instruction.
+    Invoke {
+        #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_with_str"))]
+        program_id: Address,
+        depth: usize,
+    },
+    /// `Program log: <message>`
+    Log {
+        #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_with_str"))]
+        program_id: Address,
+        depth: usize,
+        message: String,
+    },
+    /// `Program data: <field> ...`, written by `sol_log_data`. The fields
+    /// stay base64-encoded.
+    Data {
+        #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_with_str"))]
+        program_id: Address,
+        depth: usize,
+        data: Vec<String>,
+    },
+    /// `Program consumption: <remaining> units remaining`, written by
+    /// `sol_log_compute_units`.
+    ComputeUnitsRemaining {
+        #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_with_str"))]
+        program_id: Address,
+        depth: usize,
+        remaining: u64,
+    },
+    /// `Program <program_id> consumed <consumed> of <available> compute
+    /// units`. Builtin programs don't write it.
+    Consumed {
+        #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_with_str"))]
+        program_id: Address,
+        consumed: u64,
+        available: u64,
+    },
+    /// `Program return: <program_id> <data>`, with the data base64-encoded.
+    ReturnData {
+        #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_with_str"))]
+        program_id: Address,
+        data: String,
+    },
+    /// `Program <program_id> success`
+    Success {
+        #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_with_str"))]
+        program_id: Address,
+    },
+    /// `Program <program_id> failed: <error>`
+    Failed {
+        #[cfg_attr(feature = "serde", serde(with = "crate::utils::serde_with_str"))]
+        program_id: Address,
+        error: String,
+    },
+    /// `Log truncated`: the log bytes limit dropped lines from here on.
+    Truncated,
+    /// Any other line, such as a runtime diagnostic, or a program log
+    /// outside any invocation after truncation dropped its `invoke` line.
+    Other { line: String },
+}
+
+impl LogEvent {
+    /// Parses a transaction's logs, one event per line.
+    pub fn from_logs(logs: &[String]) -> Vec<LogEvent> {
+        log_events(logs)
     }
 }
 
@@ -42,6 +189,28 @@ pub struct FailedTransactionMetadata {
     pub meta: TransactionMetadata,
 }
 
+impl FailedTransactionMetadata {
+    /// The error followed by the chain of programs that failed, outermost
+    /// first, named by `labels`:
+    /// `Error processing Instruction 0: custom program error: 0x1 (in amm > token)`.
+    pub fn describe(&self, labels: &Labels) -> String {
+        // A failed CPI fails its caller too, so the failed invocations are
+        // the path from the top-level instruction down to the culprit.
+        let failed: Vec<String> = self
+            .meta
+            .invocations
+            .iter()
+            .filter(|invocation| invocation.error.is_some())
+            .map(|invocation| labels.name(&invocation.program_id))
+            .collect();
+        if failed.is_empty() {
+            self.err.to_string()
+        } else {
+            format!("{} (in {})", self.err, failed.join(" > "))
+        }
+    }
+}
+
 impl From<ProgramError> for FailedTransactionMetadata {
     fn from(value: ProgramError) -> Self {
         FailedTransactionMetadata {
@@ -62,6 +231,7 @@ pub(crate) struct ExecutionResult {
     pub(crate) signature: Signature,
     pub(crate) compute_units_consumed: u64,
     pub(crate) inner_instructions: InnerInstructionsList,
+    pub(crate) invocations: Vec<InvocationMetadata>,
     pub(crate) return_data: TransactionReturnData,
     /// Whether the transaction can be included in a block
     pub(crate) included: bool,
@@ -76,6 +246,7 @@ impl Default for ExecutionResult {
             signature: Default::default(),
             compute_units_consumed: Default::default(),
             inner_instructions: Default::default(),
+            invocations: Default::default(),
             return_data: Default::default(),
             included: false,
             fee: 0,
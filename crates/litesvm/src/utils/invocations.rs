@@ -0,0 +1,151 @@
+use {
+    crate::types::InvocationMetadata,
+    solana_bpf_loader_program::Entrypoint,
+    solana_instruction::TRANSACTION_LEVEL_STACK_HEIGHT,
+    solana_program_runtime::{
+        invoke_context::InvokeContext,
+        solana_sbpf::{declare_builtin_function, vm::ContextObject},
+    },
+    solana_sdk_ids::native_loader,
+    solana_transaction_context::transaction::TransactionContext,
+    solana_transaction_error::TransactionError,
+    std::{cell::RefCell, collections::HashMap},
+};
+
+/// What the SBF loaders saw while running one program invocation.
+struct Metered {
+    consumed: u64,
+    available: u64,
+    error: Option<String>,
+}
+
+thread_local! {
+    /// The SBF invocations of the transaction executing on this thread, by
+    /// index in the instruction trace.
+    static METERED: RefCell<Vec<(usize, Metered)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Forgets what was metered for an earlier transaction on this thread.
+pub(crate) fn start_metering() {
+    METERED.with_borrow_mut(Vec::clear);
+}
+
+/// Whether the SBF invocation at `index_in_trace` of the transaction
+/// executing on this thread succeeded, `None` if it wasn't metered.
+#[cfg(feature = "register-tracing")]
+pub(crate) fn metered_success(index_in_trace: usize) -> Option<bool> {
+    METERED.with_borrow(|invocations| {
+        invocations
+            .iter()
+            .find(|(index, _)| *index == index_in_trace)
+            .map(|(_, metered)| metered.error.is_none())
+    })
+}
+
+declare_builtin_function!(
+    /// The entrypoint of the SBF loaders, metering each program it runs: the
+    /// compute units it consumed, including its CPIs, and its error. These are
+    /// the numbers the runtime logs, so they're kept even when the log bytes
+    /// limit drops those lines.
+    MeteredEntrypoint,
+    fn rust(
+        invoke_context: &mut InvokeContext<'static, 'static>,
+        arg0: u64,
+        arg1: u64,
+        arg2: u64,
+        arg3: u64,
+        arg4: u64,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        let instruction_context = invoke_context
+            .transaction_context
+            .get_current_instruction_context()?;
+        let index_in_trace = instruction_context.get_index_in_trace();
+        // The loader itself runs program management instructions, as any
+        // other builtin, and those aren't metered.
+        let is_management = native_loader::check_id(&instruction_context.get_program_owner()?);
+        if is_management {
+            return Entrypoint::rust(invoke_context, arg0, arg1, arg2, arg3, arg4);
+        }
+
+        let available = invoke_context.get_remaining();
+        let result = Entrypoint::rust(invoke_context, arg0, arg1, arg2, arg3, arg4);
+        let metered = Metered {
+            consumed: available.saturating_sub(invoke_context.get_remaining()),
+            available,
+            error: result.as_ref().err().map(ToString::to_string),
+        };
+        METERED.with_borrow_mut(|invocations| invocations.push((index_in_trace, metered)));
+        result
+    }
+);
+
+/// The invocations of a transaction that ran, in execution order, read from
+/// its instruction trace and completed with what [`MeteredEntrypoint`]
+/// recorded while it ran.
+///
+/// A failed instruction stops the transaction, so the instructions after it
+/// are left out, and its last invocation gets the instruction error unless
+/// the loader already recorded one.
+pub(crate) fn invocations_from_instruction_trace(
+    transaction_context: &TransactionContext,
+    result: &Result<(), TransactionError>,
+) -> Vec<InvocationMetadata> {
+    let mut metered: HashMap<usize, Metered> = METERED
+        .with_borrow_mut(std::mem::take)
+        .into_iter()
+        .collect();
+    let (last_top_level, error) = match result {
+        Err(TransactionError::InstructionError(index, error)) => {
+            (usize::from(*index), Some(error.to_string()))
+        }
+        _ => (usize::MAX, None),
+    };
+
+    // Top-level instructions head the trace and CPIs follow in execution
+    // order, so each top-level instruction is followed by the CPIs under it.
+    let mut executed: Vec<Vec<InvocationMetadata>> = Vec::new();
+    // The top-level ancestor of each instruction in the trace.
+    let mut top_level_ancestor: Vec<usize> = Vec::new();
+    for index_in_trace in 0..transaction_context.get_instruction_trace_length() {
+        let Ok(instruction_context) =
+            transaction_context.get_instruction_context_at_index_in_trace(index_in_trace)
+        else {
+            break;
+        };
+        let stack_height = instruction_context.get_stack_height();
+        let ancestor = if stack_height == TRANSACTION_LEVEL_STACK_HEIGHT {
+            index_in_trace
+        } else {
+            top_level_ancestor
+                .get(instruction_context.get_index_of_caller())
+                .copied()
+                .unwrap_or(usize::MAX)
+        };
+        top_level_ancestor.push(ancestor);
+        if ancestor > last_top_level {
+            continue;
+        }
+        let Ok(program_id) = instruction_context.get_program_key() else {
+            continue;
+        };
+        let metered = metered.remove(&index_in_trace);
+        let invocation = InvocationMetadata {
+            program_id: *program_id,
+            stack_height,
+            compute_units_consumed: metered.as_ref().map(|metered| metered.consumed),
+            compute_units_available: metered.as_ref().map(|metered| metered.available),
+            error: metered.and_then(|metered| metered.error),
+        };
+        if ancestor == index_in_trace {
+            executed.push(vec![invocation]);
+        } else if let Some(invocations) = executed.get_mut(ancestor) {
+            invocations.push(invocation);
+        }
+    }
+
+    let mut invocations: Vec<InvocationMetadata> = executed.into_iter().flatten().collect();
+    if let (Some(error), Some(last)) = (error, invocations.last_mut()) {
+        last.error.get_or_insert(error);
+    }
+    invocations
+}
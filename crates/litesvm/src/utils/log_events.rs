@@ -0,0 +1,272 @@
+use {crate::types::LogEvent, solana_address::Address, std::str::FromStr};
+
+/// The shape of one log line on its own, before the invocation that wrote it
+/// is known.
+pub(crate) enum Line<'a> {
+    Invoke {
+        program_id: Address,
+        depth: usize,
+    },
+    Log(&'a str),
+    Data(&'a str),
+    Consumed {
+        program_id: Address,
+        consumed: u64,
+        available: u64,
+    },
+    Remaining(u64),
+    Return {
+        program_id: Address,
+        data: &'a str,
+    },
+    Success(Address),
+    Failed {
+        program_id: Address,
+        error: &'a str,
+    },
+    Truncated,
+    Other,
+}
+
+/// Matches the exact shapes the runtime writes. Program logs are always
+/// prefixed (`Program log:`, `Program data:`), so a program can't forge the
+/// `invoke`, `consumed` and status lines.
+pub(crate) fn classify(line: &str) -> Line<'_> {
+    if line == "Log truncated" {
+        return Line::Truncated;
+    }
+    let Some(rest) = line.strip_prefix("Program ") else {
+        return Line::Other;
+    };
+    if let Some(message) = rest.strip_prefix("log: ") {
+        return Line::Log(message);
+    }
+    if let Some(data) = rest.strip_prefix("data: ") {
+        return Line::Data(data);
+    }
+    if let Some(remaining) = rest
+        .strip_prefix("consumption: ")
+        .and_then(|rest| rest.strip_suffix(" units remaining"))
+        .and_then(|remaining| remaining.parse().ok())
+    {
+        return Line::Remaining(remaining);
+    }
+    if let Some((program_id, data)) = rest
+        .strip_prefix("return: ")
+        .and_then(|rest| rest.split_once(' '))
+    {
+        return match Address::from_str(program_id) {
+            Ok(program_id) => Line::Return { program_id, data },
+            Err(_) => Line::Other,
+        };
+    }
+
+    let Some((program_id, event)) = rest.split_once(' ') else {
+        return Line::Other;
+    };
+    let Ok(program_id) = Address::from_str(program_id) else {
+        return Line::Other;
+    };
+    if let Some(depth) = event
+        .strip_prefix("invoke [")
+        .and_then(|rest| rest.strip_suffix(']'))
+        .and_then(|depth| depth.parse().ok())
+    {
+        Line::Invoke { program_id, depth }
+    } else if let Some(rest) = event.strip_prefix("consumed ") {
+        let mut words = rest.split(' ');
+        match (
+            words.next().map(str::parse),
+            words.next(),
+            words.next().map(str::parse),
+        ) {
+            (Some(Ok(consumed)), Some("of"), Some(Ok(available))) => Line::Consumed {
+                program_id,
+                consumed,
+                available,
+            },
+            _ => Line::Other,
+        }
+    } else if event == "success" {
+        Line::Success(program_id)
+    } else if let Some(error) = event.strip_prefix("failed: ") {
+        Line::Failed { program_id, error }
+    } else {
+        Line::Other
+    }
+}
+
+/// One event per line of `logs`. Lines a program writes are attributed to
+/// the innermost invocation still running; with none running they are
+/// [`LogEvent::Other`].
+pub(crate) fn log_events(logs: &[String]) -> Vec<LogEvent> {
+    // The program and depth of each invocation still running.
+    let mut stack: Vec<(Address, usize)> = Vec::new();
+    logs.iter()
+        .map(|line| {
+            let current = stack.last().copied();
+            let other = || LogEvent::Other { line: line.clone() };
+            match classify(line) {
+                Line::Invoke { program_id, depth } => {
+                    stack.push((program_id, depth));
+                    LogEvent::Invoke { program_id, depth }
+                }
+                Line::Log(message) => match current {
+                    Some((program_id, depth)) => LogEvent::Log {
+                        program_id,
+                        depth,
+                        message: message.to_string(),
+                    },
+                    None => other(),
+                },
+                Line::Data(data) => match current {
+                    Some((program_id, depth)) => LogEvent::Data {
+                        program_id,
+                        depth,
+                        data: data
+                            .split(' ')
+                            .filter(|field| !field.is_empty())
+                            .map(str::to_string)
+                            .collect(),
+                    },
+                    None => other(),
+                },
+                Line::Remaining(remaining) => match current {
+                    Some((program_id, depth)) => LogEvent::ComputeUnitsRemaining {
+                        program_id,
+                        depth,
+                        remaining,
+                    },
+                    None => other(),
+                },
+                Line::Consumed {
+                    program_id,
+                    consumed,
+                    available,
+                } => LogEvent::Consumed {
+                    program_id,
+                    consumed,
+                    available,
+                },
+                Line::Return { program_id, data } => LogEvent::ReturnData {
+                    program_id,
+                    data: data.to_string(),
+                },
+                Line::Success(program_id) => {
+                    pop(&mut stack, &program_id);
+                    LogEvent::Success { program_id }
+                }
+                Line::Failed { program_id, error } => {
+                    pop(&mut stack, &program_id);
+                    LogEvent::Failed {
+                        program_id,
+                        error: error.to_string(),
+                    }
+                }
+                Line::Truncated => LogEvent::Truncated,
+                Line::Other => other(),
+            }
+        })
+        .collect()
+}
+
+/// Truncation can drop an `invoke` line, so only close the innermost
+/// invocation if it is the one reporting.
+fn pop(stack: &mut Vec<(Address, usize)>, program_id: &Address) {
+    if stack.last().is_some_and(|(top, _)| top == program_id) {
+        stack.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_address::address};
+
+    const PROG_A: Address = address!("GtdambwDgHWrDJdVPBkEHGhCwokqgAoch162teUjJse2");
+    const PROG_B: Address = address!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+    #[test]
+    fn events_attribute_logs_to_the_running_invocation() {
+        let logs = [
+            format!("Program {PROG_A} invoke [1]"),
+            "Program log: Instruction: Swap".to_string(),
+            format!("Program {PROG_B} invoke [2]"),
+            "Program data: AQID BAU=".to_string(),
+            "Program consumption: 1234 units remaining".to_string(),
+            format!("Program {PROG_B} consumed 100 of 1500 compute units"),
+            format!("Program return: {PROG_B} AQ=="),
+            format!("Program {PROG_B} success"),
+            "Program log: back in A".to_string(),
+            format!("Program {PROG_A} failed: custom program error: 0x1"),
+            "Program log: outside any invocation".to_string(),
+            "Log truncated".to_string(),
+        ];
+        assert_eq!(
+            log_events(&logs),
+            [
+                LogEvent::Invoke {
+                    program_id: PROG_A,
+                    depth: 1,
+                },
+                LogEvent::Log {
+                    program_id: PROG_A,
+                    depth: 1,
+                    message: "Instruction: Swap".to_string(),
+                },
+                LogEvent::Invoke {
+                    program_id: PROG_B,
+                    depth: 2,
+                },
+                LogEvent::Data {
+                    program_id: PROG_B,
+                    depth: 2,
+                    data: vec!["AQID".to_string(), "BAU=".to_string()],
+                },
+                LogEvent::ComputeUnitsRemaining {
+                    program_id: PROG_B,
+                    depth: 2,
+                    remaining: 1234,
+                },
+                LogEvent::Consumed {
+                    program_id: PROG_B,
+                    consumed: 100,
+                    available: 1500,
+                },
+                LogEvent::ReturnData {
+                    program_id: PROG_B,
+                    data: "AQ==".to_string(),
+                },
+                LogEvent::Success { program_id: PROG_B },
+                LogEvent::Log {
+                    program_id: PROG_A,
+                    depth: 1,
+                    message: "back in A".to_string(),
+                },
+                LogEvent::Failed {
+                    program_id: PROG_A,
+                    error: "custom program error: 0x1".to_string(),
+                },
+                LogEvent::Other {
+                    line: "Program log: outside any invocation".to_string(),
+                },
+                LogEvent::Truncated,
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_runtime_lines_are_other() {
+        for line in [
+            "Program not-a-valid-base58-pubkey invoke [1]".to_string(),
+            format!("Program {PROG_A} invoke [one]"),
+            format!("Program {PROG_A} consumed lots of compute units"),
+            format!("Program {PROG_A} is not deployed"),
+            "Program is not deployed".to_string(),
+        ] {
+            assert_eq!(
+                log_events(std::slice::from_ref(&line)),
+                [LogEvent::Other { line }]
+            );
+        }
+    }
+}
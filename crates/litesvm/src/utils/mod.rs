@@ -10,6 +10,8 @@ use {
 };
 
 pub mod inner_instructions;
+pub(crate) mod invocations;
+pub(crate) mod log_events;
 pub mod rent;
 #[cfg(feature = "serde")]
 pub mod serde_with_str;
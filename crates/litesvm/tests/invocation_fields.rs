@@ -0,0 +1,151 @@
+use {
+    litesvm::{
+        register_tracing::{invocation_fields, InvocationFields},
+        InvocationInspectCallback, LiteSVM,
+    },
+    solana_address::{address, Address},
+    solana_keypair::Keypair,
+    solana_message::Message,
+    solana_program_pack::Pack,
+    solana_program_runtime::invoke_context::InvokeContext,
+    solana_signer::Signer,
+    solana_transaction::{sanitized::SanitizedTransaction, Transaction},
+    solana_transaction_context::IndexOfAccount,
+    spl_associated_token_account_interface::instruction::create_associated_token_account,
+    spl_token_interface::state::Mint,
+    std::sync::{Arc, Mutex},
+};
+
+const ATA_PROGRAM: Address = address!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+const SYSTEM_PROGRAM: Address = address!("11111111111111111111111111111111");
+
+struct RecordFields(Arc<Mutex<Vec<InvocationFields>>>);
+
+impl InvocationInspectCallback for RecordFields {
+    fn before_invocation(
+        &self,
+        _: &LiteSVM,
+        _: &SanitizedTransaction,
+        _: &[IndexOfAccount],
+        _: &mut InvokeContext,
+        _: bool,
+    ) {
+    }
+
+    fn after_invocation(
+        &self,
+        _: &LiteSVM,
+        _: &SanitizedTransaction,
+        _: &[IndexOfAccount],
+        invoke_context: &InvokeContext,
+        _: bool,
+    ) {
+        *self.0.lock().unwrap() = invocation_fields(invoke_context);
+    }
+}
+
+#[test]
+fn invocation_fields_locate_cpis() {
+    let mut svm = LiteSVM::new();
+    let recorded = Arc::new(Mutex::new(Vec::new()));
+    svm.set_invocation_inspect_callback(RecordFields(recorded.clone()));
+    let payer_kp = Keypair::new();
+    let payer_pk = payer_kp.pubkey();
+    let mint_kp = Keypair::new();
+    let mint_pk = mint_kp.pubkey();
+    svm.airdrop(&payer_pk, 1_000_000_000).unwrap();
+
+    let ixs = [
+        solana_system_interface::instruction::create_account(
+            &payer_pk,
+            &mint_pk,
+            svm.minimum_balance_for_rent_exemption(Mint::LEN),
+            Mint::LEN as u64,
+            &spl_token_interface::ID,
+        ),
+        spl_token_interface::instruction::initialize_mint2(
+            &spl_token_interface::ID,
+            &mint_pk,
+            &payer_pk,
+            None,
+            8,
+        )
+        .unwrap(),
+        create_associated_token_account(&payer_pk, &payer_pk, &mint_pk, &spl_token_interface::ID),
+    ];
+    svm.send_transaction(Transaction::new(
+        &[&payer_kp, &mint_kp],
+        Message::new(&ixs, Some(&payer_pk)),
+        svm.latest_blockhash(),
+    ))
+    .unwrap();
+
+    let fields = recorded.lock().unwrap().clone();
+    // The top-level instructions come first, then the CPIs of the
+    // associated token program. Only SBF programs are metered, so the
+    // system program has no outcome.
+    let outcome = |program_id: Address| (program_id != SYSTEM_PROGRAM).then_some(true);
+    let programs = [SYSTEM_PROGRAM, spl_token_interface::ID, ATA_PROGRAM];
+    for (index, program_id) in programs.into_iter().enumerate() {
+        assert_eq!(
+            fields[index],
+            InvocationFields {
+                instruction_index: index,
+                depth: 1,
+                caller: None,
+                success: outcome(program_id),
+            }
+        );
+    }
+    let cpis = [
+        spl_token_interface::ID,
+        SYSTEM_PROGRAM,
+        spl_token_interface::ID,
+        spl_token_interface::ID,
+    ];
+    assert_eq!(fields.len(), 3 + cpis.len());
+    for (cpi, program_id) in fields[3..].iter().zip(cpis) {
+        assert_eq!(
+            *cpi,
+            InvocationFields {
+                instruction_index: 2,
+                depth: 2,
+                caller: Some(ATA_PROGRAM),
+                success: outcome(program_id),
+            }
+        );
+    }
+}
+
+#[test]
+fn invocation_fields_record_failures() {
+    let mut svm = LiteSVM::new();
+    let recorded = Arc::new(Mutex::new(Vec::new()));
+    svm.set_invocation_inspect_callback(RecordFields(recorded.clone()));
+    let payer_kp = Keypair::new();
+    let payer_pk = payer_kp.pubkey();
+    svm.airdrop(&payer_pk, 1_000_000_000).unwrap();
+
+    // Memo data must be valid UTF-8, so the second memo fails and the third
+    // never runs.
+    let memo = |data: &[u8]| solana_instruction::Instruction {
+        program_id: address!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr"),
+        accounts: vec![],
+        data: data.to_vec(),
+    };
+    let ixs = [memo(b"ok"), memo(&[0xff, 0xfe]), memo(b"unreached")];
+    svm.send_transaction(Transaction::new(
+        &[&payer_kp],
+        Message::new(&ixs, Some(&payer_pk)),
+        svm.latest_blockhash(),
+    ))
+    .unwrap_err();
+
+    let outcomes: Vec<_> = recorded
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|fields| fields.success)
+        .collect();
+    assert_eq!(outcomes, [Some(true), Some(false), None]);
+}
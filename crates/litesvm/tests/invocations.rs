@@ -0,0 +1,160 @@
+use {
+    litesvm::{types::TransactionMetadata, LiteSVM},
+    solana_address::{address, Address},
+    solana_instruction::{AccountMeta, Instruction},
+    solana_keypair::Keypair,
+    solana_message::Message,
+    solana_program_pack::Pack,
+    solana_signer::Signer,
+    solana_transaction::Transaction,
+    spl_associated_token_account_interface::instruction::create_associated_token_account,
+    spl_token_interface::state::Mint,
+    std::collections::HashSet,
+};
+
+const ATA_PROGRAM: Address = address!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+const MEMO_PROGRAM: Address = address!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+const SYSTEM_PROGRAM: Address = address!("11111111111111111111111111111111");
+
+/// Creates a mint and an associated token account for it: three top-level
+/// instructions, the last with four CPIs.
+fn create_mint_and_ata(svm: &mut LiteSVM) -> TransactionMetadata {
+    let payer_kp = Keypair::new();
+    let payer_pk = payer_kp.pubkey();
+    let mint_kp = Keypair::new();
+    let mint_pk = mint_kp.pubkey();
+    svm.airdrop(&payer_pk, 1_000_000_000).unwrap();
+
+    let ixs = [
+        solana_system_interface::instruction::create_account(
+            &payer_pk,
+            &mint_pk,
+            svm.minimum_balance_for_rent_exemption(Mint::LEN),
+            Mint::LEN as u64,
+            &spl_token_interface::ID,
+        ),
+        spl_token_interface::instruction::initialize_mint2(
+            &spl_token_interface::ID,
+            &mint_pk,
+            &payer_pk,
+            None,
+            8,
+        )
+        .unwrap(),
+        create_associated_token_account(&payer_pk, &payer_pk, &mint_pk, &spl_token_interface::ID),
+    ];
+    svm.send_transaction(Transaction::new(
+        &[&payer_kp, &mint_kp],
+        Message::new(&ixs, Some(&payer_pk)),
+        svm.latest_blockhash(),
+    ))
+    .unwrap()
+}
+
+#[test]
+fn invocations_survive_log_truncation() {
+    let mut svm = LiteSVM::new().with_log_bytes_limit(Some(100));
+    let meta = create_mint_and_ata(&mut svm);
+
+    assert_eq!(meta.logs.last().unwrap(), "Log truncated");
+    assert_eq!(
+        meta.invocations
+            .iter()
+            .map(|invocation| (invocation.program_id, invocation.stack_height))
+            .collect::<Vec<_>>(),
+        [
+            (SYSTEM_PROGRAM, 1),
+            (spl_token_interface::ID, 1),
+            (ATA_PROGRAM, 1),
+            (spl_token_interface::ID, 2),
+            (SYSTEM_PROGRAM, 2),
+            (spl_token_interface::ID, 2),
+            (spl_token_interface::ID, 2),
+        ]
+    );
+    // The CPIs are the inner instructions, in the same order.
+    assert_eq!(meta.inner_instructions[2].len(), 4);
+    assert!(meta
+        .invocations
+        .iter()
+        .all(|invocation| invocation.error.is_none()));
+    // Builtins don't report compute units; BPF programs do.
+    assert_eq!(meta.invocations[0].compute_units_consumed, None);
+    let ata = &meta.invocations[2];
+    let total: u64 = meta.invocations[3..]
+        .iter()
+        .filter_map(|invocation| invocation.compute_units_consumed)
+        .sum();
+    assert!(ata.compute_units_consumed.unwrap() > total);
+    assert!(ata.compute_units_available.is_some());
+}
+
+#[test]
+fn invocations_meter_what_the_runtime_logs() {
+    let mut svm = LiteSVM::new().with_log_bytes_limit(None);
+    let meta = create_mint_and_ata(&mut svm);
+
+    let metered: Vec<_> = meta
+        .invocations
+        .iter()
+        .filter_map(|invocation| {
+            Some(format!(
+                "Program {} consumed {} of {} compute units",
+                invocation.program_id,
+                invocation.compute_units_consumed?,
+                invocation.compute_units_available?
+            ))
+        })
+        .collect();
+    let logged: Vec<_> = meta
+        .logs
+        .iter()
+        .filter(|log| log.contains(" consumed "))
+        .cloned()
+        .collect();
+    assert_eq!(metered.len(), 5);
+    // Invocations are in execution order and `consumed` lines come when a
+    // program returns, so compare them as sets.
+    assert_eq!(
+        metered.iter().collect::<HashSet<_>>(),
+        logged.iter().collect::<HashSet<_>>()
+    );
+}
+
+#[test]
+fn invocations_record_failures() {
+    let mut svm = LiteSVM::new();
+    let payer_kp = Keypair::new();
+    let payer_pk = payer_kp.pubkey();
+    svm.airdrop(&payer_pk, 1_000_000_000).unwrap();
+
+    // Memo data must be valid UTF-8.
+    let memo_ix = Instruction {
+        program_id: MEMO_PROGRAM,
+        accounts: vec![AccountMeta::new_readonly(payer_pk, true)],
+        data: vec![0xff, 0xfe],
+    };
+    let failed = svm
+        .send_transaction(Transaction::new_signed_with_payer(
+            &[memo_ix],
+            Some(&payer_pk),
+            &[&payer_kp],
+            svm.latest_blockhash(),
+        ))
+        .unwrap_err();
+
+    let [invocation] = failed.meta.invocations.as_slice() else {
+        panic!("expected one invocation: {:?}", failed.meta.invocations);
+    };
+    assert_eq!(invocation.program_id, MEMO_PROGRAM);
+    assert!(invocation.compute_units_consumed.is_some());
+    let error = invocation.error.as_deref().unwrap();
+    assert!(
+        error.starts_with("invalid utf-8"),
+        "unexpected error: {error}"
+    );
+    assert_eq!(
+        failed.meta.logs.last().unwrap(),
+        &format!("Program {MEMO_PROGRAM} failed: {error}")
+    );
+}
@@ -0,0 +1,66 @@
+use {
+    litesvm::LiteSVM,
+    solana_address::{address, Address},
+    solana_instruction::{AccountMeta, Instruction},
+    solana_keypair::Keypair,
+    solana_signer::Signer,
+    solana_transaction::Transaction,
+};
+
+const MEMO_PROGRAM: Address = address!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+fn memo_tx(svm: &LiteSVM, payer_kp: &Keypair, data: &[u8]) -> Transaction {
+    let payer_pk = payer_kp.pubkey();
+    let memo_ix = Instruction {
+        program_id: MEMO_PROGRAM,
+        accounts: vec![AccountMeta::new_readonly(payer_pk, true)],
+        data: data.to_vec(),
+    };
+    Transaction::new_signed_with_payer(
+        &[memo_ix],
+        Some(&payer_pk),
+        &[payer_kp],
+        svm.latest_blockhash(),
+    )
+}
+
+#[test]
+fn pretty_logs_use_labels() {
+    let mut svm = LiteSVM::new();
+    let payer_kp = Keypair::new();
+    let payer_pk = payer_kp.pubkey();
+    svm.airdrop(&payer_pk, 1_000_000_000).unwrap();
+    svm.label(payer_pk, "alice");
+
+    let meta = svm
+        .send_transaction(memo_tx(&svm, &payer_kp, b"hello"))
+        .unwrap();
+
+    let logs = meta.pretty_logs_with_labels(svm.labels());
+    assert!(logs.contains("Program memo invoke [1]"), "{logs}");
+    // The memo program logs its signers.
+    assert!(logs.contains("alice"), "{logs}");
+    assert!(!logs.contains(&payer_pk.to_string()), "{logs}");
+    assert!(!logs.contains(&MEMO_PROGRAM.to_string()), "{logs}");
+    assert!(meta.pretty_logs().contains(&MEMO_PROGRAM.to_string()));
+}
+
+#[test]
+fn failures_name_the_failing_program() {
+    let mut svm = LiteSVM::new();
+    let payer_kp = Keypair::new();
+    svm.airdrop(&payer_kp.pubkey(), 1_000_000_000).unwrap();
+    svm.label(MEMO_PROGRAM, "my-memo");
+
+    // Memo data must be valid UTF-8.
+    let failed = svm
+        .send_transaction(memo_tx(&svm, &payer_kp, &[0xff, 0xfe]))
+        .unwrap_err();
+
+    let description = failed.describe(svm.labels());
+    assert!(
+        description.starts_with("Error processing Instruction 0: "),
+        "{description}"
+    );
+    assert!(description.ends_with(" (in my-memo)"), "{description}");
+}
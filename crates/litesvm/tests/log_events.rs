@@ -0,0 +1,87 @@
+use {
+    litesvm::{types::LogEvent, LiteSVM},
+    solana_address::{address, Address},
+    solana_keypair::Keypair,
+    solana_message::Message,
+    solana_program_pack::Pack,
+    solana_signer::Signer,
+    solana_transaction::Transaction,
+    spl_associated_token_account_interface::instruction::create_associated_token_account,
+    spl_token_interface::state::Mint,
+};
+
+const ATA_PROGRAM: Address = address!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+#[test]
+fn log_events_attribute_cpi_logs() {
+    let mut svm = LiteSVM::new();
+    let payer_kp = Keypair::new();
+    let payer_pk = payer_kp.pubkey();
+    let mint_kp = Keypair::new();
+    let mint_pk = mint_kp.pubkey();
+    svm.airdrop(&payer_pk, 1_000_000_000).unwrap();
+
+    let ixs = [
+        solana_system_interface::instruction::create_account(
+            &payer_pk,
+            &mint_pk,
+            svm.minimum_balance_for_rent_exemption(Mint::LEN),
+            Mint::LEN as u64,
+            &spl_token_interface::ID,
+        ),
+        spl_token_interface::instruction::initialize_mint2(
+            &spl_token_interface::ID,
+            &mint_pk,
+            &payer_pk,
+            None,
+            8,
+        )
+        .unwrap(),
+        create_associated_token_account(&payer_pk, &payer_pk, &mint_pk, &spl_token_interface::ID),
+    ];
+    let meta = svm
+        .send_transaction(Transaction::new(
+            &[&payer_kp, &mint_kp],
+            Message::new(&ixs, Some(&payer_pk)),
+            svm.latest_blockhash(),
+        ))
+        .unwrap();
+
+    let events = meta.log_events();
+    assert_eq!(events, LogEvent::from_logs(&meta.logs));
+    assert_eq!(events.len(), meta.logs.len());
+    assert_eq!(
+        events[0],
+        LogEvent::Invoke {
+            program_id: solana_sdk_ids::system_program::ID,
+            depth: 1,
+        }
+    );
+    assert!(events.contains(&LogEvent::Log {
+        program_id: ATA_PROGRAM,
+        depth: 1,
+        message: "Initialize the associated token account".to_string(),
+    }));
+    // The associated token program asks the token program for the account
+    // size through a CPI, which returns it.
+    let get_size = events
+        .iter()
+        .position(|event| {
+            *event
+                == LogEvent::Invoke {
+                    program_id: spl_token_interface::ID,
+                    depth: 2,
+                }
+        })
+        .unwrap();
+    assert!(matches!(
+        &events[get_size + 2],
+        LogEvent::ReturnData { program_id, .. } if *program_id == spl_token_interface::ID
+    ));
+    assert_eq!(
+        events.last(),
+        Some(&LogEvent::Success {
+            program_id: ATA_PROGRAM
+        })
+    );
+}
@@ -0,0 +1,139 @@
+use {
+    litesvm::{
+        register_tracing::DefaultRegisterTracingCallback,
+        register_tracing_filter::{eval, expr},
+        register_tracing_format::{read_compact, read_manifest, read_registers, TraceFormat},
+        LiteSVM,
+    },
+    solana_address::{address, Address},
+    solana_keypair::Keypair,
+    solana_message::Message,
+    solana_program_pack::Pack,
+    solana_signer::Signer,
+    solana_transaction::Transaction,
+    spl_associated_token_account_interface::instruction::create_associated_token_account,
+    spl_token_interface::state::Mint,
+    std::{fs::File, path::Path},
+};
+
+const ATA_PROGRAM: Address = address!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// Creates a mint and an associated token account for it, tracing into
+/// `trace_dir`.
+fn trace_ata_creation(trace_dir: &Path, format: TraceFormat) -> String {
+    let mut svm = LiteSVM::new_debuggable(true);
+    svm.set_invocation_inspect_callback(
+        DefaultRegisterTracingCallback {
+            sbf_trace_dir: trace_dir.to_string_lossy().into_owned(),
+            sbf_trace_filter: String::new(),
+            ..Default::default()
+        }
+        .with_trace_format(format),
+    );
+    svm.label(ATA_PROGRAM, "associated token");
+    let payer_kp = Keypair::new();
+    let payer_pk = payer_kp.pubkey();
+    let mint_kp = Keypair::new();
+    let mint_pk = mint_kp.pubkey();
+    svm.airdrop(&payer_pk, 1_000_000_000).unwrap();
+
+    let ixs = [
+        solana_system_interface::instruction::create_account(
+            &payer_pk,
+            &mint_pk,
+            svm.minimum_balance_for_rent_exemption(Mint::LEN),
+            Mint::LEN as u64,
+            &spl_token_interface::ID,
+        ),
+        spl_token_interface::instruction::initialize_mint2(
+            &spl_token_interface::ID,
+            &mint_pk,
+            &payer_pk,
+            None,
+            8,
+        )
+        .unwrap(),
+        create_associated_token_account(&payer_pk, &payer_pk, &mint_pk, &spl_token_interface::ID),
+    ];
+    let tx = Transaction::new(
+        &[&payer_kp, &mint_kp],
+        Message::new(&ixs, Some(&payer_pk)),
+        svm.latest_blockhash(),
+    );
+    let signature = tx.signatures[0].to_string();
+    svm.send_transaction(tx).unwrap();
+    signature
+}
+
+#[test]
+fn compact_traces_are_indexed_by_the_manifest() {
+    let trace_dir = tempfile::tempdir().unwrap();
+    let signature = trace_ata_creation(trace_dir.path(), TraceFormat::Compact);
+
+    let manifest = read_manifest(trace_dir.path()).unwrap();
+    // The system program is a builtin, so only the token initialization and
+    // the associated token program with its CPIs are traced.
+    assert!(manifest.len() > 2, "{manifest:?}");
+    assert!(manifest.iter().all(|entry| entry.signature == signature
+        && entry.success == Some(true)
+        && entry.file.ends_with(".sbftrace")));
+    let top_level = manifest
+        .iter()
+        .find(|entry| entry.program_id == ATA_PROGRAM)
+        .unwrap();
+    assert_eq!((top_level.instruction_index, top_level.depth), (2, 1));
+    assert_eq!(top_level.program_label.as_deref(), Some("associated token"));
+    assert_eq!(top_level.caller, None);
+    let cpi = manifest
+        .iter()
+        .find(|entry| entry.caller == Some(ATA_PROGRAM))
+        .unwrap();
+    assert_eq!((cpi.instruction_index, cpi.depth), (2, 2));
+    assert_eq!(cpi.caller_label.as_deref(), Some("associated token"));
+    // Programs are matched by label too, as when tracing.
+    let by_caller_label = expr(r#"caller == "associated token""#).unwrap();
+    assert!(eval(&by_caller_label, &cpi.filter_row()));
+    assert!(!eval(&by_caller_label, &top_level.filter_row()));
+    assert_eq!(
+        top_level.test.as_deref(),
+        Some("compact_traces_are_indexed_by_the_manifest")
+    );
+
+    for entry in &manifest {
+        let trace = read_compact(File::open(trace_dir.path().join(&entry.file)).unwrap()).unwrap();
+        assert_eq!(trace.program_id, entry.program_id);
+        assert_eq!(trace.registers.len(), entry.steps);
+        // Every traced program is kept once, for disassembly.
+        assert!(entry.program_path(trace_dir.path()).unwrap().is_file());
+    }
+}
+
+#[test]
+fn raw_traces_are_indexed_by_the_manifest() {
+    let raw_dir = tempfile::tempdir().unwrap();
+    trace_ata_creation(raw_dir.path(), TraceFormat::Raw);
+    let compact_dir = tempfile::tempdir().unwrap();
+    trace_ata_creation(compact_dir.path(), TraceFormat::Compact);
+
+    let raw = read_manifest(raw_dir.path()).unwrap();
+    let compact = read_manifest(compact_dir.path()).unwrap();
+    assert_eq!(raw.len(), compact.len());
+    for (raw, compact) in raw.iter().zip(&compact) {
+        assert!(raw.file.ends_with(".regs"));
+        let raw_registers = read_registers(&raw_dir.path().join(&raw.file)).unwrap();
+        let compact_registers = read_registers(&compact_dir.path().join(&compact.file)).unwrap();
+        assert_eq!(raw_registers.len(), raw.steps);
+        assert_eq!(compact_registers.len(), compact.steps);
+        assert_eq!(
+            (raw.program_id, raw.depth, raw.instruction_index),
+            (compact.program_id, compact.depth, compact.instruction_index)
+        );
+        let raw_size = std::fs::metadata(raw_dir.path().join(&raw.file))
+            .unwrap()
+            .len();
+        let compact_size = std::fs::metadata(compact_dir.path().join(&compact.file))
+            .unwrap()
+            .len();
+        assert!(compact_size < raw_size, "{compact_size} >= {raw_size}");
+    }
+}
@@ -1,9 +1,9 @@
 use {
-    litesvm::LiteSVM,
+    litesvm::{error::LiteSVMError, LiteSVM},
     solana_account::Account,
     solana_address::{address, Address},
     solana_clock::Clock,
-    solana_instruction::{account_meta::AccountMeta, Instruction},
+    solana_instruction::{account_meta::AccountMeta, error::InstructionError, Instruction},
     solana_keypair::Keypair,
     solana_loader_v3_interface::{
         get_program_data_address, instruction::UpgradeableLoaderInstruction,
@@ -14,6 +14,8 @@ use {
     solana_sdk_ids::bpf_loader_upgradeable,
     solana_signer::Signer,
     solana_transaction::Transaction,
+    solana_transaction_error::TransactionError,
+    spl_token_interface::error::TokenError,
     std::path::PathBuf,
     wincode::{Deserialize, Serialize},
 };
@@ -154,3 +156,189 @@ fn close_upgradeable_program_keeps_vm_usable() {
         );
     }
 }
+
+#[test]
+fn closed_program_can_not_be_invoked() {
+    let memo_bytes = include_bytes!("../src/programs/elf/spl_memo-4.0.0.so");
+    let authority_kp = Keypair::new();
+    let authority = authority_kp.pubkey();
+    let program_id = Address::new_unique();
+    let mut svm = LiteSVM::new();
+    svm.airdrop(&authority, LAMPORTS_PER_SOL).unwrap();
+    svm.add_program(program_id, memo_bytes).unwrap();
+    let programdata_address = set_program_upgrade_authority(&mut svm, program_id, authority);
+    svm.warp_to_slot(1);
+
+    // Closing writes both the programdata and the program account. The
+    // program must be reloaded against the closed programdata, or the cache
+    // keeps serving the old code.
+    let close_ix = Instruction::new_with_bytes(
+        bpf_loader_upgradeable::id(),
+        &UpgradeableLoaderInstruction::serialize(&UpgradeableLoaderInstruction::Close {
+            tombstone: false,
+        })
+        .unwrap(),
+        vec![
+            AccountMeta::new(programdata_address, false),
+            AccountMeta::new(authority, false),
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(program_id, false),
+        ],
+    );
+    svm.send_transaction(Transaction::new(
+        &[&authority_kp],
+        Message::new_with_blockhash(&[close_ix], Some(&authority), &svm.latest_blockhash()),
+        svm.latest_blockhash(),
+    ))
+    .unwrap();
+
+    svm.expire_blockhash();
+    let failed = svm
+        .send_transaction(Transaction::new(
+            &[&authority_kp],
+            Message::new_with_blockhash(
+                &[Instruction::new_with_bytes(program_id, b"hello", vec![])],
+                Some(&authority),
+                &svm.latest_blockhash(),
+            ),
+            svm.latest_blockhash(),
+        ))
+        .unwrap_err();
+    assert_eq!(
+        failed.err,
+        TransactionError::InstructionError(0, InstructionError::UnsupportedProgramId)
+    );
+}
+
+#[test]
+fn upgrade_program_in_place_keeps_authority() {
+    let memo_bytes = include_bytes!("../src/programs/elf/spl_memo-4.0.0.so");
+    let token_bytes = include_bytes!("../src/programs/elf/spl_token-3.5.0.so");
+    let payer_kp = Keypair::new();
+    let authority = Address::new_unique();
+    let program_id = Address::new_unique();
+    let mut svm = LiteSVM::new();
+    svm.airdrop(&payer_kp.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.add_program(program_id, memo_bytes).unwrap();
+    let programdata_address = set_program_upgrade_authority(&mut svm, program_id, authority);
+    let program_account = svm.get_account(&program_id).unwrap();
+    let send_memo = |svm: &mut LiteSVM| {
+        // A fresh blockhash keeps the repeated memo from being a duplicate.
+        svm.expire_blockhash();
+        let tx = Transaction::new(
+            &[&payer_kp],
+            Message::new_with_blockhash(
+                &[Instruction::new_with_bytes(program_id, b"hello", vec![])],
+                Some(&payer_kp.pubkey()),
+                &svm.latest_blockhash(),
+            ),
+            svm.latest_blockhash(),
+        );
+        svm.send_transaction(tx)
+    };
+    send_memo(&mut svm).unwrap();
+    let metadata_len = UpgradeableLoaderState::size_of_programdata_metadata();
+    let programdata_metadata = |svm: &LiteSVM| {
+        let programdata = svm.get_account(&programdata_address).unwrap();
+        UpgradeableLoaderState::deserialize(&programdata.data[..metadata_len]).unwrap()
+    };
+    let UpgradeableLoaderState::ProgramData {
+        slot: deployed_slot,
+        upgrade_authority_address: Some(_),
+    } = programdata_metadata(&svm)
+    else {
+        panic!("expected ProgramData with an upgrade authority");
+    };
+    assert_ne!(deployed_slot, 7);
+
+    svm.warp_to_slot(7);
+    svm.upgrade_program_in_place(program_id, token_bytes)
+        .unwrap();
+
+    // The token program rejects the memo as an unknown instruction.
+    assert_eq!(
+        send_memo(&mut svm).unwrap_err().err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TokenError::InvalidInstruction as u32)
+        )
+    );
+    assert_eq!(svm.get_account(&program_id).unwrap(), program_account);
+    // The upgrade authority is kept and the deployment slot moves to the
+    // current one.
+    assert_eq!(
+        programdata_metadata(&svm),
+        UpgradeableLoaderState::ProgramData {
+            slot: 7,
+            upgrade_authority_address: Some(authority),
+        }
+    );
+    let programdata = svm.get_account(&programdata_address).unwrap();
+    assert_eq!(&programdata.data[metadata_len..], token_bytes);
+    assert!(programdata.lamports >= svm.minimum_balance_for_rent_exemption(programdata.data.len()));
+
+    let not_upgradeable = Address::new_unique();
+    svm.add_program_with_loader(
+        not_upgradeable,
+        memo_bytes,
+        solana_sdk_ids::bpf_loader::id(),
+    )
+    .unwrap();
+    let err = svm
+        .upgrade_program_in_place(not_upgradeable, token_bytes)
+        .unwrap_err();
+    assert!(
+        matches!(
+            &err,
+            LiteSVMError::InvalidLoader(message)
+                if *message == format!("Program {not_upgradeable} is not owned by the upgradeable loader")
+        ),
+        "{err:?}"
+    );
+}
+
+#[test]
+fn upgrade_program_in_place_keeps_the_programdata_length() {
+    let memo_bytes = include_bytes!("../src/programs/elf/spl_memo-4.0.0.so");
+    let token_bytes = include_bytes!("../src/programs/elf/spl_token-3.5.0.so");
+    let token_2022_bytes = include_bytes!("../src/programs/elf/spl_token_2022-11.0.0.so");
+    let payer_kp = Keypair::new();
+    let program_id = Address::new_unique();
+    let programdata_address = get_program_data_address(&program_id);
+    let mut svm = LiteSVM::new();
+    svm.airdrop(&payer_kp.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.add_program(program_id, token_bytes).unwrap();
+    let metadata_len = UpgradeableLoaderState::size_of_programdata_metadata();
+    let deployed_len = svm.get_account(&programdata_address).unwrap().data.len();
+    assert_eq!(deployed_len, metadata_len + token_bytes.len());
+
+    // A smaller binary keeps the account's size and zeroes the rest.
+    svm.upgrade_program_in_place(program_id, memo_bytes)
+        .unwrap();
+    let programdata = svm.get_account(&programdata_address).unwrap();
+    assert_eq!(programdata.data.len(), deployed_len);
+    let code = &programdata.data[metadata_len..];
+    assert_eq!(&code[..memo_bytes.len()], memo_bytes);
+    assert!(code[memo_bytes.len()..].iter().all(|byte| *byte == 0));
+    let tx = Transaction::new(
+        &[&payer_kp],
+        Message::new_with_blockhash(
+            &[Instruction::new_with_bytes(program_id, b"hello", vec![])],
+            Some(&payer_kp.pubkey()),
+            &svm.latest_blockhash(),
+        ),
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx).unwrap();
+
+    // A larger one grows the account and tops up its rent.
+    svm.upgrade_program_in_place(program_id, token_2022_bytes)
+        .unwrap();
+    let programdata = svm.get_account(&programdata_address).unwrap();
+    assert_eq!(
+        programdata.data.len(),
+        metadata_len + token_2022_bytes.len()
+    );
+    assert_eq!(&programdata.data[metadata_len..], token_2022_bytes);
+    assert!(programdata.lamports >= svm.minimum_balance_for_rent_exemption(programdata.data.len()));
+}
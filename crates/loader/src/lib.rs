@@ -1,9 +1,14 @@
 use {
-    litesvm::{types::FailedTransactionMetadata, LiteSVM},
+    litesvm::{
+        types::{FailedTransactionMetadata, TransactionMetadata},
+        LiteSVM,
+    },
     solana_address::Address,
+    solana_instruction::Instruction,
     solana_keypair::Keypair,
     solana_loader_v3_interface::{
-        instruction as bpf_loader_upgradeable, state::UpgradeableLoaderState,
+        get_program_data_address, instruction as bpf_loader_upgradeable,
+        state::UpgradeableLoaderState,
     },
     solana_signer::Signer,
     solana_transaction::Transaction,
@@ -11,6 +16,101 @@ use {
 
 const CHUNK_SIZE: usize = 512;
 
+/// Maximum size of a serialized transaction: the IPv6 minimum MTU minus the
+/// IPv6 and UDP headers.
+const PACKET_DATA_SIZE: usize = 1280 - 40 - 8;
+
+/// How [`deploy_upgradeable_program_with`] writes the program to its buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeployOptions {
+    /// Program bytes written per buffer write transaction. Ignored when
+    /// `pack_writes` is set.
+    pub chunk_size: usize,
+    /// Write as many bytes per transaction as fit in a packet, the way
+    /// `solana program deploy` does.
+    pub pack_writes: bool,
+}
+
+impl Default for DeployOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: CHUNK_SIZE,
+            pack_writes: false,
+        }
+    }
+}
+
+/// Why [`deploy_upgradeable_program_with`] failed.
+#[derive(Debug, thiserror::Error)]
+pub enum DeployError {
+    /// `chunk_size` is zero, or too large for a buffer write transaction to
+    /// fit in a packet. Nothing was sent.
+    #[error("invalid chunk size {chunk_size}, expected 1 to {max} bytes")]
+    InvalidChunkSize { chunk_size: usize, max: usize },
+    /// One of the deployment transactions failed.
+    #[error("deployment transaction failed: {}", .0.err)]
+    Transaction(Box<FailedTransactionMetadata>),
+}
+
+impl From<FailedTransactionMetadata> for DeployError {
+    fn from(failed: FailedTransactionMetadata) -> Self {
+        Self::Transaction(Box::new(failed))
+    }
+}
+
+/// Why [`upgrade_program`] failed, with what it sent before the failure.
+#[derive(Debug, thiserror::Error)]
+#[error("upgrade transaction failed: {}", .failed.err)]
+pub struct UpgradeError {
+    /// The transaction that failed.
+    pub failed: Box<FailedTransactionMetadata>,
+    /// The transactions sent before it, which all succeeded.
+    pub sent: Vec<TransactionMetadata>,
+    /// The buffer the program was written to, `None` if creating it failed.
+    /// It is left open: close it with [`close_buffer`] to get its lamports
+    /// back.
+    pub buffer: Option<Address>,
+}
+
+/// What a deployment sent and cost.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeployReport {
+    /// Transactions sent: the buffer creation, the writes and the deployment.
+    pub transaction_count: usize,
+    /// Sum of the fees paid by every transaction.
+    pub total_fees: u64,
+    /// Sum of the compute units consumed by every transaction.
+    pub total_compute_units: u64,
+    /// The buffer the program was written to. It is closed by the deployment.
+    pub buffer: Address,
+    /// The account holding the deployed program's code.
+    pub programdata: Address,
+}
+
+/// Sends `instructions` in one transaction paid by `payer_kp`. Signers that
+/// share the payer's address are only used once.
+fn send(
+    svm: &mut LiteSVM,
+    instructions: &[Instruction],
+    payer_kp: &Keypair,
+    signers: &[&Keypair],
+) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+    let mut all_signers = vec![payer_kp];
+    for signer in signers {
+        if all_signers.iter().all(|kp| kp.pubkey() != signer.pubkey()) {
+            all_signers.push(signer);
+        }
+    }
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer_kp.pubkey()),
+        &all_signers,
+        svm.latest_blockhash(),
+    );
+
+    svm.send_transaction(tx)
+}
+
 pub fn set_upgrade_authority(
     svm: &mut LiteSVM,
     from_keypair: &Keypair,
@@ -34,65 +134,143 @@ pub fn set_upgrade_authority(
     Ok(())
 }
 
+/// Largest number of bytes a buffer write can carry while its transaction
+/// still fits in a packet.
+fn max_write_chunk_size(payer_kp: &Keypair, buffer: &Address, authority_kp: &Keypair) -> usize {
+    let tx = Transaction::new_with_payer(
+        &[bpf_loader_upgradeable::write(
+            buffer,
+            &authority_kp.pubkey(),
+            0,
+            Vec::new(),
+        )],
+        Some(&payer_kp.pubkey()),
+    );
+    // Unsigned transactions already hold a placeholder for every signature.
+    let empty_size = <Transaction as wincode::Serialize>::serialized_size(&tx).unwrap() as usize;
+    // The length prefix of the instruction data takes a second byte once the
+    // data is longer than 127 bytes.
+    PACKET_DATA_SIZE - empty_size - 1
+}
+
+/// Creates the buffer `buffer_kp`, owned by `authority_kp`, and writes
+/// `program_bytes` into it, recording every transaction sent in `sent`.
 fn load_upgradeable_buffer(
     svm: &mut LiteSVM,
     payer_kp: &Keypair,
+    buffer_kp: &Keypair,
+    authority_kp: &Keypair,
     program_bytes: &[u8],
-) -> Result<Address, FailedTransactionMetadata> {
+    options: DeployOptions,
+    sent: &mut Vec<TransactionMetadata>,
+) -> Result<(), FailedTransactionMetadata> {
     let payer_pk = payer_kp.pubkey();
-    let buffer_kp = Keypair::new();
+    let authority_pk = authority_kp.pubkey();
     let buffer_pk = buffer_kp.pubkey();
     // loader
     let buffer_len = UpgradeableLoaderState::size_of_buffer(program_bytes.len());
     let lamports = svm.minimum_balance_for_rent_exemption(buffer_len);
 
-    let tx = Transaction::new_signed_with_payer(
+    sent.push(send(
+        svm,
         &bpf_loader_upgradeable::create_buffer(
             &payer_pk,
             &buffer_pk,
-            &payer_pk,
+            &authority_pk,
             lamports,
             program_bytes.len(),
         )
         .unwrap(),
-        Some(&payer_pk),
-        &[payer_kp, &buffer_kp],
-        svm.latest_blockhash(),
-    );
+        payer_kp,
+        &[buffer_kp],
+    )?);
 
-    svm.send_transaction(tx)?;
-
-    let chunk_size = CHUNK_SIZE;
+    let chunk_size = if options.pack_writes {
+        max_write_chunk_size(payer_kp, &buffer_pk, authority_kp)
+    } else {
+        options.chunk_size
+    };
     let mut offset = 0;
     for chunk in program_bytes.chunks(chunk_size) {
-        let tx = Transaction::new_signed_with_payer(
+        sent.push(send(
+            svm,
             &[bpf_loader_upgradeable::write(
                 &buffer_pk,
-                &payer_pk,
+                &authority_pk,
                 offset,
                 chunk.to_vec(),
             )],
-            Some(&payer_pk),
-            &[payer_kp],
-            svm.latest_blockhash(),
-        );
-
-        svm.send_transaction(tx)?;
-        offset += chunk_size as u32;
+            payer_kp,
+            &[authority_kp],
+        )?);
+        // Chunks are at most a packet long and program data at most 10 MiB.
+        offset += chunk.len() as u32;
     }
 
-    Ok(buffer_pk)
+    Ok(())
 }
 
+/// Deploys `program_bytes` at the address of `program_kp` through the
+/// upgradeable loader, with `payer_kp` as upgrade authority.
 pub fn deploy_upgradeable_program(
     svm: &mut LiteSVM,
     payer_kp: &Keypair,
     program_kp: &Keypair,
     program_bytes: &[u8],
-) -> Result<(), FailedTransactionMetadata> {
+) -> Result<DeployReport, FailedTransactionMetadata> {
+    deploy(
+        svm,
+        payer_kp,
+        program_kp,
+        program_bytes,
+        DeployOptions::default(),
+    )
+}
+
+/// Same as [`deploy_upgradeable_program`], writing the buffer as set in
+/// `options`. A `chunk_size` that is zero or too large for a write
+/// transaction is rejected before anything is sent.
+pub fn deploy_upgradeable_program_with(
+    svm: &mut LiteSVM,
+    payer_kp: &Keypair,
+    program_kp: &Keypair,
+    program_bytes: &[u8],
+    options: DeployOptions,
+) -> Result<DeployReport, DeployError> {
+    if !options.pack_writes {
+        // The buffer address doesn't change the size of a write.
+        let max = max_write_chunk_size(payer_kp, &Address::default(), payer_kp);
+        if !(1..=max).contains(&options.chunk_size) {
+            return Err(DeployError::InvalidChunkSize {
+                chunk_size: options.chunk_size,
+                max,
+            });
+        }
+    }
+    Ok(deploy(svm, payer_kp, program_kp, program_bytes, options)?)
+}
+
+fn deploy(
+    svm: &mut LiteSVM,
+    payer_kp: &Keypair,
+    program_kp: &Keypair,
+    program_bytes: &[u8],
+    options: DeployOptions,
+) -> Result<DeployReport, FailedTransactionMetadata> {
     let program_pk = program_kp.pubkey();
     let payer_pk = payer_kp.pubkey();
-    let buffer_pk = load_upgradeable_buffer(svm, payer_kp, program_bytes)?;
+    let mut sent = Vec::new();
+    let buffer_kp = Keypair::new();
+    let buffer_pk = buffer_kp.pubkey();
+    load_upgradeable_buffer(
+        svm,
+        payer_kp,
+        &buffer_kp,
+        payer_kp,
+        program_bytes,
+        options,
+        &mut sent,
+    )?;
 
     let lamports = svm.minimum_balance_for_rent_exemption(program_bytes.len());
     #[allow(deprecated)]
@@ -112,7 +290,154 @@ pub fn deploy_upgradeable_program(
         svm.latest_blockhash(),
     );
 
-    svm.send_transaction(tx)?;
+    sent.push(svm.send_transaction(tx)?);
 
-    Ok(())
+    Ok(DeployReport {
+        transaction_count: sent.len(),
+        total_fees: sent.iter().map(|meta| meta.fee).sum(),
+        total_compute_units: sent.iter().map(|meta| meta.compute_units_consumed).sum(),
+        buffer: buffer_pk,
+        programdata: get_program_data_address(&program_pk),
+    })
+}
+
+/// Replaces the code of a deployed program with `program_bytes`.
+///
+/// The bytes are written to a new buffer that the upgrade then closes,
+/// refunding its lamports to the payer. The programdata account must already
+/// be large enough; use [`extend_program`] first otherwise. As on a real
+/// cluster, a program can not be upgraded in the slot it was deployed in.
+///
+/// Returns the metadata of every transaction sent, the upgrade last. On
+/// failure, the [`UpgradeError`] holds the transactions sent before and the
+/// buffer left behind.
+pub fn upgrade_program(
+    svm: &mut LiteSVM,
+    payer_kp: &Keypair,
+    program_address: &Address,
+    authority_kp: &Keypair,
+    program_bytes: &[u8],
+) -> Result<Vec<TransactionMetadata>, UpgradeError> {
+    let mut sent = Vec::new();
+    let buffer_kp = Keypair::new();
+    let buffer_pk = buffer_kp.pubkey();
+    let result = load_upgradeable_buffer(
+        svm,
+        payer_kp,
+        &buffer_kp,
+        authority_kp,
+        program_bytes,
+        DeployOptions::default(),
+        &mut sent,
+    )
+    .and_then(|()| {
+        send(
+            svm,
+            &[bpf_loader_upgradeable::upgrade(
+                program_address,
+                &buffer_pk,
+                &authority_kp.pubkey(),
+                &payer_kp.pubkey(),
+                true,
+            )],
+            payer_kp,
+            &[authority_kp],
+        )
+    });
+    match result {
+        Ok(meta) => {
+            sent.push(meta);
+            Ok(sent)
+        }
+        Err(failed) => Err(UpgradeError {
+            failed: Box::new(failed),
+            // The buffer creation is the first transaction sent.
+            buffer: (!sent.is_empty()).then_some(buffer_pk),
+            sent,
+        }),
+    }
+}
+
+/// Grows the programdata account of a program by `additional_bytes`, with
+/// the payer funding the extra rent.
+pub fn extend_program(
+    svm: &mut LiteSVM,
+    payer_kp: &Keypair,
+    program_address: &Address,
+    additional_bytes: u32,
+) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+    send(
+        svm,
+        &[bpf_loader_upgradeable::extend_program(
+            program_address,
+            Some(&payer_kp.pubkey()),
+            additional_bytes,
+        )],
+        payer_kp,
+        &[],
+    )
+}
+
+/// Closes a program, moving the lamports of its programdata account to
+/// `recipient`. The program can not be invoked or redeployed afterwards.
+pub fn close_program(
+    svm: &mut LiteSVM,
+    payer_kp: &Keypair,
+    program_address: &Address,
+    authority_kp: &Keypair,
+    recipient: &Address,
+) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+    send(
+        svm,
+        &[bpf_loader_upgradeable::close_any(
+            &get_program_data_address(program_address),
+            recipient,
+            Some(&authority_kp.pubkey()),
+            Some(program_address),
+            false,
+        )],
+        payer_kp,
+        &[authority_kp],
+    )
+}
+
+/// Closes a buffer, moving its lamports to `recipient`.
+pub fn close_buffer(
+    svm: &mut LiteSVM,
+    payer_kp: &Keypair,
+    buffer_address: &Address,
+    authority_kp: &Keypair,
+    recipient: &Address,
+) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+    send(
+        svm,
+        &[bpf_loader_upgradeable::close(
+            buffer_address,
+            recipient,
+            &authority_kp.pubkey(),
+            false,
+        )],
+        payer_kp,
+        &[authority_kp],
+    )
+}
+
+/// Hands the write authority of a buffer over to `new_authority_address`.
+pub fn set_buffer_authority(
+    svm: &mut LiteSVM,
+    payer_kp: &Keypair,
+    buffer_address: &Address,
+    authority_kp: &Keypair,
+    new_authority_address: &Address,
+) -> Result<TransactionMetadata, FailedTransactionMetadata> {
+    send(
+        svm,
+        &[bpf_loader_upgradeable::set_buffer_authority(
+            buffer_address,
+            &authority_kp.pubkey(),
+            new_authority_address,
+        )],
+        payer_kp,
+        &[authority_kp],
+    )
 }
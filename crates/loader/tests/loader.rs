@@ -2,12 +2,24 @@ use {
     crate::programs_bytes::HELLO_WORLD_BYTES,
     agave_feature_set::FeatureSet,
     litesvm::LiteSVM,
-    litesvm_loader::{deploy_upgradeable_program, set_upgrade_authority},
-    solana_instruction::{account_meta::AccountMeta, Instruction},
+    litesvm_loader::{
+        close_buffer, close_program, deploy_upgradeable_program, deploy_upgradeable_program_with,
+        extend_program, set_buffer_authority, set_upgrade_authority, upgrade_program, DeployError,
+        DeployOptions, UpgradeError,
+    },
+    solana_account::Account,
+    solana_address::Address,
+    solana_instruction::{account_meta::AccountMeta, error::InstructionError, Instruction},
     solana_keypair::Keypair,
+    solana_loader_v3_interface::{
+        get_program_data_address, instruction::MINIMUM_EXTEND_PROGRAM_BYTES,
+        state::UpgradeableLoaderState,
+    },
     solana_message::Message,
     solana_signer::Signer,
     solana_transaction::Transaction,
+    solana_transaction_error::TransactionError,
+    wincode::{Deserialize, Serialize},
 };
 
 mod programs_bytes;
@@ -81,3 +93,269 @@ fn hello_world_with_deploy_upgradeable() {
     )
     .unwrap();
 }
+
+fn svm_with_payer() -> (LiteSVM, Keypair) {
+    let mut feature_set = FeatureSet::all_enabled();
+    // The fixture is an sbpf v0 ELF; keep its deployment allowed.
+    feature_set.deactivate(&agave_feature_set::disable_sbpf_v0_v1_v2_deployment::id());
+    let mut svm = LiteSVM::default()
+        .with_feature_set(feature_set)
+        .with_builtins()
+        .with_lamports(1_000_000_000_000_000)
+        .with_sysvars();
+    let payer_kp = Keypair::new();
+    svm.airdrop(&payer_kp.pubkey(), 100_000_000_000).unwrap();
+    (svm, payer_kp)
+}
+
+fn deploy_hello_world(svm: &mut LiteSVM, payer_kp: &Keypair) -> Address {
+    let program_kp = Keypair::new();
+    deploy_upgradeable_program(svm, payer_kp, &program_kp, HELLO_WORLD_BYTES).unwrap();
+    // Programs can not be changed in the slot they were deployed in.
+    svm.warp_to_slot(svm.get_sysvar::<solana_clock::Clock>().slot + 1);
+    program_kp.pubkey()
+}
+
+fn say_hello(svm: &mut LiteSVM, payer_kp: &Keypair, program_id: Address) -> bool {
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &[],
+        vec![AccountMeta::new(payer_kp.pubkey(), true)],
+    );
+    let message = Message::new(&[instruction], Some(&payer_kp.pubkey()));
+    let tx = Transaction::new(&[payer_kp], message, svm.latest_blockhash());
+    svm.send_transaction(tx).is_ok()
+}
+
+#[test]
+fn upgrade_and_extend_program() {
+    let (mut svm, payer_kp) = svm_with_payer();
+    let program_id = deploy_hello_world(&mut svm, &payer_kp);
+    let programdata_address = get_program_data_address(&program_id);
+    let programdata_len = svm.get_account(&programdata_address).unwrap().data.len();
+
+    extend_program(
+        &mut svm,
+        &payer_kp,
+        &program_id,
+        MINIMUM_EXTEND_PROGRAM_BYTES,
+    )
+    .unwrap();
+    assert_eq!(
+        svm.get_account(&programdata_address).unwrap().data.len(),
+        programdata_len + MINIMUM_EXTEND_PROGRAM_BYTES as usize
+    );
+
+    svm.warp_to_slot(svm.get_sysvar::<solana_clock::Clock>().slot + 1);
+    let slot = svm.get_sysvar::<solana_clock::Clock>().slot;
+    let sent = upgrade_program(
+        &mut svm,
+        &payer_kp,
+        &program_id,
+        &payer_kp,
+        HELLO_WORLD_BYTES,
+    )
+    .unwrap();
+
+    // Buffer creation, one write per chunk, then the upgrade itself.
+    assert_eq!(sent.len(), 2 + HELLO_WORLD_BYTES.len().div_ceil(512));
+    let programdata = svm.get_account(&programdata_address).unwrap();
+    assert_eq!(
+        UpgradeableLoaderState::deserialize(
+            &programdata.data[..UpgradeableLoaderState::size_of_programdata_metadata()]
+        )
+        .unwrap(),
+        UpgradeableLoaderState::ProgramData {
+            slot,
+            upgrade_authority_address: Some(payer_kp.pubkey()),
+        }
+    );
+    svm.warp_to_slot(slot + 1);
+    assert!(say_hello(&mut svm, &payer_kp, program_id));
+}
+
+#[test]
+fn failed_upgrade_leaves_the_buffer_to_close() {
+    let (mut svm, payer_kp) = svm_with_payer();
+    let program_id = deploy_hello_world(&mut svm, &payer_kp);
+
+    // The buffer is written, but its authority can't upgrade the program.
+    let impostor_kp = Keypair::new();
+    let UpgradeError {
+        failed,
+        sent,
+        buffer,
+    } = upgrade_program(
+        &mut svm,
+        &payer_kp,
+        &program_id,
+        &impostor_kp,
+        HELLO_WORLD_BYTES,
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        failed.err,
+        TransactionError::InstructionError(0, InstructionError::IncorrectAuthority)
+    );
+    assert_eq!(sent.len(), 1 + HELLO_WORLD_BYTES.len().div_ceil(512));
+    let buffer = buffer.unwrap();
+    let buffer_lamports = svm.get_balance(&buffer).unwrap();
+    let recipient = Address::new_unique();
+    close_buffer(&mut svm, &payer_kp, &buffer, &impostor_kp, &recipient).unwrap();
+    assert_eq!(svm.get_balance(&recipient), Some(buffer_lamports));
+}
+
+#[test]
+fn close_program_reclaims_rent() {
+    let (mut svm, payer_kp) = svm_with_payer();
+    let program_id = deploy_hello_world(&mut svm, &payer_kp);
+    let programdata_lamports = svm
+        .get_balance(&get_program_data_address(&program_id))
+        .unwrap();
+    let recipient = Address::new_unique();
+
+    close_program(&mut svm, &payer_kp, &program_id, &payer_kp, &recipient).unwrap();
+
+    assert_eq!(svm.get_balance(&recipient), Some(programdata_lamports));
+    assert!(!say_hello(&mut svm, &payer_kp, program_id));
+}
+
+#[test]
+fn buffer_authority_handover_and_close() {
+    let (mut svm, payer_kp) = svm_with_payer();
+    let new_authority = Keypair::new();
+    let recipient = Address::new_unique();
+    let buffer = Address::new_unique();
+    let mut data = UpgradeableLoaderState::serialize(&UpgradeableLoaderState::Buffer {
+        authority_address: Some(payer_kp.pubkey()),
+    })
+    .unwrap();
+    data.extend_from_slice(HELLO_WORLD_BYTES);
+    let buffer_lamports = svm.minimum_balance_for_rent_exemption(data.len());
+    svm.set_account(
+        buffer,
+        Account {
+            lamports: buffer_lamports,
+            data,
+            owner: solana_sdk_ids::bpf_loader_upgradeable::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    )
+    .unwrap();
+
+    set_buffer_authority(
+        &mut svm,
+        &payer_kp,
+        &buffer,
+        &payer_kp,
+        &new_authority.pubkey(),
+    )
+    .unwrap();
+    assert!(close_buffer(&mut svm, &payer_kp, &buffer, &payer_kp, &recipient).is_err());
+
+    let meta = close_buffer(&mut svm, &payer_kp, &buffer, &new_authority, &recipient).unwrap();
+    assert_eq!(meta.fee, 10_000);
+    assert_eq!(svm.get_balance(&recipient), Some(buffer_lamports));
+    assert_eq!(svm.get_account(&buffer), None);
+}
+
+#[test]
+fn deploy_report_accounts_for_every_transaction() {
+    let (mut svm, payer_kp) = svm_with_payer();
+    let program_kp = Keypair::new();
+    let balance = svm.get_balance(&payer_kp.pubkey()).unwrap();
+
+    let report = deploy_upgradeable_program_with(
+        &mut svm,
+        &payer_kp,
+        &program_kp,
+        HELLO_WORLD_BYTES,
+        DeployOptions {
+            chunk_size: 256,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let writes = HELLO_WORLD_BYTES.len().div_ceil(256);
+    assert_eq!(report.transaction_count, writes + 2);
+    // The buffer creation and the deployment are also signed by the new
+    // account.
+    assert_eq!(report.total_fees, 5000 * (writes as u64 + 4));
+    assert!(report.total_compute_units > 0);
+    assert_eq!(
+        report.programdata,
+        get_program_data_address(&program_kp.pubkey())
+    );
+    assert_eq!(svm.get_account(&report.buffer), None);
+    let programdata_lamports = svm.get_balance(&report.programdata).unwrap();
+    let program_lamports = svm.get_balance(&program_kp.pubkey()).unwrap();
+    assert_eq!(
+        svm.get_balance(&payer_kp.pubkey()).unwrap(),
+        balance - report.total_fees - programdata_lamports - program_lamports
+    );
+}
+
+#[test]
+fn packed_writes_fill_a_packet() {
+    let (mut svm, payer_kp) = svm_with_payer();
+
+    let report = deploy_upgradeable_program_with(
+        &mut svm,
+        &payer_kp,
+        &Keypair::new(),
+        HELLO_WORLD_BYTES,
+        DeployOptions {
+            pack_writes: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    // A write signed by the payer alone carries 1012 bytes in 1232.
+    let tx = Transaction::new_with_payer(
+        &[solana_loader_v3_interface::instruction::write(
+            &report.buffer,
+            &payer_kp.pubkey(),
+            0,
+            vec![0; 1012],
+        )],
+        Some(&payer_kp.pubkey()),
+    );
+    assert_eq!(wincode::serialize(&tx).unwrap().len(), 1232);
+    assert_eq!(
+        report.transaction_count,
+        HELLO_WORLD_BYTES.len().div_ceil(1012) + 2
+    );
+}
+
+#[test]
+fn invalid_chunk_sizes_are_rejected_before_sending() {
+    let (mut svm, payer_kp) = svm_with_payer();
+    let balance = svm.get_balance(&payer_kp.pubkey()).unwrap();
+
+    for chunk_size in [0, 1013] {
+        let err = deploy_upgradeable_program_with(
+            &mut svm,
+            &payer_kp,
+            &Keypair::new(),
+            HELLO_WORLD_BYTES,
+            DeployOptions {
+                chunk_size,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                DeployError::InvalidChunkSize { chunk_size: rejected, max: 1012 }
+                    if rejected == chunk_size
+            ),
+            "{err:?}"
+        );
+    }
+    assert_eq!(svm.get_balance(&payer_kp.pubkey()), Some(balance));
+}
@@ -441,8 +441,8 @@ impl LiteSvm {
         if self
             .0
             .accounts_db()
-            .inner
-            .contains_key(&native_mint::inline_spl::SPL_TOKEN_PROGRAM_ID)
+            .get_account_ref(&native_mint::inline_spl::SPL_TOKEN_PROGRAM_ID)
+            .is_some()
         {
             native_mint::create_native_mint(&mut self.0);
         }
@@ -450,8 +450,8 @@ impl LiteSvm {
         if self
             .0
             .accounts_db()
-            .inner
-            .contains_key(&native_mint::inline_spl::SPL_TOKEN_2022_PROGRAM_ID)
+            .get_account_ref(&native_mint::inline_spl::SPL_TOKEN_2022_PROGRAM_ID)
+            .is_some()
         {
             native_mint::create_native_mint_2022(&mut self.0);
         }
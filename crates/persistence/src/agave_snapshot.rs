@@ -0,0 +1,249 @@
+//! Reader for Agave snapshot archives (`snapshot-<slot>-<hash>.tar.zst` and
+//! `incremental-snapshot-<base>-<slot>-<hash>.tar.zst`).
+//!
+//! An archive is a zstd-compressed tarball. Account state lives in
+//! `accounts/<slot>.<id>` storage files, each a sequence of stored accounts:
+//!
+//! ```text
+//! StoredMeta   write_version: u64, data_len: u64, pubkey: [u8; 32]
+//! AccountMeta  lamports: u64, rent_epoch: u64, owner: [u8; 32],
+//!              executable: bool, 7 bytes padding
+//! hash         [u8; 32] (obsolete, always written)
+//! data         data_len bytes, padded to an 8-byte boundary
+//! ```
+//!
+//! The bank fields stored next to them are not read: the newest version of
+//! each account is picked by the slot in its storage file name, and the
+//! remaining `LiteSVM` state (sysvars, features, config) is left as is.
+
+use {
+    crate::PersistenceError,
+    litesvm::LiteSVM,
+    solana_account::{Account, AccountSharedData, WritableAccount},
+    solana_address::Address,
+    std::{
+        collections::{HashMap, HashSet},
+        fs::File,
+        io::{BufReader, Read},
+        path::Path,
+    },
+};
+
+const STORED_META_SIZE: usize = 48;
+const ACCOUNT_META_SIZE: usize = 56;
+const ACCOUNT_HASH_SIZE: usize = 32;
+const STORED_ACCOUNT_HEADER_SIZE: usize = STORED_META_SIZE + ACCOUNT_META_SIZE + ACCOUNT_HASH_SIZE;
+const STORED_ACCOUNT_ALIGN: usize = 8;
+const MAX_PERMITTED_DATA_LENGTH: u64 = 10 * 1024 * 1024;
+
+/// Selects which accounts are loaded by [`load_agave_snapshot`].
+///
+/// An empty filter accepts every account. Otherwise an account is kept if
+/// its owner is one of [`owners`](Self::owners) or its address is one of
+/// [`addresses`](Self::addresses).
+#[derive(Debug, Clone, Default)]
+pub struct AgaveSnapshotFilter {
+    owners: HashSet<Address>,
+    addresses: HashSet<Address>,
+}
+
+impl AgaveSnapshotFilter {
+    /// Creates a filter that accepts every account.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keeps accounts owned by any of `owners`.
+    pub fn owners(mut self, owners: impl IntoIterator<Item = Address>) -> Self {
+        self.owners.extend(owners);
+        self
+    }
+
+    /// Keeps the accounts at `addresses`.
+    pub fn addresses(mut self, addresses: impl IntoIterator<Item = Address>) -> Self {
+        self.addresses.extend(addresses);
+        self
+    }
+
+    fn accepts(&self, address: &Address, owner: &Address) -> bool {
+        (self.owners.is_empty() && self.addresses.is_empty())
+            || self.owners.contains(owner)
+            || self.addresses.contains(address)
+    }
+}
+
+/// Loads accounts from Agave full and incremental snapshot archives into `svm`.
+///
+/// Archives may be passed in any order: for every address the version from
+/// the highest slot wins, and `filter` applies to that version only.
+/// Accounts are written to `svm` as they are read, so only the winning slot
+/// of each address is kept aside. Accounts whose newest version has zero
+/// lamports are removed from `svm`, as are accounts written from an older
+/// version that the filter accepted but the newest one it rejects. Returns
+/// the number of accounts written.
+pub fn load_agave_snapshot<P: AsRef<Path>>(
+    svm: &mut LiteSVM,
+    archives: impl IntoIterator<Item = P>,
+    filter: &AgaveSnapshotFilter,
+) -> Result<usize, PersistenceError> {
+    // The slot of the newest version read so far for each address, and
+    // whether that version is in `svm`.
+    let mut winners: HashMap<Address, (u64, bool)> = HashMap::new();
+    for archive in archives {
+        read_archive(svm, archive.as_ref(), filter, &mut winners)?;
+    }
+    svm.rebuild_caches()?;
+
+    Ok(winners.values().filter(|(_, alive)| *alive).count())
+}
+
+fn read_archive(
+    svm: &mut LiteSVM,
+    path: &Path,
+    filter: &AgaveSnapshotFilter,
+    winners: &mut HashMap<Address, (u64, bool)>,
+) -> Result<(), PersistenceError> {
+    let decoder = zstd::stream::read::Decoder::new(BufReader::new(File::open(path)?))?;
+    let mut archive = tar::Archive::new(decoder);
+    let mut storage = Vec::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let entry_path = entry.path()?.into_owned();
+        let Some(slot) = storage_slot(&entry_path) else {
+            continue;
+        };
+        storage.clear();
+        entry.read_to_end(&mut storage)?;
+
+        for stored in StoredAccounts::new(&storage) {
+            let stored = stored.map_err(|offset| PersistenceError::MalformedAccountsFile {
+                path: entry_path.clone(),
+                offset,
+            })?;
+            let (winning_slot, written) = winners.entry(stored.address).or_insert((slot, false));
+            if *winning_slot > slot {
+                continue;
+            }
+            *winning_slot = slot;
+            // A zero-lamport account is how a snapshot records a closed one.
+            let closed = stored.lamports == 0;
+            let keep = !closed && filter.accepts(&stored.address, &stored.owner);
+            // Drop an older version written before, unless this one replaces
+            // it. Accounts the filter never accepted are left alone.
+            let remove = !keep && (closed || *written);
+            *written = keep;
+            if keep {
+                svm.set_account_no_checks(stored.address, stored.to_account());
+            } else if remove {
+                svm.set_account(stored.address, Account::default())
+                    .map_err(|err| PersistenceError::RemoveClosedAccount(stored.address, err))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Slot of an `accounts/<slot>.<id>` storage file, `None` for anything else.
+fn storage_slot(path: &Path) -> Option<u64> {
+    let mut components = path.components();
+    if components.next()?.as_os_str() != "accounts" {
+        return None;
+    }
+    let file_name = components.next()?.as_os_str().to_str()?;
+    if components.next().is_some() {
+        return None;
+    }
+    let (slot, id) = file_name.split_once('.')?;
+    id.parse::<u64>().ok()?;
+    slot.parse().ok()
+}
+
+struct StoredAccount<'a> {
+    address: Address,
+    owner: Address,
+    lamports: u64,
+    rent_epoch: u64,
+    executable: bool,
+    data: &'a [u8],
+}
+
+impl StoredAccount<'_> {
+    fn to_account(&self) -> AccountSharedData {
+        let mut account = AccountSharedData::new(self.lamports, 0, &self.owner);
+        account.set_data_from_slice(self.data);
+        account.set_executable(self.executable);
+        account.set_rent_epoch(self.rent_epoch);
+        account
+    }
+}
+
+/// Iterates the accounts of one storage file. Yields the byte offset of the
+/// offending entry on malformed input and stops at the zeroed tail of a
+/// preallocated file.
+struct StoredAccounts<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> StoredAccounts<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+
+    fn next_account(&mut self) -> Result<Option<StoredAccount<'a>>, usize> {
+        let offset = self.offset;
+        let Some(header) = self.bytes.get(offset..offset + STORED_ACCOUNT_HEADER_SIZE) else {
+            return if self.bytes[offset..].iter().all(|byte| *byte == 0) {
+                Ok(None)
+            } else {
+                Err(offset)
+            };
+        };
+        if header.iter().all(|byte| *byte == 0) {
+            return Ok(None);
+        }
+
+        let read_u64 = |at: usize| u64::from_le_bytes(header[at..at + 8].try_into().unwrap());
+        let read_address =
+            |at: usize| Address::new_from_array(header[at..at + 32].try_into().unwrap());
+        let data_len = read_u64(8);
+        let executable = match header[STORED_META_SIZE + 48] {
+            0 => false,
+            1 => true,
+            _ => return Err(offset),
+        };
+        if data_len > MAX_PERMITTED_DATA_LENGTH {
+            return Err(offset);
+        }
+        let data_start = offset + STORED_ACCOUNT_HEADER_SIZE;
+        let data_end = data_start + data_len as usize;
+        let data = self.bytes.get(data_start..data_end).ok_or(offset)?;
+
+        self.offset = data_end
+            .next_multiple_of(STORED_ACCOUNT_ALIGN)
+            .min(self.bytes.len());
+        Ok(Some(StoredAccount {
+            address: read_address(16),
+            lamports: read_u64(STORED_META_SIZE),
+            rent_epoch: read_u64(STORED_META_SIZE + 8),
+            owner: read_address(STORED_META_SIZE + 16),
+            executable,
+            data,
+        }))
+    }
+}
+
+impl<'a> Iterator for StoredAccounts<'a> {
+    type Item = Result<StoredAccount<'a>, usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_account() {
+            Ok(account) => account.map(Ok),
+            Err(offset) => {
+                // Fuse after the first error.
+                self.offset = self.bytes.len();
+                Some(Err(offset))
+            }
+        }
+    }
+}
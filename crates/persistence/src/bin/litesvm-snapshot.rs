@@ -0,0 +1,271 @@
+//! Command-line tools for saved LiteSVM snapshots.
+
+use {
+    agave_feature_set::FEATURE_NAMES,
+    litesvm::LiteSVM,
+    litesvm_persistence::{diff, load_from_file},
+    solana_account::ReadableAccount,
+    solana_address::Address,
+    std::{collections::BTreeSet, error::Error, process::ExitCode, str::FromStr},
+};
+
+const USAGE: &str = "\
+usage: litesvm-snapshot <command> [args]
+
+commands:
+    accounts <snapshot> [--owner <address>] [--min-size <bytes>] [--max-size <bytes>]
+        list accounts sorted by address, optionally filtered by owner or data size
+    account <snapshot> <address> [--json]
+        dump one account with its data as hex, or as JSON
+    features <snapshot>
+        list every feature in the snapshot's feature set and its activation
+    config <snapshot>
+        show the runtime configuration: blockhash, fees, compute budget, stakes
+    history <snapshot>
+        list saved transactions and their results, oldest first
+    extract-program <snapshot> <program> <output>
+        write the ELF of a deployed program to a file
+    diff <before> <after>
+        compare two saved snapshots; exits with 1 if they differ
+";
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = match args.as_slice() {
+        ["diff", before, after] => run_diff(before, after),
+        [command, snapshot, args @ ..] if *command != "diff" => {
+            run_inspect(command, snapshot, args)
+        }
+        ["-h" | "--help" | "help"] => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        _ => Ok(usage()),
+    };
+    result.unwrap_or_else(|err| {
+        eprintln!("error: {err}");
+        ExitCode::from(2)
+    })
+}
+
+fn usage() -> ExitCode {
+    eprint!("{USAGE}");
+    ExitCode::from(2)
+}
+
+fn run_diff(before: &str, after: &str) -> Result<ExitCode> {
+    let diff = diff(&load_from_file(before)?, &load_from_file(after)?);
+    print!("{diff}");
+    Ok(if diff.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(1)
+    })
+}
+
+fn run_inspect(command: &str, snapshot: &str, args: &[&str]) -> Result<ExitCode> {
+    let load = || load_from_file(snapshot);
+    match (command, args) {
+        ("accounts", filters) => {
+            let Some(filter) = AccountFilter::parse(filters)? else {
+                return Ok(usage());
+            };
+            list_accounts(&load()?, &filter)
+        }
+        ("account", [address]) => dump_account(&load()?, &parse_address(address)?, false)?,
+        ("account", [address, "--json"]) => dump_account(&load()?, &parse_address(address)?, true)?,
+        ("features", []) => list_features(&load()?),
+        ("config", []) => show_config(&load()?),
+        ("history", []) => list_history(&load()?),
+        ("extract-program", [program, output]) => {
+            extract_program(&load()?, &parse_address(program)?, output)?
+        }
+        _ => return Ok(usage()),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn parse_address(address: &str) -> Result<Address> {
+    Address::from_str(address).map_err(|err| format!("invalid address `{address}`: {err}").into())
+}
+
+#[derive(Default)]
+struct AccountFilter {
+    owner: Option<Address>,
+    min_size: Option<usize>,
+    max_size: Option<usize>,
+}
+
+impl AccountFilter {
+    /// Returns `None` when the arguments do not match the usage.
+    fn parse(mut args: &[&str]) -> Result<Option<Self>> {
+        let mut filter = Self::default();
+        while let [flag, value, rest @ ..] = args {
+            match *flag {
+                "--owner" => filter.owner = Some(parse_address(value)?),
+                "--min-size" => filter.min_size = Some(value.parse()?),
+                "--max-size" => filter.max_size = Some(value.parse()?),
+                _ => return Ok(None),
+            }
+            args = rest;
+        }
+        Ok(args.is_empty().then_some(filter))
+    }
+
+    fn matches(&self, account: &impl ReadableAccount) -> bool {
+        let size = account.data().len();
+        self.owner.is_none_or(|owner| *account.owner() == owner)
+            && self.min_size.is_none_or(|min| size >= min)
+            && self.max_size.is_none_or(|max| size <= max)
+    }
+}
+
+fn list_accounts(svm: &LiteSVM, filter: &AccountFilter) {
+    let mut accounts: Vec<_> = svm
+        .accounts_db()
+        .iter()
+        .filter(|(_, account)| filter.matches(*account))
+        .collect();
+    accounts.sort_unstable_by_key(|(address, _)| **address);
+    println!(
+        "{:<44}  {:>20}  {:>10}  {:<44}  executable",
+        "address", "lamports", "data size", "owner"
+    );
+    for (address, account) in accounts {
+        println!(
+            "{:<44}  {:>20}  {:>10}  {:<44}  {}",
+            address.to_string(),
+            account.lamports(),
+            account.data().len(),
+            account.owner().to_string(),
+            account.executable()
+        );
+    }
+}
+
+fn dump_account(svm: &LiteSVM, address: &Address, json: bool) -> Result<()> {
+    let account = svm
+        .accounts_db()
+        .get_account_ref(address)
+        .ok_or_else(|| format!("account {address} not found"))?;
+    if json {
+        #[cfg(feature = "json")]
+        {
+            println!(
+                "{}",
+                litesvm_persistence::account_to_json(address, account)?
+            );
+            return Ok(());
+        }
+        #[cfg(not(feature = "json"))]
+        return Err("--json requires litesvm-snapshot to be built with the `json` feature".into());
+    }
+
+    println!("address:    {address}");
+    println!("lamports:   {}", account.lamports());
+    println!("owner:      {}", account.owner());
+    println!("executable: {}", account.executable());
+    println!("rent epoch: {}", account.rent_epoch());
+    println!("data:       {} bytes", account.data().len());
+    for (line, chunk) in account.data().chunks(16).enumerate() {
+        let hex: Vec<String> = chunk.iter().map(|byte| format!("{byte:02x}")).collect();
+        let text: String = chunk
+            .iter()
+            .map(|byte| {
+                if byte.is_ascii_graphic() || *byte == b' ' {
+                    *byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        println!("{:08x}  {:<47}  |{text}|", line * 16, hex.join(" "));
+    }
+    Ok(())
+}
+
+fn list_features(svm: &LiteSVM) {
+    let feature_set = svm.get_feature_set_ref();
+    let features: BTreeSet<&Address> = feature_set
+        .active()
+        .keys()
+        .chain(feature_set.inactive())
+        .collect();
+    for feature in features {
+        let activation = match feature_set.activated_slot(feature) {
+            Some(slot) => format!("active at slot {slot}"),
+            None => "inactive".to_string(),
+        };
+        let name = FEATURE_NAMES.get(feature).copied().unwrap_or_default();
+        println!("{:<44}  {activation:<24}  {name}", feature.to_string());
+    }
+}
+
+fn show_config(svm: &LiteSVM) {
+    let fee_structure = svm.get_fee_structure();
+    let optional = |value: Option<usize>| match value {
+        Some(value) => value.to_string(),
+        None => "none".to_string(),
+    };
+    println!("airdrop account:         {}", svm.airdrop_pubkey());
+    println!("latest blockhash:        {}", svm.latest_blockhash());
+    println!("sigverify:               {}", svm.get_sigverify());
+    println!("blockhash check:         {}", svm.get_blockhash_check());
+    println!(
+        "log bytes limit:         {}",
+        optional(svm.get_log_bytes_limit())
+    );
+    println!(
+        "history capacity:        {}",
+        svm.transaction_history_capacity()
+    );
+    println!(
+        "lamports per signature:  {}",
+        fee_structure.lamports_per_signature
+    );
+    println!(
+        "lamports per write lock: {}",
+        fee_structure.lamports_per_write_lock
+    );
+    for bin in &fee_structure.compute_fee_bins {
+        println!(
+            "compute fee bin:         up to {} CU costs {} lamports",
+            bin.limit, bin.fee
+        );
+    }
+    match svm.get_compute_budget() {
+        Some(budget) => println!("compute budget:          {budget:#?}"),
+        None => println!("compute budget:          default"),
+    }
+    let mut stakes: Vec<_> = svm.epoch_vote_stakes().collect();
+    stakes.sort_unstable();
+    for (vote_account, stake) in stakes {
+        println!("epoch stake:             {vote_account} {stake}");
+    }
+}
+
+fn list_history(svm: &LiteSVM) {
+    for (signature, result) in svm.transaction_history_entries() {
+        let (status, meta) = match result {
+            Ok(meta) => ("ok".to_string(), meta),
+            Err(failed) => (format!("failed: {}", failed.err), &failed.meta),
+        };
+        println!(
+            "{signature}  {} CU  fee {}  {status}",
+            meta.compute_units_consumed, meta.fee
+        );
+    }
+}
+
+fn extract_program(svm: &LiteSVM, program: &Address, output: &str) -> Result<()> {
+    let elf = svm
+        .accounts_db()
+        .try_program_elf_bytes(program)
+        .map_err(|err| format!("no program ELF found at {program}: {err}"))?;
+    std::fs::write(output, elf)?;
+    println!("wrote {} bytes to {output}", elf.len());
+    Ok(())
+}
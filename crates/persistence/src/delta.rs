@@ -0,0 +1,134 @@
+//! Delta snapshots.
+//!
+//! A delta stores only the accounts that differ from a base state, which keeps
+//! fixtures derived from a common setup (or from a fresh `LiteSVM::new()`, with
+//! all its bundled programs) small. Everything else — features, config,
+//! blockhash, history, epoch stakes — is small and is stored in full.
+//!
+//! The airdrop account always travels with the airdrop keypair: it is written
+//! to every delta, and the base's own airdrop account is dropped when a delta
+//! is applied, so a delta taken against one `LiteSVM::new()` can be applied to
+//! another.
+
+use {
+    crate::{
+        extract_snapshot, restore_from_snapshot,
+        types::{AccountEntryWire, LiteSvmDeltaV1},
+        PersistenceError, DELTA_STATE_VERSION, STATE_VERSION, V1_STATE_VERSION, V2_STATE_VERSION,
+    },
+    litesvm::LiteSVM,
+    solana_account::AccountSharedData,
+    solana_address::Address,
+    std::{
+        collections::{HashMap, HashSet},
+        fs::File,
+        io::{BufWriter, Write},
+        path::Path,
+    },
+    wincode::{Deserialize, Serialize},
+};
+
+fn extract_delta(base: &LiteSVM, svm: &LiteSVM) -> LiteSvmDeltaV1 {
+    let (base_accounts, accounts) = (base.accounts_db(), svm.accounts_db());
+    let airdrop = svm.airdrop_pubkey();
+
+    let mut snapshot = extract_snapshot(svm);
+    snapshot.state.accounts.retain(|entry| {
+        entry.address == airdrop
+            || base_accounts.get_account_ref(&entry.address) != Some(&entry.account)
+    });
+    // Sorted so that deltas of the same state are byte-for-byte identical.
+    snapshot
+        .state
+        .accounts
+        .sort_unstable_by_key(|entry| entry.address);
+    let mut removed: Vec<Address> = base_accounts
+        .iter()
+        .filter(|(address, _)| accounts.get_account_ref(address).is_none())
+        .map(|(address, _)| *address)
+        .collect();
+    removed.sort_unstable();
+
+    LiteSvmDeltaV1 { snapshot, removed }
+}
+
+fn apply_delta(base: &LiteSVM, delta: LiteSvmDeltaV1) -> Result<LiteSVM, PersistenceError> {
+    let LiteSvmDeltaV1 {
+        mut snapshot,
+        removed,
+    } = delta;
+    let removed: HashSet<Address> = removed.into_iter().collect();
+    let base_airdrop = base.airdrop_pubkey();
+
+    let mut accounts: HashMap<Address, AccountSharedData> = base
+        .accounts_db()
+        .iter()
+        .filter(|(address, _)| **address != base_airdrop && !removed.contains(*address))
+        .map(|(address, account)| (*address, account.clone()))
+        .collect();
+    accounts.extend(snapshot.state.accounts.drain(..).map(Into::into));
+    snapshot.state.accounts = accounts.into_iter().map(AccountEntryWire::from).collect();
+
+    restore_from_snapshot(snapshot)
+}
+
+fn deserialize_delta(bytes: &[u8]) -> Result<LiteSvmDeltaV1, PersistenceError> {
+    let (version, rest) = bytes.split_first().ok_or(PersistenceError::EmptyInput)?;
+    match *version {
+        DELTA_STATE_VERSION => Ok(LiteSvmDeltaV1::deserialize(rest)?),
+        V1_STATE_VERSION | V2_STATE_VERSION | STATE_VERSION => Err(PersistenceError::NotADelta),
+        version => Err(PersistenceError::UnsupportedVersion(version)),
+    }
+}
+
+/// Saves the LiteSVM state as a delta against `base`.
+pub fn save_delta(
+    base: &LiteSVM,
+    svm: &LiteSVM,
+    path: impl AsRef<Path>,
+) -> Result<(), PersistenceError> {
+    let bytes = delta_to_bytes(base, svm)?;
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(&bytes)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Loads a delta written by [`save_delta`] on top of `base`.
+pub fn load_delta(base: &LiteSVM, path: impl AsRef<Path>) -> Result<LiteSVM, PersistenceError> {
+    delta_from_bytes(base, &std::fs::read(path)?)
+}
+
+/// Serializes the LiteSVM state as a delta against `base`.
+pub fn delta_to_bytes(base: &LiteSVM, svm: &LiteSVM) -> Result<Vec<u8>, PersistenceError> {
+    let delta = extract_delta(base, svm);
+    let payload_size = LiteSvmDeltaV1::serialized_size(&delta)? as usize;
+    let mut buf = Vec::with_capacity(1 + payload_size);
+    buf.push(DELTA_STATE_VERSION);
+    LiteSvmDeltaV1::serialize_into(&mut buf, &delta)?;
+    Ok(buf)
+}
+
+/// Deserializes a delta produced by [`delta_to_bytes`] on top of `base`.
+pub fn delta_from_bytes(base: &LiteSVM, bytes: &[u8]) -> Result<LiteSVM, PersistenceError> {
+    apply_delta(base, deserialize_delta(bytes)?)
+}
+
+/// Loads a chain of snapshot files in order.
+///
+/// The first file may be a full snapshot or a delta against `LiteSVM::new()`;
+/// every following file must be a delta against the state built so far.
+pub fn load_delta_chain<P: AsRef<Path>>(
+    paths: impl IntoIterator<Item = P>,
+) -> Result<LiteSVM, PersistenceError> {
+    let mut paths = paths.into_iter();
+    let first = std::fs::read(paths.next().ok_or(PersistenceError::EmptyInput)?)?;
+    let mut svm = match first.first() {
+        Some(&DELTA_STATE_VERSION) => delta_from_bytes(&LiteSVM::new(), &first)?,
+        _ => crate::from_bytes(&first)?,
+    };
+    for path in paths {
+        svm = load_delta(&svm, path)?;
+    }
+    Ok(svm)
+}
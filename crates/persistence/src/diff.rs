@@ -0,0 +1,307 @@
+//! Structural comparison of two LiteSVM states.
+
+use {
+    litesvm::LiteSVM,
+    solana_account::{AccountSharedData, ReadableAccount},
+    solana_address::Address,
+    solana_compute_budget::compute_budget::ComputeBudget,
+    solana_fee_structure::FeeStructure,
+    solana_hash::Hash,
+    std::{
+        collections::{BTreeMap, BTreeSet},
+        fmt,
+    },
+};
+
+/// A value that differs between the two states.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change<T> {
+    pub before: T,
+    pub after: T,
+}
+
+impl<T: PartialEq> Change<T> {
+    fn between(before: T, after: T) -> Option<Self> {
+        (before != after).then_some(Self { before, after })
+    }
+}
+
+/// Fields of an account present in both states that differ.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountChange {
+    pub address: Address,
+    pub lamports: Option<Change<u64>>,
+    pub owner: Option<Change<Address>>,
+    pub executable: Option<Change<bool>>,
+    pub rent_epoch: Option<Change<u64>>,
+    pub data: Option<DataChange>,
+}
+
+/// Summary of differing account data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataChange {
+    pub before_len: usize,
+    pub after_len: usize,
+    /// Offset of the first byte that differs, or the length of the shorter
+    /// data when one is a prefix of the other.
+    pub first_difference: usize,
+}
+
+/// A feature whose activation differs. `None` means inactive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeatureChange {
+    pub feature: Address,
+    pub before: Option<u64>,
+    pub after: Option<u64>,
+}
+
+/// Differences between two LiteSVM states, as returned by [`diff`].
+///
+/// All lists are sorted by address. The [`Display`](fmt::Display) impl
+/// renders a human-readable report.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SnapshotDiff {
+    pub added_accounts: Vec<Address>,
+    pub removed_accounts: Vec<Address>,
+    pub modified_accounts: Vec<AccountChange>,
+    pub features: Vec<FeatureChange>,
+    pub latest_blockhash: Option<Change<Hash>>,
+    pub fee_structure: Option<Change<FeeStructure>>,
+    pub compute_budget: Option<Change<Option<ComputeBudget>>>,
+    /// Stake per vote account; `0` when the vote account has no stake.
+    pub epoch_stakes: Vec<(Address, Change<u64>)>,
+}
+
+impl SnapshotDiff {
+    /// Whether the two states are equivalent.
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// Compares two LiteSVM states, typically loaded from saved snapshots.
+pub fn diff(a: &LiteSVM, b: &LiteSVM) -> SnapshotDiff {
+    let (before, after) = (a.accounts_db(), b.accounts_db());
+    let mut added_accounts: Vec<Address> = after
+        .iter()
+        .filter(|(address, _)| before.get_account_ref(address).is_none())
+        .map(|(address, _)| *address)
+        .collect();
+    added_accounts.sort_unstable();
+    let mut removed_accounts: Vec<Address> = before
+        .iter()
+        .filter(|(address, _)| after.get_account_ref(address).is_none())
+        .map(|(address, _)| *address)
+        .collect();
+    removed_accounts.sort_unstable();
+    let mut modified_accounts: Vec<AccountChange> = before
+        .iter()
+        .filter_map(|(address, account)| {
+            let other = after.get_account_ref(address)?;
+            (account != other).then(|| account_change(*address, account, other))
+        })
+        .collect();
+    modified_accounts.sort_unstable_by_key(|change| change.address);
+
+    SnapshotDiff {
+        added_accounts,
+        removed_accounts,
+        modified_accounts,
+        features: feature_changes(a, b),
+        latest_blockhash: Change::between(a.latest_blockhash(), b.latest_blockhash()),
+        fee_structure: Change::between(
+            a.get_fee_structure().clone(),
+            b.get_fee_structure().clone(),
+        ),
+        compute_budget: Change::between(a.get_compute_budget(), b.get_compute_budget()),
+        epoch_stakes: epoch_stake_changes(a, b),
+    }
+}
+
+fn account_change(
+    address: Address,
+    before: &AccountSharedData,
+    after: &AccountSharedData,
+) -> AccountChange {
+    let data = (before.data() != after.data()).then(|| DataChange {
+        before_len: before.data().len(),
+        after_len: after.data().len(),
+        first_difference: before
+            .data()
+            .iter()
+            .zip(after.data())
+            .position(|(x, y)| x != y)
+            .unwrap_or_else(|| before.data().len().min(after.data().len())),
+    });
+    AccountChange {
+        address,
+        lamports: Change::between(before.lamports(), after.lamports()),
+        owner: Change::between(*before.owner(), *after.owner()),
+        executable: Change::between(before.executable(), after.executable()),
+        rent_epoch: Change::between(before.rent_epoch(), after.rent_epoch()),
+        data,
+    }
+}
+
+fn feature_changes(a: &LiteSVM, b: &LiteSVM) -> Vec<FeatureChange> {
+    let (before, after) = (a.get_feature_set_ref(), b.get_feature_set_ref());
+    let features: BTreeSet<Address> = before
+        .active()
+        .keys()
+        .chain(before.inactive())
+        .chain(after.active().keys())
+        .chain(after.inactive())
+        .copied()
+        .collect();
+    features
+        .into_iter()
+        .filter_map(|feature| {
+            let change = Change::between(
+                before.activated_slot(&feature),
+                after.activated_slot(&feature),
+            )?;
+            Some(FeatureChange {
+                feature,
+                before: change.before,
+                after: change.after,
+            })
+        })
+        .collect()
+}
+
+fn epoch_stake_changes(a: &LiteSVM, b: &LiteSVM) -> Vec<(Address, Change<u64>)> {
+    let before: BTreeMap<Address, u64> = a.epoch_vote_stakes().map(|(k, v)| (*k, *v)).collect();
+    let after: BTreeMap<Address, u64> = b.epoch_vote_stakes().map(|(k, v)| (*k, *v)).collect();
+    let vote_accounts: BTreeSet<Address> = before.keys().chain(after.keys()).copied().collect();
+    vote_accounts
+        .into_iter()
+        .filter_map(|vote_account| {
+            let change = Change::between(
+                before.get(&vote_account).copied().unwrap_or_default(),
+                after.get(&vote_account).copied().unwrap_or_default(),
+            )?;
+            Some((vote_account, change))
+        })
+        .collect()
+}
+
+fn activation(slot: Option<u64>) -> String {
+    match slot {
+        Some(slot) => format!("active at slot {slot}"),
+        None => "inactive".to_string(),
+    }
+}
+
+impl fmt::Display for SnapshotDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no differences");
+        }
+
+        for address in &self.added_accounts {
+            writeln!(f, "+ account {address}")?;
+        }
+        for address in &self.removed_accounts {
+            writeln!(f, "- account {address}")?;
+        }
+        for change in &self.modified_accounts {
+            writeln!(f, "~ account {}", change.address)?;
+            if let Some(Change { before, after }) = &change.lamports {
+                writeln!(f, "    lamports: {before} -> {after}")?;
+            }
+            if let Some(Change { before, after }) = &change.owner {
+                writeln!(f, "    owner: {before} -> {after}")?;
+            }
+            if let Some(Change { before, after }) = &change.executable {
+                writeln!(f, "    executable: {before} -> {after}")?;
+            }
+            if let Some(Change { before, after }) = &change.rent_epoch {
+                writeln!(f, "    rent epoch: {before} -> {after}")?;
+            }
+            if let Some(data) = &change.data {
+                writeln!(
+                    f,
+                    "    data: {} -> {} bytes, first difference at byte {}",
+                    data.before_len, data.after_len, data.first_difference
+                )?;
+            }
+        }
+
+        for change in &self.features {
+            writeln!(
+                f,
+                "~ feature {}: {} -> {}",
+                change.feature,
+                activation(change.before),
+                activation(change.after)
+            )?;
+        }
+
+        if let Some(Change { before, after }) = &self.latest_blockhash {
+            writeln!(f, "~ latest blockhash: {before} -> {after}")?;
+        }
+
+        if let Some(Change { before, after }) = &self.fee_structure {
+            writeln!(f, "~ fee structure")?;
+            if before.lamports_per_signature != after.lamports_per_signature {
+                writeln!(
+                    f,
+                    "    lamports per signature: {} -> {}",
+                    before.lamports_per_signature, after.lamports_per_signature
+                )?;
+            }
+            if before.lamports_per_write_lock != after.lamports_per_write_lock {
+                writeln!(
+                    f,
+                    "    lamports per write lock: {} -> {}",
+                    before.lamports_per_write_lock, after.lamports_per_write_lock
+                )?;
+            }
+            if before.compute_fee_bins != after.compute_fee_bins {
+                writeln!(
+                    f,
+                    "    compute fee bins: {:?} -> {:?}",
+                    before.compute_fee_bins, after.compute_fee_bins
+                )?;
+            }
+        }
+
+        if let Some(Change { before, after }) = &self.compute_budget {
+            writeln!(f, "~ compute budget")?;
+            match (before, after) {
+                (Some(before), Some(after)) => {
+                    // Field-by-field through the pretty `Debug` output, which
+                    // keeps this in sync with `ComputeBudget` for free.
+                    let (before, after) = (format!("{before:#?}"), format!("{after:#?}"));
+                    for (before, after) in before.lines().zip(after.lines()) {
+                        if before != after {
+                            let name = before.trim().split(':').next().unwrap_or_default();
+                            let value = |line: &str| {
+                                line.split_once(": ")
+                                    .map(|(_, value)| value.trim_end_matches(',').to_string())
+                                    .unwrap_or_default()
+                            };
+                            writeln!(f, "    {name}: {} -> {}", value(before), value(after))?;
+                        }
+                    }
+                }
+                (before, after) => {
+                    let describe = |budget: &Option<ComputeBudget>| {
+                        if budget.is_some() {
+                            "custom"
+                        } else {
+                            "default"
+                        }
+                    };
+                    writeln!(f, "    {} -> {}", describe(before), describe(after))?;
+                }
+            }
+        }
+
+        for (vote_account, Change { before, after }) in &self.epoch_stakes {
+            writeln!(f, "~ epoch stake {vote_account}: {before} -> {after}")?;
+        }
+
+        Ok(())
+    }
+}
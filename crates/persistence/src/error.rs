@@ -8,16 +8,33 @@ pub enum PersistenceError {
     Write(#[from] wincode::error::WriteError),
     #[error("read error: {0}")]
     Read(#[from] wincode::error::ReadError),
+    #[cfg(feature = "json")]
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
     #[error("empty input")]
     EmptyInput,
     #[error("unsupported snapshot version: {0}")]
     UnsupportedVersion(u8),
+    #[error("delta snapshot must be loaded on top of a base state")]
+    DeltaWithoutBase,
+    #[error("expected a delta snapshot, found a full snapshot")]
+    NotADelta,
     #[error("failed to rebuild caches: {0}")]
     CacheRebuild(#[from] litesvm::error::LiteSVMError),
     #[error("invalid epoch stakes: {0}")]
     InvalidEpochStakes(#[source] litesvm::error::LiteSVMError),
     #[error("duplicate epoch stake for vote account {0}")]
     DuplicateEpochStake(solana_address::Address),
+    #[error("malformed accounts file {} at offset {offset}", path.display())]
+    MalformedAccountsFile {
+        path: std::path::PathBuf,
+        offset: usize,
+    },
+    #[error("failed to remove account {0}: {1}")]
+    RemoveClosedAccount(
+        solana_address::Address,
+        #[source] litesvm::error::LiteSVMError,
+    ),
     #[error("serialization thread panicked")]
     ThreadPanic,
 }
@@ -0,0 +1,426 @@
+//! JSON encoding of snapshots.
+//!
+//! Meant for fixtures that are reviewed as text: addresses, hashes and
+//! signatures are base58 strings, account data is base64, and accounts are
+//! sorted by address so that regenerating a fixture gives a minimal diff.
+//! With `decode_accounts`, known account types also get a `decoded` field;
+//! it is there for readers only and is ignored when loading.
+
+use {
+    crate::{
+        types::{
+            ComputeBudgetWire, FeatureSetSnapshot, LiteSvmSnapshotV2, LiteSvmSnapshotV3, TxResult,
+        },
+        PersistenceError, STATE_VERSION,
+    },
+    base64::{prelude::BASE64_STANDARD, Engine},
+    serde::{de, Deserialize, Deserializer, Serialize, Serializer},
+    serde_json::{json, Value},
+    solana_account::{AccountSharedData, ReadableAccount, WritableAccount},
+    solana_address::Address,
+    solana_clock::Clock,
+    solana_compute_budget::compute_budget::ComputeBudget,
+    solana_fee_structure::{FeeBin, FeeStructure},
+    solana_hash::Hash,
+    solana_loader_v3_interface::state::UpgradeableLoaderState,
+    solana_program_pack::Pack,
+    solana_signature::Signature,
+    spl_token_interface::state::{Account as TokenAccount, AccountState, Mint},
+    std::{
+        collections::{BTreeMap, BTreeSet},
+        fmt,
+        str::FromStr,
+    },
+    wincode::DeserializeOwned,
+};
+
+const TOKEN_2022_PROGRAM_ID: Address =
+    Address::from_str_const("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// Serializes through `Display` and deserializes through `FromStr`, which
+/// is base58 for addresses, hashes and signatures.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct Base58<T>(T);
+
+impl<T: fmt::Display> Serialize for Base58<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.0)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Base58<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map(Base58).map_err(de::Error::custom)
+    }
+}
+
+mod base64_bytes {
+    use super::*;
+
+    pub fn serialize<T, S>(bytes: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<[u8]>,
+        S: Serializer,
+    {
+        serializer.serialize_str(&BASE64_STANDARD.encode(bytes))
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: TryFrom<Vec<u8>>,
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        let bytes = BASE64_STANDARD.decode(s).map_err(de::Error::custom)?;
+        let len = bytes.len();
+        T::try_from(bytes).map_err(|_| de::Error::invalid_length(len, &"a valid byte length"))
+    }
+}
+
+mod option_compute_budget {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct Wrapper(#[serde(with = "ComputeBudgetWire")] ComputeBudget);
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<ComputeBudget>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.map(Wrapper).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<ComputeBudget>, D::Error> {
+        Ok(Option::<Wrapper>::deserialize(deserializer)?.map(|Wrapper(budget)| budget))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotJson {
+    version: u8,
+    accounts: Vec<AccountJson>,
+    #[serde(with = "base64_bytes")]
+    airdrop_keypair: [u8; 64],
+    feature_set: FeatureSetJson,
+    latest_blockhash: Base58<Hash>,
+    history: Vec<HistoryEntryJson>,
+    history_capacity: u64,
+    #[serde(with = "option_compute_budget")]
+    compute_budget: Option<ComputeBudget>,
+    sigverify: bool,
+    blockhash_check: bool,
+    fee_structure: FeeStructureJson,
+    log_bytes_limit: Option<u64>,
+    epoch_vote_stakes: BTreeMap<Base58<Address>, u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FeeStructureJson {
+    lamports_per_signature: u64,
+    lamports_per_write_lock: u64,
+    compute_fee_bins: Vec<FeeBinJson>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FeeBinJson {
+    limit: u64,
+    fee: u64,
+}
+
+impl From<FeeStructure> for FeeStructureJson {
+    fn from(value: FeeStructure) -> Self {
+        Self {
+            lamports_per_signature: value.lamports_per_signature,
+            lamports_per_write_lock: value.lamports_per_write_lock,
+            compute_fee_bins: value
+                .compute_fee_bins
+                .into_iter()
+                .map(|bin| FeeBinJson {
+                    limit: bin.limit,
+                    fee: bin.fee,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<FeeStructureJson> for FeeStructure {
+    fn from(value: FeeStructureJson) -> Self {
+        Self {
+            lamports_per_signature: value.lamports_per_signature,
+            lamports_per_write_lock: value.lamports_per_write_lock,
+            compute_fee_bins: value
+                .compute_fee_bins
+                .into_iter()
+                .map(|bin| FeeBin {
+                    limit: bin.limit,
+                    fee: bin.fee,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct AccountJson {
+    address: Base58<Address>,
+    lamports: u64,
+    owner: Base58<Address>,
+    executable: bool,
+    rent_epoch: u64,
+    #[serde(with = "base64_bytes")]
+    data: Vec<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    decoded: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FeatureSetJson {
+    active: BTreeMap<Base58<Address>, u64>,
+    inactive: BTreeSet<Base58<Address>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct HistoryEntryJson {
+    signature: Base58<Signature>,
+    result: TxResult,
+}
+
+fn account_json(address: &Address, account: &AccountSharedData, decode: bool) -> AccountJson {
+    AccountJson {
+        address: Base58(*address),
+        lamports: account.lamports(),
+        owner: Base58(*account.owner()),
+        executable: account.executable(),
+        rent_epoch: account.rent_epoch(),
+        data: account.data().to_vec(),
+        decoded: decode.then(|| decode_account(address, account)).flatten(),
+    }
+}
+
+fn to_json(snapshot: LiteSvmSnapshotV3, decode_accounts: bool) -> SnapshotJson {
+    let LiteSvmSnapshotV3 {
+        state,
+        epoch_vote_stakes,
+    } = snapshot;
+    let mut accounts: Vec<AccountJson> = state
+        .accounts
+        .into_iter()
+        .map(|entry| account_json(&entry.address, &entry.account, decode_accounts))
+        .collect();
+    accounts.sort_unstable_by_key(|account| account.address.0);
+
+    SnapshotJson {
+        version: STATE_VERSION,
+        accounts,
+        airdrop_keypair: state.airdrop_kp,
+        feature_set: FeatureSetJson {
+            active: state
+                .feature_set
+                .active
+                .into_iter()
+                .map(|entry| (Base58(entry.address), entry.slot))
+                .collect(),
+            inactive: state.feature_set.inactive.into_iter().map(Base58).collect(),
+        },
+        latest_blockhash: Base58(state.latest_blockhash),
+        history: state
+            .history
+            .into_iter()
+            .map(|(signature, result)| HistoryEntryJson {
+                signature: Base58(signature),
+                result,
+            })
+            .collect(),
+        history_capacity: state.history_capacity,
+        compute_budget: state.compute_budget,
+        sigverify: state.sigverify,
+        blockhash_check: state.blockhash_check,
+        fee_structure: state.fee_structure.into(),
+        log_bytes_limit: state.log_bytes_limit,
+        epoch_vote_stakes: epoch_vote_stakes
+            .into_iter()
+            .map(|(vote_account, stake)| (Base58(vote_account), stake))
+            .collect(),
+    }
+}
+
+fn from_json(json: SnapshotJson) -> Result<LiteSvmSnapshotV3, PersistenceError> {
+    if json.version != STATE_VERSION {
+        return Err(PersistenceError::UnsupportedVersion(json.version));
+    }
+    let accounts = json
+        .accounts
+        .into_iter()
+        .map(|account| {
+            let mut shared = AccountSharedData::new(account.lamports, 0, &account.owner.0);
+            shared.set_data_from_slice(&account.data);
+            shared.set_executable(account.executable);
+            shared.set_rent_epoch(account.rent_epoch);
+            (account.address.0, shared).into()
+        })
+        .collect();
+
+    Ok(LiteSvmSnapshotV3 {
+        state: LiteSvmSnapshotV2 {
+            accounts,
+            airdrop_kp: json.airdrop_keypair,
+            feature_set: FeatureSetSnapshot {
+                active: json
+                    .feature_set
+                    .active
+                    .into_iter()
+                    .map(|(address, slot)| (address.0, slot).into())
+                    .collect(),
+                inactive: json
+                    .feature_set
+                    .inactive
+                    .into_iter()
+                    .map(|address| address.0)
+                    .collect(),
+            },
+            latest_blockhash: json.latest_blockhash.0,
+            history: json
+                .history
+                .into_iter()
+                .map(|entry| (entry.signature.0, entry.result))
+                .collect(),
+            history_capacity: json.history_capacity,
+            compute_budget: json.compute_budget,
+            sigverify: json.sigverify,
+            blockhash_check: json.blockhash_check,
+            fee_structure: json.fee_structure.into(),
+            log_bytes_limit: json.log_bytes_limit,
+        },
+        epoch_vote_stakes: json
+            .epoch_vote_stakes
+            .into_iter()
+            .map(|(vote_account, stake)| (vote_account.0, stake))
+            .collect(),
+    })
+}
+
+/// Whether `bytes` hold a JSON snapshot rather than a versioned binary one.
+pub(crate) fn is_json(bytes: &[u8]) -> bool {
+    bytes.iter().find(|byte| !byte.is_ascii_whitespace()) == Some(&b'{')
+}
+
+pub(crate) fn to_json_bytes(
+    snapshot: LiteSvmSnapshotV3,
+    decode_accounts: bool,
+) -> Result<Vec<u8>, PersistenceError> {
+    let mut bytes = serde_json::to_vec_pretty(&to_json(snapshot, decode_accounts))?;
+    bytes.push(b'\n');
+    Ok(bytes)
+}
+
+pub(crate) fn from_json_bytes(bytes: &[u8]) -> Result<LiteSvmSnapshotV3, PersistenceError> {
+    from_json(serde_json::from_slice(bytes)?)
+}
+
+/// Renders a single account as it appears in a JSON snapshot, including the
+/// `decoded` view when its type is known.
+pub fn account_to_json(
+    address: &Address,
+    account: &AccountSharedData,
+) -> Result<String, PersistenceError> {
+    Ok(serde_json::to_string_pretty(&account_json(
+        address, account, true,
+    ))?)
+}
+
+fn decode_account(address: &Address, account: &AccountSharedData) -> Option<Value> {
+    let data = account.data();
+    if *address == solana_sdk_ids::sysvar::clock::id() {
+        let clock = Clock::deserialize_from(data).ok()?;
+        return Some(json!({
+            "type": "clock",
+            "slot": clock.slot,
+            "epoch_start_timestamp": clock.epoch_start_timestamp,
+            "epoch": clock.epoch,
+            "leader_schedule_epoch": clock.leader_schedule_epoch,
+            "unix_timestamp": clock.unix_timestamp,
+        }));
+    }
+
+    let owner = account.owner();
+    if *owner == solana_sdk_ids::bpf_loader_upgradeable::id() {
+        return Some(match UpgradeableLoaderState::deserialize_from(data).ok()? {
+            UpgradeableLoaderState::Uninitialized => json!({ "type": "uninitialized" }),
+            UpgradeableLoaderState::Buffer { authority_address } => json!({
+                "type": "buffer",
+                "authority": authority_address.map(|address| address.to_string()),
+            }),
+            UpgradeableLoaderState::Program {
+                programdata_address,
+            } => json!({
+                "type": "program",
+                "programdata": programdata_address.to_string(),
+            }),
+            UpgradeableLoaderState::ProgramData {
+                slot,
+                upgrade_authority_address,
+            } => json!({
+                "type": "programdata",
+                "slot": slot,
+                "upgrade_authority": upgrade_authority_address.map(|address| address.to_string()),
+            }),
+        });
+    }
+    if *owner == spl_token_interface::ID || *owner == TOKEN_2022_PROGRAM_ID {
+        return decode_token_account(data, *owner == TOKEN_2022_PROGRAM_ID);
+    }
+    None
+}
+
+/// Decodes the base state of a mint or token account. Token-2022 accounts
+/// with extensions are told apart by the account type byte that follows the
+/// base token account layout.
+fn decode_token_account(data: &[u8], is_token_2022: bool) -> Option<Value> {
+    const MINT_ACCOUNT_TYPE: u8 = 1;
+    const TOKEN_ACCOUNT_TYPE: u8 = 2;
+
+    let account_type = is_token_2022
+        .then(|| data.get(TokenAccount::LEN).copied())
+        .flatten();
+    let optional_address = |address: Option<Address>| address.map(|address| address.to_string());
+
+    if data.len() == Mint::LEN || account_type == Some(MINT_ACCOUNT_TYPE) {
+        let mint = Mint::unpack_from_slice(&data[..Mint::LEN]).ok()?;
+        Some(json!({
+            "type": "mint",
+            "mint_authority": optional_address(mint.mint_authority.into()),
+            "supply": mint.supply,
+            "decimals": mint.decimals,
+            "is_initialized": mint.is_initialized,
+            "freeze_authority": optional_address(mint.freeze_authority.into()),
+        }))
+    } else if data.len() == TokenAccount::LEN || account_type == Some(TOKEN_ACCOUNT_TYPE) {
+        let account = TokenAccount::unpack_from_slice(&data[..TokenAccount::LEN]).ok()?;
+        let state = match account.state {
+            AccountState::Uninitialized => "uninitialized",
+            AccountState::Initialized => "initialized",
+            AccountState::Frozen => "frozen",
+        };
+        Some(json!({
+            "type": "token_account",
+            "mint": account.mint.to_string(),
+            "owner": account.owner.to_string(),
+            "amount": account.amount,
+            "delegate": optional_address(account.delegate.into()),
+            "state": state,
+            "is_native": Option::<u64>::from(account.is_native),
+            "delegated_amount": account.delegated_amount,
+            "close_authority": optional_address(account.close_authority.into()),
+        }))
+    } else {
+        None
+    }
+}
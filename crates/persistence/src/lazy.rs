@@ -0,0 +1,143 @@
+//! Memory-mapped snapshot loading.
+//!
+//! [`load_from_file_lazy`] indexes the accounts of a snapshot in place and
+//! only copies an account's data out of the mapping the first time it is
+//! read, so a snapshot with millions of accounts loads in roughly the time it
+//! takes to walk their headers.
+//!
+//! Each account entry of a current snapshot is laid out as wincode writes it:
+//!
+//! ```text
+//! address     [u8; 32]
+//! lamports    u64
+//! data        u64 length, then the bytes
+//! owner       [u8; 32]
+//! executable  bool
+//! rent_epoch  u64
+//! ```
+
+use {
+    crate::{
+        restore_from_snapshot,
+        types::{AccountEntryWire, LiteSvmSnapshotV3Tail},
+        PersistenceError, STATE_VERSION,
+    },
+    litesvm::{LazyAccounts, LiteSVM},
+    memmap2::Mmap,
+    solana_account::Account,
+    solana_address::Address,
+    solana_sdk_ids::{
+        bpf_loader, bpf_loader_deprecated, bpf_loader_upgradeable, loader_v4, native_loader, sysvar,
+    },
+    std::{
+        fs::File,
+        io::{self, ErrorKind},
+        ops::Range,
+        path::Path,
+    },
+    wincode::Deserialize,
+};
+
+/// Loads a LiteSVM state from a file, reading account data on first access.
+///
+/// The file is memory-mapped and must not be modified while the returned
+/// instance (or any clone of it) is alive. Sysvar and program accounts are
+/// still read up front since the runtime caches are built from them.
+/// Snapshots in an older version or another encoding are loaded eagerly.
+pub fn load_from_file_lazy(path: impl AsRef<Path>) -> Result<LiteSVM, PersistenceError> {
+    let file = File::open(path)?;
+    // SAFETY: the mapping is only read, and callers are told not to modify
+    // the file while it is in use.
+    let mmap = unsafe { Mmap::map(&file)? };
+    if mmap.first() != Some(&STATE_VERSION) {
+        return crate::from_bytes(&mmap);
+    }
+
+    let mut reader = Reader {
+        bytes: &mmap,
+        offset: 1,
+    };
+    let mut eager = Vec::new();
+    let mut lazy = Vec::new();
+    for _ in 0..reader.u64()? {
+        let address = reader.address()?;
+        let lamports = reader.u64()?;
+        let data_len = reader.u64()?;
+        let data = reader.range(data_len)?;
+        let mut header = Account {
+            lamports,
+            data: Vec::new(),
+            owner: reader.address()?,
+            executable: reader.bool()?,
+            rent_epoch: reader.u64()?,
+        };
+        if must_load_eagerly(&header) {
+            header.data = mmap[data].to_vec();
+            eager.push(AccountEntryWire::from((address, header.into())));
+        } else {
+            lazy.push((address, header, data));
+        }
+    }
+    let tail = LiteSvmSnapshotV3Tail::deserialize(&mmap[reader.offset..])?;
+
+    let mut svm = restore_from_snapshot(tail.with_accounts(eager))?;
+    let mut accounts = LazyAccounts::new(mmap);
+    for (address, header, data) in lazy {
+        accounts.insert(address, header, data);
+    }
+    svm.set_lazy_accounts(accounts);
+    Ok(svm)
+}
+
+/// Sysvars and programs feed the caches rebuilt on load.
+fn must_load_eagerly(account: &Account) -> bool {
+    account.executable
+        || [
+            sysvar::id(),
+            native_loader::id(),
+            bpf_loader::id(),
+            bpf_loader_deprecated::id(),
+            bpf_loader_upgradeable::id(),
+            loader_v4::id(),
+        ]
+        .contains(&account.owner)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl Reader<'_> {
+    fn range(&mut self, len: u64) -> io::Result<Range<usize>> {
+        let start = self.offset;
+        let end = usize::try_from(len)
+            .ok()
+            .and_then(|len| start.checked_add(len))
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(ErrorKind::UnexpectedEof)?;
+        self.offset = end;
+        Ok(start..end)
+    }
+
+    fn array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let range = self.range(N as u64)?;
+        Ok(self.bytes[range].try_into().unwrap())
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        self.array().map(u64::from_le_bytes)
+    }
+
+    fn address(&mut self) -> io::Result<Address> {
+        self.array().map(Address::new_from_array)
+    }
+
+    fn bool(&mut self) -> io::Result<bool> {
+        match self.array::<1>()? {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            _ => Err(ErrorKind::InvalidData.into()),
+        }
+    }
+}
@@ -1,7 +1,25 @@
+#[cfg(feature = "agave-snapshot")]
+mod agave_snapshot;
+mod delta;
+mod diff;
 mod error;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "mmap")]
+mod lazy;
 mod types;
 
-pub use error::PersistenceError;
+#[cfg(feature = "agave-snapshot")]
+pub use agave_snapshot::{load_agave_snapshot, AgaveSnapshotFilter};
+#[cfg(feature = "json")]
+pub use json::account_to_json;
+#[cfg(feature = "mmap")]
+pub use lazy::load_from_file_lazy;
+pub use {
+    delta::{delta_from_bytes, delta_to_bytes, load_delta, load_delta_chain, save_delta},
+    diff::{diff, AccountChange, Change, DataChange, FeatureChange, SnapshotDiff},
+    error::PersistenceError,
+};
 use {
     litesvm::LiteSVM,
     std::{
@@ -19,6 +37,9 @@ use {
 const V1_STATE_VERSION: u8 = 1;
 const V2_STATE_VERSION: u8 = 2;
 const STATE_VERSION: u8 = 3;
+/// Deltas use a separate version range so they are never mistaken for a full
+/// snapshot.
+const DELTA_STATE_VERSION: u8 = 0x80 | 1;
 
 fn extract_snapshot_v2(svm: &LiteSVM) -> LiteSvmSnapshotV2 {
     LiteSvmSnapshotV2 {
@@ -26,7 +47,6 @@ fn extract_snapshot_v2(svm: &LiteSVM) -> LiteSvmSnapshotV2 {
         // The actual data bytes are written once during serialization via AccountSchema.
         accounts: svm
             .accounts_db()
-            .inner
             .iter()
             .map(|(k, v)| AccountEntryWire::from((*k, v.clone())))
             .collect(),
@@ -115,19 +135,49 @@ fn deserialize_snapshot(version: u8, bytes: &[u8]) -> Result<LiteSvmSnapshotV3,
         }
         V2_STATE_VERSION => Ok(LiteSvmSnapshotV2::deserialize(bytes)?.into()),
         STATE_VERSION => Ok(LiteSvmSnapshotV3::deserialize(bytes)?),
+        DELTA_STATE_VERSION => Err(PersistenceError::DeltaWithoutBase),
         version => Err(PersistenceError::UnsupportedVersion(version)),
     }
 }
 
+fn decode_snapshot(bytes: &[u8]) -> Result<LiteSvmSnapshotV3, PersistenceError> {
+    #[cfg(feature = "json")]
+    if json::is_json(bytes) {
+        return json::from_json_bytes(bytes);
+    }
+    let (version, rest) = bytes.split_first().ok_or(PersistenceError::EmptyInput)?;
+    deserialize_snapshot(*version, rest)
+}
+
+/// Encoding used when writing a snapshot. Loading detects the format on its
+/// own.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SnapshotFormat {
+    /// Compact versioned wincode encoding.
+    #[default]
+    Binary,
+    /// Pretty-printed JSON with base58 addresses and base64 account data.
+    /// With `decode_accounts`, known account types (token mints and
+    /// accounts, upgradeable loader state, the clock sysvar) are also
+    /// rendered in a `decoded` field that is ignored when loading.
+    #[cfg(feature = "json")]
+    Json { decode_accounts: bool },
+}
+
 /// Saves the full LiteSVM state to a file.
 pub fn save_to_file(svm: &LiteSVM, path: impl AsRef<Path>) -> Result<(), PersistenceError> {
-    let snapshot = extract_snapshot(svm);
+    save_to_file_with(svm, path, SnapshotFormat::Binary)
+}
+
+/// Saves the full LiteSVM state to a file in the given format.
+pub fn save_to_file_with(
+    svm: &LiteSVM,
+    path: impl AsRef<Path>,
+    format: SnapshotFormat,
+) -> Result<(), PersistenceError> {
+    let bytes = to_bytes_with(svm, format)?;
     let mut writer = BufWriter::new(File::create(path)?);
-    let payload_size = LiteSvmSnapshotV3::serialized_size(&snapshot)? as usize;
-    let mut payload = Vec::with_capacity(payload_size);
-    LiteSvmSnapshotV3::serialize_into(&mut payload, &snapshot)?;
-    writer.write_all(&[STATE_VERSION])?;
-    writer.write_all(&payload)?;
+    writer.write_all(&bytes)?;
     writer.flush()?;
     Ok(())
 }
@@ -137,26 +187,33 @@ pub fn load_from_file(path: impl AsRef<Path>) -> Result<LiteSVM, PersistenceErro
     let mut reader = File::open(path)?;
     let mut bytes = Vec::new();
     reader.read_to_end(&mut bytes)?;
-    let (version, rest) = bytes.split_first().ok_or(PersistenceError::EmptyInput)?;
-    let snapshot = deserialize_snapshot(*version, rest)?;
-    restore_from_snapshot(snapshot)
+    from_bytes(&bytes)
 }
 
 /// Serializes the full LiteSVM state to bytes.
 pub fn to_bytes(svm: &LiteSVM) -> Result<Vec<u8>, PersistenceError> {
+    to_bytes_with(svm, SnapshotFormat::Binary)
+}
+
+/// Serializes the full LiteSVM state to bytes in the given format.
+pub fn to_bytes_with(svm: &LiteSVM, format: SnapshotFormat) -> Result<Vec<u8>, PersistenceError> {
     let snapshot = extract_snapshot(svm);
-    let payload_size = LiteSvmSnapshotV3::serialized_size(&snapshot)? as usize;
-    let mut buf = Vec::with_capacity(1 + payload_size);
-    buf.push(STATE_VERSION);
-    LiteSvmSnapshotV3::serialize_into(&mut buf, &snapshot)?;
-    Ok(buf)
+    match format {
+        SnapshotFormat::Binary => {
+            let payload_size = LiteSvmSnapshotV3::serialized_size(&snapshot)? as usize;
+            let mut buf = Vec::with_capacity(1 + payload_size);
+            buf.push(STATE_VERSION);
+            LiteSvmSnapshotV3::serialize_into(&mut buf, &snapshot)?;
+            Ok(buf)
+        }
+        #[cfg(feature = "json")]
+        SnapshotFormat::Json { decode_accounts } => json::to_json_bytes(snapshot, decode_accounts),
+    }
 }
 
 /// Deserializes the full LiteSVM state from bytes.
 pub fn from_bytes(bytes: &[u8]) -> Result<LiteSVM, PersistenceError> {
-    let (version, rest) = bytes.split_first().ok_or(PersistenceError::EmptyInput)?;
-    let snapshot = deserialize_snapshot(*version, rest)?;
-    restore_from_snapshot(snapshot)
+    restore_from_snapshot(decode_snapshot(bytes)?)
 }
 
 #[cfg(test)]
@@ -1,6 +1,8 @@
 use {
     agave_feature_set::FeatureSet,
-    litesvm::types::{FailedTransactionMetadata, TransactionMetadata, TransactionResult},
+    litesvm::types::{
+        FailedTransactionMetadata, InvocationMetadata, TransactionMetadata, TransactionResult,
+    },
     solana_account::AccountSharedData,
     solana_address::Address,
     solana_compute_budget::compute_budget::ComputeBudget,
@@ -164,6 +166,11 @@ impl From<ComputeBudgetV1> for ComputeBudget {
 }
 
 #[derive(SchemaWrite, SchemaRead)]
+#[cfg_attr(
+    feature = "json",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(remote = "ComputeBudget")
+)]
 #[wincode(from = "ComputeBudget")]
 pub(crate) struct ComputeBudgetWire {
     pub compute_unit_limit: u64,
@@ -249,8 +256,8 @@ impl From<FeatureActivationWire> for (Address, u64) {
     }
 }
 
-#[derive(SchemaWrite, SchemaRead)]
-#[wincode(from = "TransactionMetadata")]
+/// A `TransactionMetadata` as snapshots store it.
+#[derive(Clone, SchemaWrite, SchemaRead)]
 pub(crate) struct TransactionMetadataWire {
     pub signature: Signature,
     pub logs: Vec<String>,
@@ -259,36 +266,108 @@ pub(crate) struct TransactionMetadataWire {
     pub compute_units_consumed: u64,
     pub return_data: TransactionReturnData,
     pub fee: u64,
+    /// Not part of the binary format; restored transactions have none.
+    #[wincode(skip)]
+    pub invocations: Vec<InvocationMetadata>,
 }
 
-#[derive(SchemaWrite, SchemaRead)]
-#[wincode(from = "FailedTransactionMetadata")]
+impl From<TransactionMetadata> for TransactionMetadataWire {
+    fn from(meta: TransactionMetadata) -> Self {
+        Self {
+            signature: meta.signature,
+            logs: meta.logs,
+            inner_instructions: meta.inner_instructions,
+            compute_units_consumed: meta.compute_units_consumed,
+            return_data: meta.return_data,
+            fee: meta.fee,
+            invocations: meta.invocations,
+        }
+    }
+}
+
+impl From<TransactionMetadataWire> for TransactionMetadata {
+    fn from(wire: TransactionMetadataWire) -> Self {
+        Self {
+            signature: wire.signature,
+            logs: wire.logs,
+            inner_instructions: wire.inner_instructions,
+            compute_units_consumed: wire.compute_units_consumed,
+            return_data: wire.return_data,
+            fee: wire.fee,
+            invocations: wire.invocations,
+        }
+    }
+}
+
+#[derive(Clone, SchemaWrite, SchemaRead)]
 pub(crate) struct FailedTransactionMetadataWire {
     pub err: TransactionError,
-    #[wincode(with = "TransactionMetadataWire")]
-    pub meta: TransactionMetadata,
+    pub meta: TransactionMetadataWire,
 }
 
 /// Mirror of `Result<TransactionMetadata, FailedTransactionMetadata>` so
-/// wincode can derive a schema for it.
-#[derive(SchemaWrite, SchemaRead)]
+/// wincode can derive a schema for it. JSON snapshots keep the metadata as
+/// litesvm serializes it.
+#[derive(Clone, SchemaWrite, SchemaRead)]
+#[cfg_attr(
+    feature = "json",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "TxResultJson", into = "TxResultJson")
+)]
 pub(crate) enum TxResult {
-    Ok(#[wincode(with = "TransactionMetadataWire")] TransactionMetadata),
-    Err(#[wincode(with = "FailedTransactionMetadataWire")] FailedTransactionMetadata),
+    Ok(TransactionMetadataWire),
+    Err(FailedTransactionMetadataWire),
 }
 
 impl TxResult {
     pub fn from_result(r: TransactionResult) -> Self {
         match r {
-            Ok(m) => TxResult::Ok(m),
-            Err(e) => TxResult::Err(e),
+            Ok(meta) => TxResult::Ok(meta.into()),
+            Err(FailedTransactionMetadata { err, meta }) => {
+                TxResult::Err(FailedTransactionMetadataWire {
+                    err,
+                    meta: meta.into(),
+                })
+            }
         }
     }
 
     pub fn into_result(self) -> TransactionResult {
         match self {
-            TxResult::Ok(m) => Ok(m),
-            TxResult::Err(e) => Err(e),
+            TxResult::Ok(meta) => Ok(meta.into()),
+            TxResult::Err(FailedTransactionMetadataWire { err, meta }) => {
+                Err(FailedTransactionMetadata {
+                    err,
+                    meta: meta.into(),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+#[derive(serde::Serialize, serde::Deserialize)]
+enum TxResultJson {
+    Ok(TransactionMetadata),
+    Err(FailedTransactionMetadata),
+}
+
+#[cfg(feature = "json")]
+impl From<TxResultJson> for TxResult {
+    fn from(json: TxResultJson) -> Self {
+        TxResult::from_result(match json {
+            TxResultJson::Ok(meta) => Ok(meta),
+            TxResultJson::Err(err) => Err(err),
+        })
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<TxResult> for TxResultJson {
+    fn from(result: TxResult) -> Self {
+        match result.into_result() {
+            Ok(meta) => TxResultJson::Ok(meta),
+            Err(err) => TxResultJson::Err(err),
         }
     }
 }
@@ -405,3 +484,54 @@ impl From<LiteSvmSnapshotV2> for LiteSvmSnapshotV3 {
         }
     }
 }
+
+/// Delta against a base state. The non-account state is stored in full;
+/// `snapshot.state.accounts` holds only the accounts that were added or
+/// changed, and `removed` the base accounts that no longer exist.
+#[derive(SchemaWrite, SchemaRead)]
+pub(crate) struct LiteSvmDeltaV1 {
+    pub snapshot: LiteSvmSnapshotV3,
+    pub removed: Vec<Address>,
+}
+
+/// The fields of a [`LiteSvmSnapshotV3`] that follow its accounts, read on
+/// their own once the accounts have been indexed in place.
+#[cfg(feature = "mmap")]
+#[derive(SchemaRead)]
+pub(crate) struct LiteSvmSnapshotV3Tail {
+    pub airdrop_kp: [u8; 64],
+    pub feature_set: FeatureSetSnapshot,
+    pub latest_blockhash: Hash,
+    pub history: Vec<(Signature, TxResult)>,
+    pub history_capacity: u64,
+    #[wincode(with = "Option<ComputeBudgetWire>")]
+    pub compute_budget: Option<ComputeBudget>,
+    pub sigverify: bool,
+    pub blockhash_check: bool,
+    #[wincode(with = "FeeStructureWire")]
+    pub fee_structure: FeeStructure,
+    pub log_bytes_limit: Option<u64>,
+    pub epoch_vote_stakes: Vec<(Address, u64)>,
+}
+
+#[cfg(feature = "mmap")]
+impl LiteSvmSnapshotV3Tail {
+    pub fn with_accounts(self, accounts: Vec<AccountEntryWire>) -> LiteSvmSnapshotV3 {
+        LiteSvmSnapshotV3 {
+            state: LiteSvmSnapshotV2 {
+                accounts,
+                airdrop_kp: self.airdrop_kp,
+                feature_set: self.feature_set,
+                latest_blockhash: self.latest_blockhash,
+                history: self.history,
+                history_capacity: self.history_capacity,
+                compute_budget: self.compute_budget,
+                sigverify: self.sigverify,
+                blockhash_check: self.blockhash_check,
+                fee_structure: self.fee_structure,
+                log_bytes_limit: self.log_bytes_limit,
+            },
+            epoch_vote_stakes: self.epoch_vote_stakes,
+        }
+    }
+}
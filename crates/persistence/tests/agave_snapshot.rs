@@ -0,0 +1,254 @@
+use {
+    litesvm::LiteSVM,
+    litesvm_persistence::{load_agave_snapshot, AgaveSnapshotFilter, PersistenceError},
+    solana_account::Account,
+    solana_address::Address,
+    std::path::{Path, PathBuf},
+};
+
+/// Serializes accounts in the Agave append-vec layout.
+fn storage_file(accounts: &[(Address, Account)]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for (write_version, (address, account)) in accounts.iter().enumerate() {
+        bytes.extend_from_slice(&(write_version as u64).to_le_bytes());
+        bytes.extend_from_slice(&(account.data.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(address.as_ref());
+        bytes.extend_from_slice(&account.lamports.to_le_bytes());
+        bytes.extend_from_slice(&account.rent_epoch.to_le_bytes());
+        bytes.extend_from_slice(account.owner.as_ref());
+        bytes.push(account.executable as u8);
+        bytes.extend_from_slice(&[0; 7]);
+        bytes.extend_from_slice(&[0; 32]);
+        bytes.extend_from_slice(&account.data);
+        bytes.resize(bytes.len().next_multiple_of(8), 0);
+    }
+    bytes
+}
+
+/// Writes a `.tar.zst` archive holding one storage file per `(slot, accounts)`.
+fn write_archive(dir: &Path, name: &str, storages: &[(u64, Vec<u8>)]) -> PathBuf {
+    let path = dir.join(name);
+    let encoder = zstd::Encoder::new(std::fs::File::create(&path).unwrap(), 0).unwrap();
+    let mut builder = tar::Builder::new(encoder);
+    let mut append = |entry_path: String, data: &[u8]| {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, entry_path, data).unwrap();
+    };
+    append("version".to_string(), b"1.2.0");
+    for (id, (slot, storage)) in storages.iter().enumerate() {
+        append(format!("accounts/{slot}.{id}"), storage);
+    }
+    builder.into_inner().unwrap().finish().unwrap();
+    path
+}
+
+fn account(lamports: u64, owner: Address, data: Vec<u8>) -> Account {
+    Account {
+        lamports,
+        data,
+        owner,
+        executable: false,
+        rent_epoch: u64::MAX,
+    }
+}
+
+#[test]
+fn newest_slot_wins_across_full_and_incremental_archives() {
+    let dir = tempfile::tempdir().unwrap();
+    let owner = Address::new_unique();
+    let updated = Address::new_unique();
+    let untouched = Address::new_unique();
+    let closed = Address::new_unique();
+
+    let full = write_archive(
+        dir.path(),
+        "snapshot-100-hash.tar.zst",
+        &[(
+            100,
+            storage_file(&[
+                (updated, account(1_000, owner, vec![1, 2, 3])),
+                (untouched, account(2_000, owner, vec![4; 13])),
+                (closed, account(3_000, owner, vec![])),
+            ]),
+        )],
+    );
+    let incremental = write_archive(
+        dir.path(),
+        "incremental-snapshot-100-150-hash.tar.zst",
+        &[(
+            150,
+            storage_file(&[
+                (updated, account(5_000, owner, vec![9, 9])),
+                (closed, account(0, owner, vec![])),
+            ]),
+        )],
+    );
+
+    let mut svm = LiteSVM::new();
+    // The incremental archive comes first to show that order doesn't matter.
+    let loaded =
+        load_agave_snapshot(&mut svm, [&incremental, &full], &AgaveSnapshotFilter::new()).unwrap();
+
+    assert_eq!(loaded, 2);
+    let updated_account = svm.get_account(&updated).unwrap();
+    assert_eq!(updated_account.lamports, 5_000);
+    assert_eq!(updated_account.data, vec![9, 9]);
+    assert_eq!(svm.get_account(&untouched).unwrap().data, vec![4; 13]);
+    assert!(svm.get_account(&closed).is_none());
+}
+
+#[test]
+fn closed_accounts_are_removed_from_the_svm() {
+    let dir = tempfile::tempdir().unwrap();
+    let owner = Address::new_unique();
+    let closed = Address::new_unique();
+
+    let full = write_archive(
+        dir.path(),
+        "snapshot-100-hash.tar.zst",
+        &[(
+            100,
+            storage_file(&[(closed, account(3_000, owner, vec![1]))]),
+        )],
+    );
+    let incremental = write_archive(
+        dir.path(),
+        "incremental-snapshot-100-150-hash.tar.zst",
+        &[(150, storage_file(&[(closed, account(0, owner, vec![]))]))],
+    );
+
+    let mut svm = LiteSVM::new();
+    // An earlier load put the account in the SVM.
+    load_agave_snapshot(&mut svm, [&full], &AgaveSnapshotFilter::new()).unwrap();
+    assert_eq!(svm.get_balance(&closed), Some(3_000));
+
+    let loaded =
+        load_agave_snapshot(&mut svm, [&incremental], &AgaveSnapshotFilter::new()).unwrap();
+
+    assert_eq!(loaded, 0);
+    assert!(svm.get_account(&closed).is_none());
+}
+
+#[test]
+fn filter_keeps_matching_owners_and_addresses() {
+    let dir = tempfile::tempdir().unwrap();
+    let wanted_owner = Address::new_unique();
+    let other_owner = Address::new_unique();
+    let owned = Address::new_unique();
+    let pinned = Address::new_unique();
+    let skipped = Address::new_unique();
+
+    let archive = write_archive(
+        dir.path(),
+        "snapshot-7-hash.tar.zst",
+        &[(
+            7,
+            storage_file(&[
+                (owned, account(10, wanted_owner, vec![])),
+                (pinned, account(20, other_owner, vec![])),
+                (skipped, account(30, other_owner, vec![])),
+            ]),
+        )],
+    );
+
+    let mut svm = LiteSVM::new();
+    let filter = AgaveSnapshotFilter::new()
+        .owners([wanted_owner])
+        .addresses([pinned]);
+    let loaded = load_agave_snapshot(&mut svm, [&archive], &filter).unwrap();
+
+    assert_eq!(loaded, 2);
+    assert_eq!(svm.get_balance(&owned), Some(10));
+    assert_eq!(svm.get_balance(&pinned), Some(20));
+    assert!(svm.get_account(&skipped).is_none());
+}
+
+#[test]
+fn filter_applies_to_the_newest_version() {
+    let dir = tempfile::tempdir().unwrap();
+    let wanted_owner = Address::new_unique();
+    let other_owner = Address::new_unique();
+    let closed = Address::new_unique();
+    let reassigned = Address::new_unique();
+    let kept = Address::new_unique();
+
+    let full = write_archive(
+        dir.path(),
+        "snapshot-100-hash.tar.zst",
+        &[(
+            100,
+            storage_file(&[
+                (closed, account(10, wanted_owner, vec![1])),
+                (reassigned, account(20, wanted_owner, vec![2])),
+                (kept, account(30, wanted_owner, vec![3])),
+            ]),
+        )],
+    );
+    let incremental = write_archive(
+        dir.path(),
+        "incremental-snapshot-100-150-hash.tar.zst",
+        &[(
+            150,
+            storage_file(&[
+                // Closed accounts are stored as owned by the system program.
+                (
+                    closed,
+                    account(0, solana_sdk_ids::system_program::ID, vec![]),
+                ),
+                (reassigned, account(20, other_owner, vec![2])),
+            ]),
+        )],
+    );
+
+    let filter = AgaveSnapshotFilter::new().owners([wanted_owner]);
+    for archives in [[&full, &incremental], [&incremental, &full]] {
+        let mut svm = LiteSVM::new();
+        let loaded = load_agave_snapshot(&mut svm, archives, &filter).unwrap();
+
+        assert_eq!(loaded, 1);
+        assert!(svm.get_account(&closed).is_none());
+        assert!(svm.get_account(&reassigned).is_none());
+        assert_eq!(svm.get_balance(&kept), Some(30));
+    }
+}
+
+#[test]
+fn zeroed_tail_of_preallocated_storage_is_ignored() {
+    let dir = tempfile::tempdir().unwrap();
+    let address = Address::new_unique();
+    let mut storage = storage_file(&[(address, account(42, Address::new_unique(), vec![7]))]);
+    storage.resize(4096, 0);
+    let archive = write_archive(dir.path(), "snapshot-1-hash.tar.zst", &[(1, storage)]);
+
+    let mut svm = LiteSVM::new();
+    load_agave_snapshot(&mut svm, [&archive], &AgaveSnapshotFilter::new()).unwrap();
+
+    assert_eq!(svm.get_balance(&address), Some(42));
+}
+
+#[test]
+fn truncated_storage_is_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let first = storage_file(&[(
+        Address::new_unique(),
+        account(1, Address::new_unique(), vec![]),
+    )]);
+    let mut storage = first.clone();
+    storage.extend_from_slice(&storage_file(&[(
+        Address::new_unique(),
+        account(1, Address::new_unique(), vec![1; 64]),
+    )]));
+    storage.truncate(storage.len() - 8);
+    let archive = write_archive(dir.path(), "snapshot-3-hash.tar.zst", &[(3, storage)]);
+
+    let mut svm = LiteSVM::new();
+    let result = load_agave_snapshot(&mut svm, [&archive], &AgaveSnapshotFilter::new());
+
+    assert!(matches!(
+        result,
+        Err(PersistenceError::MalformedAccountsFile { offset, .. }) if offset == first.len()
+    ));
+}
@@ -0,0 +1,196 @@
+use {
+    litesvm::LiteSVM,
+    litesvm_persistence::save_to_file,
+    solana_account::Account,
+    solana_address::Address,
+    solana_keypair::Keypair,
+    solana_native_token::LAMPORTS_PER_SOL,
+    solana_signer::Signer,
+    solana_system_interface::instruction::transfer,
+    solana_transaction::Transaction,
+    std::{path::Path, process::Output},
+};
+
+fn run(args: &[&str]) -> Output {
+    std::process::Command::new(env!("CARGO_BIN_EXE_litesvm-snapshot"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: Output) -> String {
+    assert_eq!(
+        output.status.code(),
+        Some(0),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn saved(svm: &LiteSVM, dir: &Path) -> String {
+    let path = dir.join("snapshot.bin");
+    save_to_file(svm, &path).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn accounts_can_be_filtered_by_owner_and_size() {
+    let dir = tempfile::tempdir().unwrap();
+    let owner = Address::new_unique();
+    let (small, large) = (Address::new_unique(), Address::new_unique());
+    let mut svm = LiteSVM::new();
+    for (address, size) in [(small, 8), (large, 512)] {
+        svm.set_account(
+            address,
+            Account {
+                lamports: LAMPORTS_PER_SOL,
+                data: vec![0; size],
+                owner,
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+        .unwrap();
+    }
+    let path = saved(&svm, dir.path());
+    let owner = owner.to_string();
+
+    let all = stdout(run(&["accounts", &path]));
+    assert!(all.contains(&small.to_string()));
+    assert!(all.contains(&spl_token_interface::ID.to_string()));
+
+    let owned = stdout(run(&["accounts", &path, "--owner", &owner]));
+    assert_eq!(owned.lines().count(), 3);
+
+    let large_only = stdout(run(&[
+        "accounts",
+        &path,
+        "--owner",
+        &owner,
+        "--min-size",
+        "100",
+    ]));
+    assert!(large_only.contains(&large.to_string()));
+    assert!(!large_only.contains(&small.to_string()));
+
+    assert_eq!(
+        run(&["accounts", &path, "--colour", "red"]).status.code(),
+        Some(2)
+    );
+}
+
+#[test]
+fn account_is_dumped_as_hex() {
+    let dir = tempfile::tempdir().unwrap();
+    let address = Address::new_unique();
+    let mut svm = LiteSVM::new();
+    svm.set_account(
+        address,
+        Account {
+            lamports: 42,
+            data: b"hello, litesvm!\x00\x01".to_vec(),
+            owner: Address::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    )
+    .unwrap();
+    let path = saved(&svm, dir.path());
+
+    let dump = stdout(run(&["account", &path, &address.to_string()]));
+
+    assert!(dump.contains("lamports:   42\n"));
+    assert!(dump.contains("data:       17 bytes\n"));
+    assert!(dump.contains(
+        "00000000  68 65 6c 6c 6f 2c 20 6c 69 74 65 73 76 6d 21 00  |hello, litesvm!.|\n"
+    ));
+    assert!(dump.contains("00000010  01"));
+
+    let missing = run(&["account", &path, &Address::new_unique().to_string()]);
+    assert_eq!(missing.status.code(), Some(2));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn account_is_dumped_as_json() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = saved(&LiteSVM::new(), dir.path());
+    let clock = solana_sdk_ids::sysvar::clock::id().to_string();
+
+    let dump = stdout(run(&["account", &path, &clock, "--json"]));
+
+    let value: serde_json::Value = serde_json::from_str(&dump).unwrap();
+    assert_eq!(value["address"], clock);
+    assert_eq!(value["decoded"]["type"], "clock");
+}
+
+#[test]
+fn features_config_and_history_are_listed() {
+    let dir = tempfile::tempdir().unwrap();
+    let payer = Keypair::new();
+    let mut svm = LiteSVM::new();
+    svm.airdrop(&payer.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[transfer(
+            &payer.pubkey(),
+            &Address::new_unique(),
+            LAMPORTS_PER_SOL / 2,
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        svm.latest_blockhash(),
+    );
+    let signature = tx.signatures[0];
+    svm.send_transaction(tx).unwrap();
+    let path = saved(&svm, dir.path());
+
+    let features = stdout(run(&["features", &path]));
+    let (feature, slot) = svm.get_feature_set_ref().active().iter().next().unwrap();
+    assert!(features
+        .lines()
+        .any(|line| line.starts_with(&feature.to_string())
+            && line.contains(&format!("active at slot {slot}"))));
+
+    let config = stdout(run(&["config", &path]));
+    assert!(config.contains(&format!(
+        "latest blockhash:        {}\n",
+        svm.latest_blockhash()
+    )));
+    assert!(config.contains("compute budget:          default\n"));
+
+    let history = stdout(run(&["history", &path]));
+    // The airdrop comes first.
+    assert_eq!(history.lines().count(), 2);
+    let last = history.lines().last().unwrap();
+    assert!(last.starts_with(&signature.to_string()));
+    assert!(last.ends_with("  ok"));
+}
+
+#[test]
+fn program_elf_is_extracted() {
+    let dir = tempfile::tempdir().unwrap();
+    let svm = LiteSVM::new();
+    let path = saved(&svm, dir.path());
+    let output = dir.path().join("token.so");
+    let program = spl_token_interface::ID;
+
+    stdout(run(&[
+        "extract-program",
+        &path,
+        &program.to_string(),
+        output.to_str().unwrap(),
+    ]));
+
+    assert_eq!(
+        std::fs::read(&output).unwrap(),
+        svm.accounts_db().try_program_elf_bytes(&program).unwrap()
+    );
+    let not_a_program = run(&[
+        "extract-program",
+        &path,
+        &solana_sdk_ids::sysvar::clock::id().to_string(),
+        output.to_str().unwrap(),
+    ]);
+    assert_eq!(not_a_program.status.code(), Some(2));
+}
@@ -0,0 +1,132 @@
+use {
+    litesvm::LiteSVM,
+    litesvm_persistence::{
+        delta_from_bytes, delta_to_bytes, from_bytes, load_delta_chain, save_delta, save_to_file,
+        to_bytes, PersistenceError,
+    },
+    solana_account::Account,
+    solana_address::Address,
+    solana_keypair::Keypair,
+    solana_native_token::LAMPORTS_PER_SOL,
+    solana_signer::Signer,
+};
+
+fn data_account(data: Vec<u8>) -> Account {
+    Account {
+        lamports: LAMPORTS_PER_SOL,
+        data,
+        owner: Address::new_unique(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+#[test]
+fn delta_against_fresh_svm_round_trip() {
+    let base = LiteSVM::new();
+    let mut svm = base.clone();
+    let kp = Keypair::new();
+    svm.airdrop(&kp.pubkey(), 3 * LAMPORTS_PER_SOL).unwrap();
+    let stored = Address::new_unique();
+    svm.set_account(stored, data_account(vec![7; 100])).unwrap();
+    svm.set_epoch_stake(Address::new_unique(), 55).unwrap();
+
+    let delta = delta_to_bytes(&base, &svm).unwrap();
+    assert!(delta.len() * 10 < to_bytes(&svm).unwrap().len());
+
+    // Applied to a different fresh instance with its own airdrop keypair.
+    let fresh = LiteSVM::new();
+    let restored = delta_from_bytes(&fresh, &delta).unwrap();
+
+    assert_eq!(
+        restored.get_balance(&kp.pubkey()),
+        Some(3 * LAMPORTS_PER_SOL)
+    );
+    assert_eq!(restored.get_account(&stored).unwrap().data, vec![7; 100]);
+    assert_eq!(restored.epoch_total_stake(), 55);
+    assert_eq!(restored.airdrop_pubkey(), svm.airdrop_pubkey());
+    assert_eq!(
+        restored.get_balance(&svm.airdrop_pubkey()),
+        svm.get_balance(&svm.airdrop_pubkey())
+    );
+    assert!(restored.get_account(&fresh.airdrop_pubkey()).is_none());
+    assert_eq!(restored.latest_blockhash(), svm.latest_blockhash());
+}
+
+#[test]
+fn removed_accounts_are_removed() {
+    let mut base = LiteSVM::new();
+    let kept = Address::new_unique();
+    let closed = Address::new_unique();
+    base.set_account(kept, data_account(vec![1])).unwrap();
+    base.set_account(closed, data_account(vec![2])).unwrap();
+
+    let mut svm = base.clone();
+    svm.set_account(closed, Account::default()).unwrap();
+
+    let restored = delta_from_bytes(&base, &delta_to_bytes(&base, &svm).unwrap()).unwrap();
+
+    assert_eq!(restored.get_account(&kept).unwrap().data, vec![1]);
+    assert!(restored.get_account(&closed).is_none());
+}
+
+#[test]
+fn delta_is_deterministic() {
+    let base = LiteSVM::new();
+    let mut svm = base.clone();
+    for i in 0..20 {
+        svm.set_account(Address::new_unique(), data_account(vec![i; 8]))
+            .unwrap();
+    }
+
+    assert_eq!(
+        delta_to_bytes(&base, &svm).unwrap(),
+        delta_to_bytes(&base, &svm).unwrap()
+    );
+}
+
+#[test]
+fn chain_of_deltas_on_full_snapshot() {
+    let dir = tempfile::tempdir().unwrap();
+    let first = Address::new_unique();
+    let second = Address::new_unique();
+
+    let mut svm = LiteSVM::new();
+    svm.airdrop(&first, LAMPORTS_PER_SOL).unwrap();
+    let full_path = dir.path().join("full.bin");
+    save_to_file(&svm, &full_path).unwrap();
+
+    let base = svm.clone();
+    svm.airdrop(&second, 2 * LAMPORTS_PER_SOL).unwrap();
+    let delta_one = dir.path().join("one.delta");
+    save_delta(&base, &svm, &delta_one).unwrap();
+
+    let base = svm.clone();
+    svm.airdrop(&first, 4 * LAMPORTS_PER_SOL).unwrap();
+    svm.warp_to_slot(10);
+    let delta_two = dir.path().join("two.delta");
+    save_delta(&base, &svm, &delta_two).unwrap();
+
+    let restored = load_delta_chain([&full_path, &delta_one, &delta_two]).unwrap();
+    assert_eq!(restored.get_balance(&first), Some(5 * LAMPORTS_PER_SOL));
+    assert_eq!(restored.get_balance(&second), Some(2 * LAMPORTS_PER_SOL));
+    assert_eq!(restored.get_sysvar::<solana_clock::Clock>().slot, 10);
+
+    // A chain may also start from a delta against `LiteSVM::new()`.
+    let restored = load_delta_chain([&delta_one]).unwrap();
+    assert_eq!(restored.get_balance(&second), Some(2 * LAMPORTS_PER_SOL));
+}
+
+#[test]
+fn full_and_delta_snapshots_are_not_interchangeable() {
+    let svm = LiteSVM::new();
+
+    assert!(matches!(
+        from_bytes(&delta_to_bytes(&svm, &svm).unwrap()),
+        Err(PersistenceError::DeltaWithoutBase)
+    ));
+    assert!(matches!(
+        delta_from_bytes(&svm, &to_bytes(&svm).unwrap()),
+        Err(PersistenceError::NotADelta)
+    ));
+}
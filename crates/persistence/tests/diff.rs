@@ -0,0 +1,143 @@
+use {
+    agave_feature_set::FeatureSet,
+    litesvm::LiteSVM,
+    litesvm_persistence::{diff, save_to_file, Change, DataChange, FeatureChange},
+    solana_account::Account,
+    solana_address::Address,
+    solana_compute_budget::compute_budget::ComputeBudget,
+    solana_native_token::LAMPORTS_PER_SOL,
+    std::process::Command,
+};
+
+fn data_account(data: Vec<u8>) -> Account {
+    Account {
+        lamports: LAMPORTS_PER_SOL,
+        data,
+        owner: Address::new_unique(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+#[test]
+fn identical_states_have_no_differences() {
+    let svm = LiteSVM::new();
+
+    let diff = diff(&svm, &svm.clone());
+
+    assert!(diff.is_empty());
+    assert_eq!(diff.to_string(), "no differences\n");
+}
+
+#[test]
+fn account_changes_are_reported() {
+    let kept = Address::new_unique();
+    let removed = Address::new_unique();
+    let added = Address::new_unique();
+    let mut before = LiteSVM::new();
+    before
+        .set_account(kept, data_account(vec![1, 2, 3]))
+        .unwrap();
+    before.set_account(removed, data_account(vec![])).unwrap();
+
+    let mut after = before.clone();
+    after.set_account(removed, Account::default()).unwrap();
+    after.set_account(added, data_account(vec![])).unwrap();
+    let mut account = after.get_account(&kept).unwrap();
+    account.lamports += 5;
+    account.data = vec![1, 9, 3, 4];
+    after.set_account(kept, account).unwrap();
+
+    let diff = diff(&before, &after);
+
+    assert_eq!(diff.added_accounts, vec![added]);
+    assert_eq!(diff.removed_accounts, vec![removed]);
+    assert_eq!(diff.modified_accounts.len(), 1);
+    let change = &diff.modified_accounts[0];
+    assert_eq!(change.address, kept);
+    assert_eq!(
+        change.lamports,
+        Some(Change {
+            before: LAMPORTS_PER_SOL,
+            after: LAMPORTS_PER_SOL + 5
+        })
+    );
+    assert_eq!(change.owner, None);
+    assert_eq!(
+        change.data,
+        Some(DataChange {
+            before_len: 3,
+            after_len: 4,
+            first_difference: 1
+        })
+    );
+    assert!(diff
+        .to_string()
+        .contains("data: 3 -> 4 bytes, first difference at byte 1"));
+}
+
+#[test]
+fn runtime_state_changes_are_reported() {
+    let mut feature_set = FeatureSet::all_enabled();
+    let feature = *feature_set.active().keys().next().unwrap();
+    let mut compute_budget = ComputeBudget::new_with_defaults(false);
+    let before = LiteSVM::new()
+        .with_feature_set(feature_set.clone())
+        .with_compute_budget(compute_budget);
+    feature_set.deactivate(&feature);
+    compute_budget.compute_unit_limit = 7;
+    let mut after = LiteSVM::new()
+        .with_feature_set(feature_set)
+        .with_compute_budget(compute_budget);
+    after.set_epoch_stake(Address::new_unique(), 10).unwrap();
+    after.expire_blockhash();
+
+    let diff = diff(&before, &after);
+
+    assert!(diff.features.contains(&FeatureChange {
+        feature,
+        before: Some(0),
+        after: None,
+    }));
+    assert!(diff.latest_blockhash.is_some());
+    assert!(diff
+        .to_string()
+        .contains("    compute_unit_limit: 1400000 -> 7\n"));
+    assert_eq!(diff.epoch_stakes.len(), 1);
+    assert_eq!(
+        diff.epoch_stakes[0].1,
+        Change {
+            before: 0,
+            after: 10
+        }
+    );
+}
+
+#[test]
+fn cli_reports_differences_through_exit_code() {
+    let dir = tempfile::tempdir().unwrap();
+    let address = Address::new_unique();
+    let before = LiteSVM::new();
+    let mut after = before.clone();
+    after.airdrop(&address, LAMPORTS_PER_SOL).unwrap();
+    let (before_path, after_path) = (dir.path().join("a.bin"), dir.path().join("b.bin"));
+    save_to_file(&before, &before_path).unwrap();
+    save_to_file(&after, &after_path).unwrap();
+
+    let run = |a, b| {
+        Command::new(env!("CARGO_BIN_EXE_litesvm-snapshot"))
+            .arg("diff")
+            .arg(a)
+            .arg(b)
+            .output()
+            .unwrap()
+    };
+
+    let same = run(&before_path, &before_path);
+    assert_eq!(same.status.code(), Some(0));
+
+    let changed = run(&before_path, &after_path);
+    assert_eq!(changed.status.code(), Some(1));
+    let stdout = String::from_utf8(changed.stdout).unwrap();
+    assert!(stdout.contains(&format!("+ account {address}")));
+}
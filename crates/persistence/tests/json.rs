@@ -0,0 +1,156 @@
+use {
+    litesvm::LiteSVM,
+    litesvm_persistence::{
+        from_bytes, load_from_file, save_to_file_with, to_bytes_with, SnapshotFormat,
+    },
+    solana_account::Account,
+    solana_address::Address,
+    solana_keypair::Keypair,
+    solana_native_token::LAMPORTS_PER_SOL,
+    solana_program_option::COption,
+    solana_program_pack::Pack,
+    solana_signer::Signer,
+    solana_system_interface::instruction::transfer,
+    solana_transaction::Transaction,
+    spl_token_interface::state::Mint,
+};
+
+const JSON: SnapshotFormat = SnapshotFormat::Json {
+    decode_accounts: false,
+};
+
+#[test]
+fn json_file_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("snapshot.json");
+
+    let kp = Keypair::new();
+    let stored = Address::new_unique();
+    let vote_account = Address::new_unique();
+    let mut svm = LiteSVM::new().with_log_bytes_limit(Some(2048));
+    svm.airdrop(&kp.pubkey(), 5 * LAMPORTS_PER_SOL).unwrap();
+    svm.set_account(
+        stored,
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![1, 2, 3, 255],
+            owner: Address::new_unique(),
+            executable: false,
+            rent_epoch: 7,
+        },
+    )
+    .unwrap();
+    svm.set_epoch_stake(vote_account, 99).unwrap();
+    svm.warp_to_slot(12);
+
+    save_to_file_with(&svm, &path, JSON).unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(text.contains(&format!("\"{stored}\"")));
+    assert!(text.contains("\"AQID/w==\""));
+
+    let restored = load_from_file(&path).unwrap();
+    assert_eq!(
+        restored.get_balance(&kp.pubkey()),
+        Some(5 * LAMPORTS_PER_SOL)
+    );
+    assert_eq!(restored.get_account(&stored), svm.get_account(&stored));
+    assert_eq!(restored.epoch_stake(&vote_account), 99);
+    assert_eq!(restored.latest_blockhash(), svm.latest_blockhash());
+    assert_eq!(restored.airdrop_pubkey(), svm.airdrop_pubkey());
+    assert_eq!(restored.get_log_bytes_limit(), Some(2048));
+    assert_eq!(
+        restored.get_sysvar::<solana_clock::Clock>().slot,
+        svm.get_sysvar::<solana_clock::Clock>().slot
+    );
+}
+
+#[test]
+fn json_preserves_transaction_history() {
+    let kp = Keypair::new();
+    let mut svm = LiteSVM::new();
+    svm.airdrop(&kp.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[transfer(&kp.pubkey(), &Address::new_unique(), 1_000_000)],
+        Some(&kp.pubkey()),
+        &[&kp],
+        svm.latest_blockhash(),
+    );
+    let signature = tx.signatures[0];
+    let meta = svm.send_transaction(tx).unwrap();
+
+    let restored = from_bytes(&to_bytes_with(&svm, JSON).unwrap()).unwrap();
+
+    assert_eq!(restored.get_transaction(&signature), Some(&Ok(meta)));
+}
+
+#[test]
+fn json_is_deterministic() {
+    let mut svm = LiteSVM::new();
+    for lamports in 1..20 {
+        svm.airdrop(&Address::new_unique(), lamports * LAMPORTS_PER_SOL)
+            .unwrap();
+    }
+
+    assert_eq!(
+        to_bytes_with(&svm, JSON).unwrap(),
+        to_bytes_with(&svm, JSON).unwrap()
+    );
+}
+
+#[test]
+fn decoded_accounts_are_informational() {
+    let mint = Address::new_unique();
+    let authority = Address::new_unique();
+    let mut data = vec![0; Mint::LEN];
+    Mint {
+        mint_authority: COption::Some(authority),
+        supply: 1_000,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    let mut svm = LiteSVM::new();
+    svm.set_account(
+        mint,
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            data,
+            owner: spl_token_interface::ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    )
+    .unwrap();
+
+    let bytes = to_bytes_with(
+        &svm,
+        SnapshotFormat::Json {
+            decode_accounts: true,
+        },
+    )
+    .unwrap();
+    let value: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    let accounts = value["accounts"].as_array().unwrap();
+    let decoded = |address: Address| {
+        accounts
+            .iter()
+            .find(|account| account["address"] == address.to_string())
+            .unwrap()["decoded"]
+            .clone()
+    };
+    assert_eq!(decoded(mint)["type"], "mint");
+    assert_eq!(decoded(mint)["supply"], 1_000);
+    assert_eq!(decoded(mint)["mint_authority"], authority.to_string());
+    assert_eq!(
+        decoded(solana_sdk_ids::sysvar::clock::id())["type"],
+        "clock"
+    );
+
+    // Editing the decoded view has no effect on the loaded state.
+    let text = String::from_utf8(bytes)
+        .unwrap()
+        .replace("\"supply\": 1000", "\"supply\": 5");
+    let restored = from_bytes(text.as_bytes()).unwrap();
+    assert_eq!(restored.get_account(&mint), svm.get_account(&mint));
+}
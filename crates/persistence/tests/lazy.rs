@@ -0,0 +1,126 @@
+use {
+    litesvm::LiteSVM,
+    litesvm_persistence::{diff, load_from_file_lazy, save_to_file, save_to_file_with},
+    solana_account::Account,
+    solana_address::Address,
+    solana_keypair::Keypair,
+    solana_native_token::LAMPORTS_PER_SOL,
+    solana_signer::Signer,
+    solana_system_interface::instruction::transfer,
+    solana_transaction::Transaction,
+};
+
+fn data_account(owner: Address, data: Vec<u8>) -> Account {
+    Account {
+        lamports: LAMPORTS_PER_SOL,
+        data,
+        owner,
+        executable: false,
+        rent_epoch: 3,
+    }
+}
+
+#[test]
+fn lazy_load_matches_eager_load() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("snapshot.bin");
+    let stored = Address::new_unique();
+    let mut svm = LiteSVM::new();
+    svm.set_account(stored, data_account(Address::new_unique(), vec![7; 100]))
+        .unwrap();
+    for _ in 0..10 {
+        svm.airdrop(&Address::new_unique(), LAMPORTS_PER_SOL)
+            .unwrap();
+    }
+    save_to_file(&svm, &path).unwrap();
+
+    let lazy = load_from_file_lazy(&path).unwrap();
+
+    assert!(!lazy.accounts_db().is_materialized(&stored));
+    assert_eq!(lazy.get_account(&stored), svm.get_account(&stored));
+    assert!(diff(&svm, &lazy).is_empty());
+}
+
+#[test]
+fn lazy_accounts_can_transact_and_close() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("snapshot.bin");
+    let payer = Keypair::new();
+    let closed = Keypair::new();
+    let recipient = Address::new_unique();
+    let mut svm = LiteSVM::new();
+    svm.airdrop(&payer.pubkey(), 2 * LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&closed.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    save_to_file(&svm, &path).unwrap();
+
+    let mut lazy = load_from_file_lazy(&path).unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            transfer(&payer.pubkey(), &recipient, LAMPORTS_PER_SOL),
+            transfer(&closed.pubkey(), &recipient, LAMPORTS_PER_SOL),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &closed],
+        lazy.latest_blockhash(),
+    );
+    let meta = lazy.send_transaction(tx).unwrap();
+
+    assert_eq!(lazy.get_balance(&recipient), Some(2 * LAMPORTS_PER_SOL));
+    assert_eq!(
+        lazy.get_balance(&payer.pubkey()),
+        Some(LAMPORTS_PER_SOL - meta.fee)
+    );
+    assert_eq!(lazy.get_account(&closed.pubkey()), None);
+    assert!(lazy
+        .accounts_db()
+        .iter()
+        .all(|(address, _)| *address != closed.pubkey()));
+
+    lazy.airdrop(&recipient, 1).unwrap();
+    assert_eq!(lazy.get_balance(&recipient), Some(2 * LAMPORTS_PER_SOL + 1));
+}
+
+#[test]
+fn lazy_accounts_are_found_by_owner() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("snapshot.bin");
+    let owner = Address::new_unique();
+    let (first, second) = (Address::new_unique(), Address::new_unique());
+    let mut svm = LiteSVM::new();
+    svm.set_account(first, data_account(owner, vec![1]))
+        .unwrap();
+    svm.set_account(second, data_account(owner, vec![2]))
+        .unwrap();
+    svm.set_account(
+        Address::new_unique(),
+        data_account(Address::new_unique(), vec![3]),
+    )
+    .unwrap();
+    save_to_file(&svm, &path).unwrap();
+
+    let mut lazy = load_from_file_lazy(&path).unwrap();
+    lazy.set_account(second, Account::default()).unwrap();
+
+    let accounts = lazy.get_program_accounts(&owner);
+    assert_eq!(accounts.len(), 1);
+    assert_eq!(accounts[0].0, first);
+    assert_eq!(accounts[0].1.data, vec![1]);
+}
+
+#[test]
+fn lazy_load_falls_back_for_other_formats() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("snapshot.bin");
+    std::fs::write(&path, [0xff]).unwrap();
+    assert!(load_from_file_lazy(&path).is_err());
+
+    let stored = Address::new_unique();
+    let mut svm = LiteSVM::new();
+    svm.set_account(stored, data_account(Address::new_unique(), vec![9]))
+        .unwrap();
+    save_to_file_with(&svm, &path, Default::default()).unwrap();
+    let mut truncated = std::fs::read(&path).unwrap();
+    truncated.truncate(truncated.len() / 2);
+    std::fs::write(&path, truncated).unwrap();
+    assert!(load_from_file_lazy(&path).is_err());
+}
@@ -1,15 +1,14 @@
 use {
     super::{
         get_multisig_signers, get_spl_account,
-        spl_token::{instruction::approve_checked, state::Mint},
-        TOKEN_ID,
+        spl_token_2022::{instruction::approve_checked, state::Mint},
+        token_instruction, TokenInstructions, TOKEN_ID,
     },
     litesvm::{types::FailedTransactionMetadata, LiteSVM},
     smallvec::{smallvec, SmallVec},
     solana_address::Address,
     solana_keypair::Keypair,
     solana_signer::{signers::Signers, Signer},
-    solana_transaction::Transaction,
 };
 
 /// ### Description
@@ -81,8 +80,17 @@ impl<'a> ApproveChecked<'a> {
         self
     }
 
+    /// Returns the instructions and their signers without sending them.
+    pub fn instructions(self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        self.build()
+    }
+
     /// Sends the transaction.
     pub fn send(self) -> Result<(), FailedTransactionMetadata> {
+        self.build()?.send(self.svm, self.payer)
+    }
+
+    fn build(&self) -> Result<TokenInstructions, FailedTransactionMetadata> {
         let payer_pk = self.payer.pubkey();
         let token_program_id = self.token_program_id.unwrap_or(&TOKEN_ID);
 
@@ -101,24 +109,23 @@ impl<'a> ApproveChecked<'a> {
         };
 
         let mint: Mint = get_spl_account(self.svm, self.mint)?;
-        let ix = approve_checked(
-            token_program_id,
-            &source_pk,
-            self.mint,
-            self.delegate,
-            &authority,
-            &signer_keys,
-            self.amount,
-            self.decimals.unwrap_or(mint.decimals),
-        )?;
-
-        let block_hash = self.svm.latest_blockhash();
-        let mut tx = Transaction::new_with_payer(&[ix], Some(&payer_pk));
-        tx.partial_sign(&[self.payer], block_hash);
-        tx.partial_sign(self.signers.as_ref(), block_hash);
-
-        self.svm.send_transaction(tx)?;
+        let ix = token_instruction(token_program_id, |program_id| {
+            approve_checked(
+                program_id,
+                &source_pk,
+                self.mint,
+                self.delegate,
+                &authority,
+                &signer_keys,
+                self.amount,
+                self.decimals.unwrap_or(mint.decimals),
+            )
+        })?;
 
-        Ok(())
+        Ok(TokenInstructions::new(
+            vec![ix],
+            self.payer,
+            self.signers.as_ref(),
+        ))
     }
 }
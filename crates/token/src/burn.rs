@@ -1,11 +1,13 @@
 use {
-    super::{get_multisig_signers, spl_token::instruction::burn, TOKEN_ID},
+    super::{
+        get_multisig_signers, spl_token_2022::instruction::burn, token_instruction,
+        TokenInstructions, TOKEN_ID,
+    },
     litesvm::{types::FailedTransactionMetadata, LiteSVM},
     smallvec::{smallvec, SmallVec},
     solana_address::Address,
     solana_keypair::Keypair,
     solana_signer::{signers::Signers, Signer},
-    solana_transaction::Transaction,
 };
 
 /// ### Description
@@ -66,8 +68,17 @@ impl<'a> Burn<'a> {
         self
     }
 
+    /// Returns the instructions and their signers without sending them.
+    pub fn instructions(self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        self.build()
+    }
+
     /// Sends the transaction.
     pub fn send(self) -> Result<(), FailedTransactionMetadata> {
+        self.build()?.send(self.svm, self.payer)
+    }
+
+    fn build(&self) -> Result<TokenInstructions, FailedTransactionMetadata> {
         let payer_pk = self.payer.pubkey();
         let token_program_id = self.token_program_id.unwrap_or(&TOKEN_ID);
 
@@ -75,22 +86,21 @@ impl<'a> Burn<'a> {
         let signing_keys = self.signers.pubkeys();
         let signer_keys = get_multisig_signers(&authority, &signing_keys);
 
-        let ix = burn(
-            token_program_id,
-            self.account,
-            self.mint,
-            &authority,
-            &signer_keys,
-            self.amount,
-        )?;
-
-        let block_hash = self.svm.latest_blockhash();
-        let mut tx = Transaction::new_with_payer(&[ix], Some(&payer_pk));
-        tx.partial_sign(&[self.payer], block_hash);
-        tx.partial_sign(self.signers.as_ref(), block_hash);
-
-        self.svm.send_transaction(tx)?;
+        let ix = token_instruction(token_program_id, |program_id| {
+            burn(
+                program_id,
+                self.account,
+                self.mint,
+                &authority,
+                &signer_keys,
+                self.amount,
+            )
+        })?;
 
-        Ok(())
+        Ok(TokenInstructions::new(
+            vec![ix],
+            self.payer,
+            self.signers.as_ref(),
+        ))
     }
 }
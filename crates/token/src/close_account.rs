@@ -1,11 +1,13 @@
 use {
-    super::{get_multisig_signers, spl_token::instruction::close_account, TOKEN_ID},
+    super::{
+        get_multisig_signers, spl_token_2022::instruction::close_account, token_instruction,
+        TokenInstructions, TOKEN_ID,
+    },
     litesvm::{types::FailedTransactionMetadata, LiteSVM},
     smallvec::{smallvec, SmallVec},
     solana_address::Address,
     solana_keypair::Keypair,
     solana_signer::{signers::Signers, Signer},
-    solana_transaction::Transaction,
 };
 
 /// ### Description
@@ -63,8 +65,17 @@ impl<'a> CloseAccount<'a> {
         self
     }
 
+    /// Returns the instructions and their signers without sending them.
+    pub fn instructions(self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        self.build()
+    }
+
     /// Sends the transaction.
     pub fn send(self) -> Result<(), FailedTransactionMetadata> {
+        self.build()?.send(self.svm, self.payer)
+    }
+
+    fn build(&self) -> Result<TokenInstructions, FailedTransactionMetadata> {
         let token_program_id = self.token_program_id.unwrap_or(&TOKEN_ID);
         let payer_pk = self.payer.pubkey();
 
@@ -72,21 +83,20 @@ impl<'a> CloseAccount<'a> {
         let signing_keys = self.signers.pubkeys();
         let signer_keys = get_multisig_signers(&authority, &signing_keys);
 
-        let ix = close_account(
-            token_program_id,
-            self.account,
-            self.destination,
-            &authority,
-            &signer_keys,
-        )?;
-
-        let block_hash = self.svm.latest_blockhash();
-        let mut tx = Transaction::new_with_payer(&[ix], Some(&payer_pk));
-        tx.partial_sign(&[self.payer], block_hash);
-        tx.partial_sign(self.signers.as_ref(), block_hash);
-
-        self.svm.send_transaction(tx)?;
+        let ix = token_instruction(token_program_id, |program_id| {
+            close_account(
+                program_id,
+                self.account,
+                self.destination,
+                &authority,
+                &signer_keys,
+            )
+        })?;
 
-        Ok(())
+        Ok(TokenInstructions::new(
+            vec![ix],
+            self.payer,
+            self.signers.as_ref(),
+        ))
     }
 }
@@ -1,18 +1,19 @@
-#[cfg(not(feature = "token-2022"))]
-use solana_program_pack::Pack;
-#[cfg(feature = "token-2022")]
-use spl_token_2022_interface::extension::ExtensionType;
 use {
     super::{
-        spl_token::{instruction::initialize_account3, state::Account},
-        TOKEN_ID,
+        get_account_data,
+        spl_token_2022::{
+            extension::{account_len::try_calculate_account_len_from_mint_data, ExtensionType},
+            instruction::{initialize_account3, initialize_immutable_owner},
+            state::Account,
+        },
+        token_instruction, TokenInstructions, TOKEN_2022_ID, TOKEN_ID,
     },
     litesvm::{types::FailedTransactionMetadata, LiteSVM},
     solana_address::Address,
     solana_keypair::Keypair,
+    solana_program_pack::Pack,
     solana_signer::Signer,
     solana_system_interface::instruction::create_account,
-    solana_transaction::Transaction,
 };
 
 /// ### Description
@@ -21,6 +22,9 @@ use {
 /// ### Optional fields
 /// - `owner`: `payer` by default.
 /// - `account_kp`: [`Keypair::new()`] by default.
+/// - `token_program_id`: [`TOKEN_ID`] by default, or [`TOKEN_2022_ID`] if an
+///   extension is added.
+/// - `extensions`: the extensions required by the mint by default.
 pub struct CreateAccount<'a> {
     svm: &'a mut LiteSVM,
     payer: &'a Keypair,
@@ -28,7 +32,6 @@ pub struct CreateAccount<'a> {
     owner: Option<&'a Address>,
     account_kp: Option<Keypair>,
     token_program_id: Option<&'a Address>,
-    #[cfg(feature = "token-2022")]
     extensions: Vec<ExtensionType>,
 }
 
@@ -42,7 +45,6 @@ impl<'a> CreateAccount<'a> {
             owner: None,
             account_kp: None,
             token_program_id: None,
-            #[cfg(feature = "token-2022")]
             extensions: vec![],
         }
     }
@@ -65,44 +67,78 @@ impl<'a> CreateAccount<'a> {
         self
     }
 
+    /// Adds space for a Token-2022 extension to the spl account.
+    ///
+    /// [`ExtensionType::ImmutableOwner`] is also initialized.
+    pub fn extension(mut self, extension: ExtensionType) -> Self {
+        self.extensions.push(extension);
+        self
+    }
+
+    /// Returns the instructions and their signers without sending them.
+    ///
+    /// The account keypair is one of the signers, set it with
+    /// [`account_kp`](Self::account_kp) to know the account address.
+    pub fn instructions(self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        self.build().map(|(_, instructions)| instructions)
+    }
+
     /// Sends the transaction.
     pub fn send(self) -> Result<Address, FailedTransactionMetadata> {
-        #[cfg(feature = "token-2022")]
-        let account_len = ExtensionType::try_calculate_account_len::<Account>(&self.extensions)?;
-        #[cfg(not(feature = "token-2022"))]
-        let account_len = Account::LEN;
+        let (account_pk, instructions) = self.build()?;
+        instructions.send(self.svm, self.payer)?;
+
+        Ok(account_pk)
+    }
+
+    fn build(&self) -> Result<(Address, TokenInstructions), FailedTransactionMetadata> {
+        let token_program_id = self
+            .token_program_id
+            .unwrap_or(if self.extensions.is_empty() {
+                &TOKEN_ID
+            } else {
+                &TOKEN_2022_ID
+            });
+        let account_len = if *token_program_id == TOKEN_2022_ID {
+            try_calculate_account_len_from_mint_data(
+                &get_account_data(self.svm, self.mint)?,
+                &self.extensions,
+            )?
+        } else {
+            Account::LEN
+        };
 
         let lamports = self.svm.minimum_balance_for_rent_exemption(account_len);
 
-        let account_kp = self.account_kp.unwrap_or(Keypair::new());
+        let account_kp = self
+            .account_kp
+            .as_ref()
+            .map_or_else(Keypair::new, Keypair::insecure_clone);
         let account_pk = account_kp.pubkey();
-        let token_program_id = self.token_program_id.unwrap_or(&TOKEN_ID);
         let payer_pk = self.payer.pubkey();
 
-        let ix1 = create_account(
+        let mut ixs = vec![create_account(
             &payer_pk,
             &account_pk,
             lamports,
             account_len as u64,
             token_program_id,
-        );
-
-        let ix2 = initialize_account3(
-            token_program_id,
-            &account_pk,
-            self.mint,
-            self.owner.unwrap_or(&payer_pk),
-        )?;
-
-        let block_hash = self.svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[ix1, ix2],
-            Some(&payer_pk),
-            &[self.payer, &account_kp],
-            block_hash,
-        );
-        self.svm.send_transaction(tx)?;
+        )];
+        if self.extensions.contains(&ExtensionType::ImmutableOwner) {
+            ixs.push(initialize_immutable_owner(token_program_id, &account_pk)?);
+        }
+        ixs.push(token_instruction(token_program_id, |program_id| {
+            initialize_account3(
+                program_id,
+                &account_pk,
+                self.mint,
+                self.owner.unwrap_or(&payer_pk),
+            )
+        })?);
 
-        Ok(account_pk)
+        Ok((
+            account_pk,
+            TokenInstructions::new(ixs, self.payer, &[&account_kp]),
+        ))
     }
 }
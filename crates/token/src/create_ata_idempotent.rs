@@ -1,10 +1,9 @@
 use {
-    super::TOKEN_ID,
+    super::{TokenInstructions, TOKEN_ID},
     litesvm::{types::FailedTransactionMetadata, LiteSVM},
     solana_address::Address,
     solana_keypair::Keypair,
     solana_signer::Signer,
-    solana_transaction::Transaction,
     spl_associated_token_account_interface::instruction::create_associated_token_account_idempotent,
 };
 
@@ -46,8 +45,20 @@ impl<'a> CreateAssociatedTokenAccountIdempotent<'a> {
         self
     }
 
+    /// Returns the instructions and their signers without sending them.
+    pub fn instructions(self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        self.build().map(|(_, instructions)| instructions)
+    }
+
     /// Sends the transaction.
     pub fn send(self) -> Result<Address, FailedTransactionMetadata> {
+        let (ata, instructions) = self.build()?;
+        instructions.send(self.svm, self.payer)?;
+
+        Ok(ata)
+    }
+
+    fn build(&self) -> Result<(Address, TokenInstructions), FailedTransactionMetadata> {
         let token_program_id = self.token_program_id.unwrap_or(&TOKEN_ID);
         let payer_pk = self.payer.pubkey();
 
@@ -60,18 +71,12 @@ impl<'a> CreateAssociatedTokenAccountIdempotent<'a> {
             token_program_id,
         );
 
-        let block_hash = self.svm.latest_blockhash();
-        let tx =
-            Transaction::new_signed_with_payer(&[ix], Some(&payer_pk), &[self.payer], block_hash);
-
-        self.svm.send_transaction(tx)?;
-
         let ata = spl_associated_token_account_interface::address::get_associated_token_address_with_program_id(
             &authority,
             self.mint,
             token_program_id,
         );
 
-        Ok(ata)
+        Ok((ata, TokenInstructions::new(vec![ix], self.payer, &[])))
     }
 }
@@ -1,18 +1,14 @@
-#[cfg(not(feature = "token-2022"))]
-use solana_program_pack::Pack;
-#[cfg(feature = "token-2022")]
-use spl_token_2022_interface::extension::ExtensionType;
 use {
     super::{
-        spl_token::{instruction::initialize_mint2, state::Mint},
-        TOKEN_ID,
+        spl_token_2022::{extension::ExtensionType, instruction::initialize_mint2, state::Mint},
+        token_instruction, MintExtension, TokenInstructions, TOKEN_2022_ID, TOKEN_ID,
     },
     litesvm::{types::FailedTransactionMetadata, LiteSVM},
     solana_address::Address,
     solana_keypair::Keypair,
+    solana_program_pack::Pack,
     solana_signer::Signer,
     solana_system_interface::instruction::create_account,
-    solana_transaction::Transaction,
 };
 
 /// ### Description
@@ -22,14 +18,19 @@ use {
 /// - `authority`: `payer` by default.
 /// - `freeze_authority`: None by default.
 /// - `decimals`: 8 by default.
-/// - `token_program_id`: [`TOKEN_ID`] by default.
+/// - `mint_kp`: [`Keypair::new()`] by default.
+/// - `token_program_id`: [`TOKEN_ID`] by default, or [`TOKEN_2022_ID`] if an
+///   extension is added.
+/// - `extensions`: none by default.
 pub struct CreateMint<'a> {
     svm: &'a mut LiteSVM,
     payer: &'a Keypair,
     authority: Option<&'a Address>,
     freeze_authority: Option<&'a Address>,
     decimals: Option<u8>,
+    mint_kp: Option<Keypair>,
     token_program_id: Option<&'a Address>,
+    extensions: Vec<MintExtension>,
 }
 
 impl<'a> CreateMint<'a> {
@@ -41,7 +42,9 @@ impl<'a> CreateMint<'a> {
             authority: None,
             freeze_authority: None,
             decimals: None,
+            mint_kp: None,
             token_program_id: None,
+            extensions: vec![],
         }
     }
 
@@ -63,47 +66,89 @@ impl<'a> CreateMint<'a> {
         self
     }
 
+    /// Sets the [`Keypair`] of the mint account.
+    pub fn mint_kp(mut self, mint_kp: Keypair) -> Self {
+        self.mint_kp = Some(mint_kp);
+        self
+    }
+
     /// Sets the token program id of the mint account.
     pub fn token_program_id(mut self, program_id: &'a Address) -> Self {
         self.token_program_id = Some(program_id);
         self
     }
 
+    /// Adds a Token-2022 extension to the mint.
+    pub fn extension(mut self, extension: MintExtension) -> Self {
+        self.extensions.push(extension);
+        self
+    }
+
+    /// Returns the instructions and their signers without sending them.
+    ///
+    /// The mint keypair is one of the signers, set it with
+    /// [`mint_kp`](Self::mint_kp) to know the mint address.
+    pub fn instructions(self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        self.build().map(|(_, instructions)| instructions)
+    }
+
     /// Sends the transaction.
     pub fn send(self) -> Result<Address, FailedTransactionMetadata> {
-        #[cfg(feature = "token-2022")]
-        let mint_size = ExtensionType::try_calculate_account_len::<Mint>(&[])?;
-        #[cfg(not(feature = "token-2022"))]
-        let mint_size = Mint::LEN;
-        let mint_kp = Keypair::new();
+        let (mint_pk, instructions) = self.build()?;
+        instructions.send(self.svm, self.payer)?;
+
+        Ok(mint_pk)
+    }
+
+    fn build(&self) -> Result<(Address, TokenInstructions), FailedTransactionMetadata> {
+        let token_program_id = self
+            .token_program_id
+            .unwrap_or(if self.extensions.is_empty() {
+                &TOKEN_ID
+            } else {
+                &TOKEN_2022_ID
+            });
+        let mint_size = if *token_program_id == TOKEN_2022_ID {
+            ExtensionType::try_calculate_account_len::<Mint>(
+                &self
+                    .extensions
+                    .iter()
+                    .map(MintExtension::extension_type)
+                    .collect::<Vec<_>>(),
+            )?
+        } else {
+            Mint::LEN
+        };
+        let mint_kp = self
+            .mint_kp
+            .as_ref()
+            .map_or_else(Keypair::new, Keypair::insecure_clone);
         let mint_pk = mint_kp.pubkey();
-        let token_program_id = self.token_program_id.unwrap_or(&TOKEN_ID);
         let payer_pk = self.payer.pubkey();
 
-        let ix1 = create_account(
+        let mut ixs = vec![create_account(
             &payer_pk,
             &mint_pk,
             self.svm.minimum_balance_for_rent_exemption(mint_size),
             mint_size as u64,
             token_program_id,
-        );
-        let ix2 = initialize_mint2(
-            token_program_id,
-            &mint_pk,
-            self.authority.unwrap_or(&payer_pk),
-            self.freeze_authority,
-            self.decimals.unwrap_or(8),
-        )?;
-
-        let block_hash = self.svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[ix1, ix2],
-            Some(&payer_pk),
-            &[self.payer, &mint_kp],
-            block_hash,
-        );
-        self.svm.send_transaction(tx)?;
+        )];
+        for extension in &self.extensions {
+            ixs.push(extension.instruction(token_program_id, &mint_pk)?);
+        }
+        ixs.push(token_instruction(token_program_id, |program_id| {
+            initialize_mint2(
+                program_id,
+                &mint_pk,
+                self.authority.unwrap_or(&payer_pk),
+                self.freeze_authority,
+                self.decimals.unwrap_or(8),
+            )
+        })?);
 
-        Ok(mint_pk)
+        Ok((
+            mint_pk,
+            TokenInstructions::new(ixs, self.payer, &[&mint_kp]),
+        ))
     }
 }
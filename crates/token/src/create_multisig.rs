@@ -1,7 +1,7 @@
 use {
     super::{
-        spl_token::{instruction::initialize_multisig2, state::Multisig},
-        TOKEN_ID,
+        spl_token_2022::{instruction::initialize_multisig2, state::Multisig},
+        token_instruction, TokenInstructions, TOKEN_ID,
     },
     litesvm::{types::FailedTransactionMetadata, LiteSVM},
     solana_address::Address,
@@ -9,7 +9,6 @@ use {
     solana_program_pack::Pack,
     solana_signer::Signer,
     solana_system_interface::instruction::create_account,
-    solana_transaction::Transaction,
 };
 
 /// ### Description
@@ -47,11 +46,26 @@ impl<'a> CreateMultisig<'a> {
         self
     }
 
+    /// Returns the instructions and their signers without sending them.
+    pub fn instructions(self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        self.build().map(|(_, instructions)| instructions)
+    }
+
     /// Sends the transaction.
     pub fn send(self) -> Result<Address, FailedTransactionMetadata> {
+        let (multisig_pk, instructions) = self.build()?;
+        instructions.send(self.svm, self.payer)?;
+
+        Ok(multisig_pk)
+    }
+
+    fn build(&self) -> Result<(Address, TokenInstructions), FailedTransactionMetadata> {
         let token_program_id = self.token_program_id.unwrap_or(&TOKEN_ID);
         let multisig_len = Multisig::LEN;
-        let multisig_kp = self.multisig_kp.unwrap_or(Keypair::new());
+        let multisig_kp = self
+            .multisig_kp
+            .as_ref()
+            .map_or_else(Keypair::new, Keypair::insecure_clone);
         let multisig_pk = multisig_kp.pubkey();
 
         let ix1 = create_account(
@@ -61,22 +75,18 @@ impl<'a> CreateMultisig<'a> {
             multisig_len as u64,
             token_program_id,
         );
-        let ix2 = initialize_multisig2(
-            token_program_id,
-            &multisig_pk,
-            self.signers,
-            self.required_signatures,
-        )?;
+        let ix2 = token_instruction(token_program_id, |program_id| {
+            initialize_multisig2(
+                program_id,
+                &multisig_pk,
+                self.signers,
+                self.required_signatures,
+            )
+        })?;
 
-        let block_hash = self.svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[ix1, ix2],
-            Some(&self.payer.pubkey()),
-            &[self.payer, &multisig_kp],
-            block_hash,
-        );
-        self.svm.send_transaction(tx)?;
-
-        Ok(multisig_pk)
+        Ok((
+            multisig_pk,
+            TokenInstructions::new(vec![ix1, ix2], self.payer, &[&multisig_kp]),
+        ))
     }
 }
@@ -1,12 +1,22 @@
 use {
-    litesvm::LiteSVM,
+    super::{
+        spl_token_2022::instruction::create_native_mint as create_native_mint_2022,
+        TokenInstructions, TOKEN_ID,
+    },
+    litesvm::{types::FailedTransactionMetadata, LiteSVM},
     solana_account::Account,
+    solana_address::Address,
+    solana_keypair::Keypair,
+    solana_program_error::ProgramError,
     solana_program_option::COption,
     solana_program_pack::Pack,
     solana_rent::Rent,
+    solana_signer::Signer,
     spl_token_interface::{native_mint::DECIMALS, state::Mint},
 };
 
+/// Writes the SPL Token native mint account directly, since SPL Token has no
+/// instruction creating it.
 pub fn create_native_mint(svm: &mut LiteSVM) {
     let mut data = vec![0; Mint::LEN];
     let mint = Mint {
@@ -28,3 +38,71 @@ pub fn create_native_mint(svm: &mut LiteSVM) {
     svm.set_account(spl_token_interface::native_mint::ID, account)
         .unwrap();
 }
+
+/// ### Description
+/// Builder for the Token-2022 [`create_native_mint`](create_native_mint_2022)
+/// instruction.
+///
+/// ### Optional fields
+/// - `token_program_id`: [`TOKEN_ID`] by default.
+pub struct CreateNativeMint<'a> {
+    svm: &'a mut LiteSVM,
+    payer: &'a Keypair,
+    token_program_id: Option<&'a Address>,
+}
+
+impl<'a> CreateNativeMint<'a> {
+    /// Creates a new instance of [`create_native_mint`](create_native_mint_2022) instruction.
+    pub fn new(svm: &'a mut LiteSVM, payer: &'a Keypair) -> Self {
+        CreateNativeMint {
+            svm,
+            payer,
+            token_program_id: None,
+        }
+    }
+
+    /// Sets the token program id for the instruction.
+    pub fn token_program_id(mut self, program_id: &'a Address) -> Self {
+        self.token_program_id = Some(program_id);
+        self
+    }
+
+    /// Returns the instructions and their signers without sending them.
+    ///
+    /// SPL Token has no instruction creating its native mint, so this fails
+    /// with [`ProgramError::IncorrectProgramId`] for it; use [`Self::send`]
+    /// instead.
+    pub fn instructions(self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        let token_program_id = self.token_program_id.unwrap_or(&TOKEN_ID);
+        if *token_program_id == spl_token_interface::ID {
+            return Err(ProgramError::IncorrectProgramId.into());
+        }
+
+        self.build(token_program_id)
+    }
+
+    /// Sends the transaction.
+    ///
+    /// For SPL Token no transaction is sent and the account is written with
+    /// [`create_native_mint`].
+    pub fn send(self) -> Result<(), FailedTransactionMetadata> {
+        let token_program_id = self.token_program_id.unwrap_or(&TOKEN_ID);
+        if *token_program_id == spl_token_interface::ID {
+            create_native_mint(self.svm);
+            return Ok(());
+        }
+
+        self.build(token_program_id)?.send(self.svm, self.payer)
+    }
+
+    fn build(
+        &self,
+        token_program_id: &Address,
+    ) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        let payer_pk = self.payer.pubkey();
+
+        let ix = create_native_mint_2022(token_program_id, &payer_pk)?;
+
+        Ok(TokenInstructions::new(vec![ix], self.payer, &[]))
+    }
+}
@@ -0,0 +1,189 @@
+use {
+    super::{
+        get_account_data, get_spl_account,
+        spl_token_2022::{
+            self,
+            extension::{
+                account_len::{
+                    try_calculate_account_len_from_mint_data,
+                    try_for_each_required_init_account_extension,
+                },
+                BaseStateWithExtensions, BaseStateWithExtensionsMut, ExtensionType,
+                StateWithExtensions, StateWithExtensionsMut,
+            },
+            state::{Account, AccountState, Mint},
+        },
+        TOKEN_2022_ID,
+    },
+    litesvm::{types::FailedTransactionMetadata, LiteSVM},
+    solana_account::Account as SolanaAccount,
+    solana_address::Address,
+    solana_program_option::COption,
+    solana_program_pack::Pack,
+    spl_associated_token_account_interface::address::get_associated_token_address_with_program_id,
+};
+
+/// Writes `mint` at `address` for `token_program_id` without sending a
+/// transaction, so no mint authority keypair is needed.
+///
+/// If a mint of `token_program_id` already exists at `address`, only its base
+/// state is replaced and its extensions are kept.
+pub fn set_mint(
+    svm: &mut LiteSVM,
+    address: &Address,
+    mint: Mint,
+    token_program_id: &Address,
+) -> Result<(), FailedTransactionMetadata> {
+    spl_token_2022::check_spl_token_program_account(token_program_id)?;
+    let mut data = get_existing_data::<Mint>(svm, address, token_program_id)
+        .unwrap_or_else(|| vec![0; Mint::LEN]);
+    Mint::pack_into_slice(&mint, &mut data[..Mint::LEN]);
+    let lamports = svm.minimum_balance_for_rent_exemption(data.len());
+
+    write_account(svm, address, data, lamports, token_program_id);
+
+    Ok(())
+}
+
+/// Writes `account` at `address` for `token_program_id` without sending a
+/// transaction.
+///
+/// If a token account of `token_program_id` already exists at `address`, only
+/// its base state is replaced and its extensions are kept. A new Token-2022
+/// account gets the extensions required by its mint, plus the immutable owner
+/// extension when `address` is the associated token account of the owner.
+///
+/// The rent-exempt reserve of a native account is set from the account size.
+pub fn set_token_account(
+    svm: &mut LiteSVM,
+    address: &Address,
+    mut account: Account,
+    token_program_id: &Address,
+) -> Result<(), FailedTransactionMetadata> {
+    spl_token_2022::check_spl_token_program_account(token_program_id)?;
+    let mut data = match get_existing_data::<Account>(svm, address, token_program_id) {
+        Some(data) => data,
+        None => new_token_account_data(svm, address, &account, token_program_id)?,
+    };
+    let rent = svm.minimum_balance_for_rent_exemption(data.len());
+    if account.is_native.is_some() {
+        account.is_native = COption::Some(rent);
+    }
+    Account::pack_into_slice(&account, &mut data[..Account::LEN]);
+    let lamports = rent + account.is_native.map_or(0, |_| account.amount);
+
+    write_account(svm, address, data, lamports, token_program_id);
+
+    Ok(())
+}
+
+/// Sets the balance of the associated token account of `owner` to `amount`
+/// without sending a transaction, and returns its address.
+///
+/// The token program is the owner of the mint account. The associated token
+/// account is created if needed, and the mint supply is adjusted by the
+/// difference in balance, except for the native mint.
+pub fn deal(
+    svm: &mut LiteSVM,
+    mint: &Address,
+    owner: &Address,
+    amount: u64,
+) -> Result<Address, FailedTransactionMetadata> {
+    let mut mint_state: Mint = get_spl_account(svm, mint)?;
+    let token_program_id = svm.get_account(mint).unwrap().owner;
+    let is_native =
+        *mint == spl_token_interface::native_mint::ID || *mint == spl_token_2022::native_mint::ID;
+
+    let ata = get_associated_token_address_with_program_id(owner, mint, &token_program_id);
+    let previous = get_existing_data::<Account>(svm, &ata, &token_program_id)
+        .and_then(|data| Account::unpack(&data[..Account::LEN]).ok());
+    let previous_amount = previous.map_or(0, |account| account.amount);
+    let account = Account {
+        amount,
+        ..previous.unwrap_or(Account {
+            mint: *mint,
+            owner: *owner,
+            state: AccountState::Initialized,
+            is_native: if is_native {
+                COption::Some(0)
+            } else {
+                COption::None
+            },
+            ..Account::default()
+        })
+    };
+    set_token_account(svm, &ata, account, &token_program_id)?;
+
+    if !is_native {
+        mint_state.supply = mint_state
+            .supply
+            .saturating_sub(previous_amount)
+            .saturating_add(amount);
+        set_mint(svm, mint, mint_state, &token_program_id)?;
+    }
+
+    Ok(ata)
+}
+
+fn get_existing_data<S: Pack>(
+    svm: &LiteSVM,
+    address: &Address,
+    token_program_id: &Address,
+) -> Option<Vec<u8>> {
+    svm.get_account(address)
+        .filter(|account| account.owner == *token_program_id && account.data.len() >= S::LEN)
+        .map(|account| account.data)
+}
+
+fn new_token_account_data(
+    svm: &LiteSVM,
+    address: &Address,
+    account: &Account,
+    token_program_id: &Address,
+) -> Result<Vec<u8>, FailedTransactionMetadata> {
+    if *token_program_id != TOKEN_2022_ID {
+        return Ok(vec![0; Account::LEN]);
+    }
+
+    let mint_data = get_account_data(svm, &account.mint)?;
+    let ata = get_associated_token_address_with_program_id(
+        &account.owner,
+        &account.mint,
+        token_program_id,
+    );
+    let extensions: &[ExtensionType] = if *address == ata {
+        &[ExtensionType::ImmutableOwner]
+    } else {
+        &[]
+    };
+
+    let mut data = vec![0; try_calculate_account_len_from_mint_data(&mint_data, extensions)?];
+    let mut state = StateWithExtensionsMut::<Account>::unpack_uninitialized(&mut data)?;
+    for extension_type in extensions {
+        state.init_account_extension_from_type(*extension_type)?;
+    }
+    let mint = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+    try_for_each_required_init_account_extension(mint.get_tlv_data(), |extension_type| {
+        state.init_account_extension_from_type(extension_type)
+    })?;
+    state.init_account_type()?;
+
+    Ok(data)
+}
+
+fn write_account(
+    svm: &mut LiteSVM,
+    address: &Address,
+    data: Vec<u8>,
+    lamports: u64,
+    token_program_id: &Address,
+) {
+    let account = SolanaAccount {
+        lamports,
+        data,
+        owner: *token_program_id,
+        executable: false,
+        rent_epoch: 0,
+    };
+    svm.set_account(*address, account).unwrap();
+}
@@ -0,0 +1,181 @@
+use {
+    crate::{
+        get_multisig_signers,
+        spl_token_2022::{
+            extension::{
+                cpi_guard::instruction::{disable_cpi_guard, enable_cpi_guard},
+                ExtensionType,
+            },
+            instruction::reallocate,
+        },
+        TokenInstructions, TOKEN_2022_ID,
+    },
+    litesvm::{types::FailedTransactionMetadata, LiteSVM},
+    smallvec::{smallvec, SmallVec},
+    solana_address::Address,
+    solana_keypair::Keypair,
+    solana_signer::{signers::Signers, Signer},
+};
+
+/// ### Description
+/// Builder for the [`enable_cpi_guard`] instruction.
+///
+/// The account is reallocated to fit the CPI guard extension first, so associated
+/// token accounts can be used directly.
+///
+/// ### Optional fields
+/// - `owner`: `payer` by default.
+/// - `token_program_id`: [`TOKEN_2022_ID`] by default.
+pub struct EnableCpiGuard<'a> {
+    svm: &'a mut LiteSVM,
+    payer: &'a Keypair,
+    account: &'a Address,
+    token_program_id: Option<&'a Address>,
+    signers: SmallVec<[&'a Keypair; 1]>,
+    owner: Option<Address>,
+}
+
+impl<'a> EnableCpiGuard<'a> {
+    /// Creates a new instance of [`enable_cpi_guard`] instruction.
+    pub fn new(svm: &'a mut LiteSVM, payer: &'a Keypair, account: &'a Address) -> Self {
+        EnableCpiGuard {
+            svm,
+            payer,
+            account,
+            token_program_id: None,
+            owner: None,
+            signers: smallvec![payer],
+        }
+    }
+
+    /// Sets the token program id for the instruction.
+    pub fn token_program_id(mut self, program_id: &'a Address) -> Self {
+        self.token_program_id = Some(program_id);
+        self
+    }
+
+    /// Sets the owner of the account with single owner.
+    pub fn owner(mut self, owner: &'a Keypair) -> Self {
+        self.owner = Some(owner.pubkey());
+        self.signers = smallvec![owner];
+        self
+    }
+
+    /// Sets the owner of the account with multisig owner.
+    pub fn multisig(mut self, multisig: &'a Address, signers: &'a [&'a Keypair]) -> Self {
+        self.owner = Some(*multisig);
+        self.signers = SmallVec::from(signers);
+        self
+    }
+
+    /// Returns the instructions and their signers without sending them.
+    pub fn instructions(self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        self.build()
+    }
+
+    /// Sends the transaction.
+    pub fn send(self) -> Result<(), FailedTransactionMetadata> {
+        self.build()?.send(self.svm, self.payer)
+    }
+
+    fn build(&self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        let payer_pk = self.payer.pubkey();
+        let token_program_id = self.token_program_id.unwrap_or(&TOKEN_2022_ID);
+
+        let authority = self.owner.unwrap_or(payer_pk);
+        let signing_keys = self.signers.pubkeys();
+        let signer_keys = get_multisig_signers(&authority, &signing_keys);
+
+        let ix1 = reallocate(
+            token_program_id,
+            self.account,
+            &payer_pk,
+            &authority,
+            &signer_keys,
+            &[ExtensionType::CpiGuard],
+        )?;
+        let ix2 = enable_cpi_guard(token_program_id, self.account, &authority, &signer_keys)?;
+
+        Ok(TokenInstructions::new(
+            vec![ix1, ix2],
+            self.payer,
+            self.signers.as_ref(),
+        ))
+    }
+}
+
+/// ### Description
+/// Builder for the [`disable_cpi_guard`] instruction.
+///
+/// ### Optional fields
+/// - `owner`: `payer` by default.
+/// - `token_program_id`: [`TOKEN_2022_ID`] by default.
+pub struct DisableCpiGuard<'a> {
+    svm: &'a mut LiteSVM,
+    payer: &'a Keypair,
+    account: &'a Address,
+    token_program_id: Option<&'a Address>,
+    signers: SmallVec<[&'a Keypair; 1]>,
+    owner: Option<Address>,
+}
+
+impl<'a> DisableCpiGuard<'a> {
+    /// Creates a new instance of [`disable_cpi_guard`] instruction.
+    pub fn new(svm: &'a mut LiteSVM, payer: &'a Keypair, account: &'a Address) -> Self {
+        DisableCpiGuard {
+            svm,
+            payer,
+            account,
+            token_program_id: None,
+            owner: None,
+            signers: smallvec![payer],
+        }
+    }
+
+    /// Sets the token program id for the instruction.
+    pub fn token_program_id(mut self, program_id: &'a Address) -> Self {
+        self.token_program_id = Some(program_id);
+        self
+    }
+
+    /// Sets the owner of the account with single owner.
+    pub fn owner(mut self, owner: &'a Keypair) -> Self {
+        self.owner = Some(owner.pubkey());
+        self.signers = smallvec![owner];
+        self
+    }
+
+    /// Sets the owner of the account with multisig owner.
+    pub fn multisig(mut self, multisig: &'a Address, signers: &'a [&'a Keypair]) -> Self {
+        self.owner = Some(*multisig);
+        self.signers = SmallVec::from(signers);
+        self
+    }
+
+    /// Returns the instructions and their signers without sending them.
+    pub fn instructions(self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        self.build()
+    }
+
+    /// Sends the transaction.
+    pub fn send(self) -> Result<(), FailedTransactionMetadata> {
+        self.build()?.send(self.svm, self.payer)
+    }
+
+    fn build(&self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        let payer_pk = self.payer.pubkey();
+        let token_program_id = self.token_program_id.unwrap_or(&TOKEN_2022_ID);
+
+        let authority = self.owner.unwrap_or(payer_pk);
+        let signing_keys = self.signers.pubkeys();
+        let signer_keys = get_multisig_signers(&authority, &signing_keys);
+
+        let ix = disable_cpi_guard(token_program_id, self.account, &authority, &signer_keys)?;
+
+        Ok(TokenInstructions::new(
+            vec![ix],
+            self.payer,
+            self.signers.as_ref(),
+        ))
+    }
+}
@@ -0,0 +1,96 @@
+use {
+    crate::{
+        get_multisig_signers,
+        spl_token_2022::extension::interest_bearing_mint::instruction::update_rate,
+        TokenInstructions, TOKEN_2022_ID,
+    },
+    litesvm::{types::FailedTransactionMetadata, LiteSVM},
+    smallvec::{smallvec, SmallVec},
+    solana_address::Address,
+    solana_keypair::Keypair,
+    solana_signer::{signers::Signers, Signer},
+};
+
+/// ### Description
+/// Builder for the [`update_rate`] instruction.
+///
+/// ### Optional fields
+/// - `authority`: `payer` by default.
+/// - `token_program_id`: [`TOKEN_2022_ID`] by default.
+pub struct UpdateInterestRate<'a> {
+    svm: &'a mut LiteSVM,
+    payer: &'a Keypair,
+    mint: &'a Address,
+    rate: i16,
+    token_program_id: Option<&'a Address>,
+    signers: SmallVec<[&'a Keypair; 1]>,
+    authority: Option<Address>,
+}
+
+impl<'a> UpdateInterestRate<'a> {
+    /// Creates a new instance of [`update_rate`] instruction.
+    pub fn new(svm: &'a mut LiteSVM, payer: &'a Keypair, mint: &'a Address, rate: i16) -> Self {
+        UpdateInterestRate {
+            svm,
+            payer,
+            mint,
+            rate,
+            token_program_id: None,
+            authority: None,
+            signers: smallvec![payer],
+        }
+    }
+
+    /// Sets the token program id for the instruction.
+    pub fn token_program_id(mut self, program_id: &'a Address) -> Self {
+        self.token_program_id = Some(program_id);
+        self
+    }
+
+    /// Sets the rate authority with single owner.
+    pub fn authority(mut self, authority: &'a Keypair) -> Self {
+        self.authority = Some(authority.pubkey());
+        self.signers = smallvec![authority];
+        self
+    }
+
+    /// Sets the rate authority with multisig owner.
+    pub fn multisig(mut self, multisig: &'a Address, signers: &'a [&'a Keypair]) -> Self {
+        self.authority = Some(*multisig);
+        self.signers = SmallVec::from(signers);
+        self
+    }
+
+    /// Returns the instructions and their signers without sending them.
+    pub fn instructions(self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        self.build()
+    }
+
+    /// Sends the transaction.
+    pub fn send(self) -> Result<(), FailedTransactionMetadata> {
+        self.build()?.send(self.svm, self.payer)
+    }
+
+    fn build(&self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        let payer_pk = self.payer.pubkey();
+        let token_program_id = self.token_program_id.unwrap_or(&TOKEN_2022_ID);
+
+        let authority = self.authority.unwrap_or(payer_pk);
+        let signing_keys = self.signers.pubkeys();
+        let signer_keys = get_multisig_signers(&authority, &signing_keys);
+
+        let ix = update_rate(
+            token_program_id,
+            self.mint,
+            &authority,
+            &signer_keys,
+            self.rate,
+        )?;
+
+        Ok(TokenInstructions::new(
+            vec![ix],
+            self.payer,
+            self.signers.as_ref(),
+        ))
+    }
+}
@@ -0,0 +1,193 @@
+use {
+    crate::{
+        get_multisig_signers,
+        spl_token_2022::{
+            extension::{
+                memo_transfer::instruction::{
+                    disable_required_transfer_memos, enable_required_transfer_memos,
+                },
+                ExtensionType,
+            },
+            instruction::reallocate,
+        },
+        TokenInstructions, TOKEN_2022_ID,
+    },
+    litesvm::{types::FailedTransactionMetadata, LiteSVM},
+    smallvec::{smallvec, SmallVec},
+    solana_address::Address,
+    solana_keypair::Keypair,
+    solana_signer::{signers::Signers, Signer},
+};
+
+/// ### Description
+/// Builder for the [`enable_required_transfer_memos`] instruction.
+///
+/// The account is reallocated to fit the memo transfer extension first, so associated
+/// token accounts can be used directly.
+///
+/// ### Optional fields
+/// - `owner`: `payer` by default.
+/// - `token_program_id`: [`TOKEN_2022_ID`] by default.
+pub struct EnableRequiredTransferMemos<'a> {
+    svm: &'a mut LiteSVM,
+    payer: &'a Keypair,
+    account: &'a Address,
+    token_program_id: Option<&'a Address>,
+    signers: SmallVec<[&'a Keypair; 1]>,
+    owner: Option<Address>,
+}
+
+impl<'a> EnableRequiredTransferMemos<'a> {
+    /// Creates a new instance of [`enable_required_transfer_memos`] instruction.
+    pub fn new(svm: &'a mut LiteSVM, payer: &'a Keypair, account: &'a Address) -> Self {
+        EnableRequiredTransferMemos {
+            svm,
+            payer,
+            account,
+            token_program_id: None,
+            owner: None,
+            signers: smallvec![payer],
+        }
+    }
+
+    /// Sets the token program id for the instruction.
+    pub fn token_program_id(mut self, program_id: &'a Address) -> Self {
+        self.token_program_id = Some(program_id);
+        self
+    }
+
+    /// Sets the owner of the account with single owner.
+    pub fn owner(mut self, owner: &'a Keypair) -> Self {
+        self.owner = Some(owner.pubkey());
+        self.signers = smallvec![owner];
+        self
+    }
+
+    /// Sets the owner of the account with multisig owner.
+    pub fn multisig(mut self, multisig: &'a Address, signers: &'a [&'a Keypair]) -> Self {
+        self.owner = Some(*multisig);
+        self.signers = SmallVec::from(signers);
+        self
+    }
+
+    /// Returns the instructions and their signers without sending them.
+    pub fn instructions(self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        self.build()
+    }
+
+    /// Sends the transaction.
+    pub fn send(self) -> Result<(), FailedTransactionMetadata> {
+        self.build()?.send(self.svm, self.payer)
+    }
+
+    fn build(&self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        let payer_pk = self.payer.pubkey();
+        let token_program_id = self.token_program_id.unwrap_or(&TOKEN_2022_ID);
+
+        let authority = self.owner.unwrap_or(payer_pk);
+        let signing_keys = self.signers.pubkeys();
+        let signer_keys = get_multisig_signers(&authority, &signing_keys);
+
+        let ix1 = reallocate(
+            token_program_id,
+            self.account,
+            &payer_pk,
+            &authority,
+            &signer_keys,
+            &[ExtensionType::MemoTransfer],
+        )?;
+        let ix2 = enable_required_transfer_memos(
+            token_program_id,
+            self.account,
+            &authority,
+            &signer_keys,
+        )?;
+
+        Ok(TokenInstructions::new(
+            vec![ix1, ix2],
+            self.payer,
+            self.signers.as_ref(),
+        ))
+    }
+}
+
+/// ### Description
+/// Builder for the [`disable_required_transfer_memos`] instruction.
+///
+/// ### Optional fields
+/// - `owner`: `payer` by default.
+/// - `token_program_id`: [`TOKEN_2022_ID`] by default.
+pub struct DisableRequiredTransferMemos<'a> {
+    svm: &'a mut LiteSVM,
+    payer: &'a Keypair,
+    account: &'a Address,
+    token_program_id: Option<&'a Address>,
+    signers: SmallVec<[&'a Keypair; 1]>,
+    owner: Option<Address>,
+}
+
+impl<'a> DisableRequiredTransferMemos<'a> {
+    /// Creates a new instance of [`disable_required_transfer_memos`] instruction.
+    pub fn new(svm: &'a mut LiteSVM, payer: &'a Keypair, account: &'a Address) -> Self {
+        DisableRequiredTransferMemos {
+            svm,
+            payer,
+            account,
+            token_program_id: None,
+            owner: None,
+            signers: smallvec![payer],
+        }
+    }
+
+    /// Sets the token program id for the instruction.
+    pub fn token_program_id(mut self, program_id: &'a Address) -> Self {
+        self.token_program_id = Some(program_id);
+        self
+    }
+
+    /// Sets the owner of the account with single owner.
+    pub fn owner(mut self, owner: &'a Keypair) -> Self {
+        self.owner = Some(owner.pubkey());
+        self.signers = smallvec![owner];
+        self
+    }
+
+    /// Sets the owner of the account with multisig owner.
+    pub fn multisig(mut self, multisig: &'a Address, signers: &'a [&'a Keypair]) -> Self {
+        self.owner = Some(*multisig);
+        self.signers = SmallVec::from(signers);
+        self
+    }
+
+    /// Returns the instructions and their signers without sending them.
+    pub fn instructions(self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        self.build()
+    }
+
+    /// Sends the transaction.
+    pub fn send(self) -> Result<(), FailedTransactionMetadata> {
+        self.build()?.send(self.svm, self.payer)
+    }
+
+    fn build(&self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        let payer_pk = self.payer.pubkey();
+        let token_program_id = self.token_program_id.unwrap_or(&TOKEN_2022_ID);
+
+        let authority = self.owner.unwrap_or(payer_pk);
+        let signing_keys = self.signers.pubkeys();
+        let signer_keys = get_multisig_signers(&authority, &signing_keys);
+
+        let ix = disable_required_transfer_memos(
+            token_program_id,
+            self.account,
+            &authority,
+            &signer_keys,
+        )?;
+
+        Ok(TokenInstructions::new(
+            vec![ix],
+            self.payer,
+            self.signers.as_ref(),
+        ))
+    }
+}
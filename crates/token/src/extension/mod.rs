@@ -0,0 +1,136 @@
+mod cpi_guard;
+mod default_account_state;
+mod interest_bearing_mint;
+mod memo_transfer;
+mod metadata_pointer;
+mod state;
+mod token_metadata;
+mod transfer_fee;
+mod transfer_hook;
+
+use {
+    super::spl_token_2022::{
+        extension::{
+            default_account_state::instruction::initialize_default_account_state,
+            interest_bearing_mint::instruction::initialize as initialize_interest_bearing_mint,
+            metadata_pointer::instruction::initialize as initialize_metadata_pointer,
+            transfer_fee::instruction::initialize_transfer_fee_config,
+            transfer_hook::instruction::initialize as initialize_transfer_hook, ExtensionType,
+        },
+        instruction::{
+            initialize_mint_close_authority, initialize_non_transferable_mint,
+            initialize_permanent_delegate,
+        },
+        state::AccountState,
+    },
+    solana_address::Address,
+    solana_instruction::Instruction,
+    solana_program_error::ProgramError,
+};
+pub use {
+    cpi_guard::*, default_account_state::*, interest_bearing_mint::*, memo_transfer::*,
+    metadata_pointer::*, state::*, token_metadata::*, transfer_fee::*, transfer_hook::*,
+};
+
+/// ### Description
+/// A Token-2022 mint extension initialized by [`CreateMint`](crate::CreateMint)
+/// before the mint itself.
+///
+/// Token metadata is variable-length and needs the mint authority's signature,
+/// so it is added after creation with [`InitializeTokenMetadata`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum MintExtension {
+    /// Charges a fee on every transfer, withheld in the destination account.
+    TransferFeeConfig {
+        transfer_fee_config_authority: Option<Address>,
+        withdraw_withheld_authority: Option<Address>,
+        transfer_fee_basis_points: u16,
+        maximum_fee: u64,
+    },
+    /// Accrues interest on the UI amount of the mint.
+    InterestBearingConfig {
+        rate_authority: Option<Address>,
+        rate: i16,
+    },
+    /// Lets `delegate` transfer or burn tokens from any account of the mint.
+    PermanentDelegate { delegate: Address },
+    /// State given to every new token account of the mint.
+    DefaultAccountState { state: AccountState },
+    /// Points to the account holding the mint's metadata.
+    MetadataPointer {
+        authority: Option<Address>,
+        metadata_address: Option<Address>,
+    },
+    /// Lets `close_authority` close the mint once its supply is zero.
+    MintCloseAuthority { close_authority: Option<Address> },
+    /// Makes the tokens of the mint non-transferable.
+    NonTransferable,
+    /// Invokes `program_id` on every transfer of the mint.
+    TransferHook {
+        authority: Option<Address>,
+        program_id: Option<Address>,
+    },
+}
+
+impl MintExtension {
+    /// Returns the [`ExtensionType`] of the extension.
+    pub fn extension_type(&self) -> ExtensionType {
+        match self {
+            MintExtension::TransferFeeConfig { .. } => ExtensionType::TransferFeeConfig,
+            MintExtension::InterestBearingConfig { .. } => ExtensionType::InterestBearingConfig,
+            MintExtension::PermanentDelegate { .. } => ExtensionType::PermanentDelegate,
+            MintExtension::DefaultAccountState { .. } => ExtensionType::DefaultAccountState,
+            MintExtension::MetadataPointer { .. } => ExtensionType::MetadataPointer,
+            MintExtension::MintCloseAuthority { .. } => ExtensionType::MintCloseAuthority,
+            MintExtension::NonTransferable => ExtensionType::NonTransferable,
+            MintExtension::TransferHook { .. } => ExtensionType::TransferHook,
+        }
+    }
+
+    /// Returns the instruction initializing the extension on `mint`.
+    pub fn instruction(
+        &self,
+        token_program_id: &Address,
+        mint: &Address,
+    ) -> Result<Instruction, ProgramError> {
+        match self {
+            MintExtension::TransferFeeConfig {
+                transfer_fee_config_authority,
+                withdraw_withheld_authority,
+                transfer_fee_basis_points,
+                maximum_fee,
+            } => initialize_transfer_fee_config(
+                token_program_id,
+                mint,
+                transfer_fee_config_authority.as_ref(),
+                withdraw_withheld_authority.as_ref(),
+                *transfer_fee_basis_points,
+                *maximum_fee,
+            ),
+            MintExtension::InterestBearingConfig {
+                rate_authority,
+                rate,
+            } => initialize_interest_bearing_mint(token_program_id, mint, *rate_authority, *rate),
+            MintExtension::PermanentDelegate { delegate } => {
+                initialize_permanent_delegate(token_program_id, mint, delegate)
+            }
+            MintExtension::DefaultAccountState { state } => {
+                initialize_default_account_state(token_program_id, mint, state)
+            }
+            MintExtension::MetadataPointer {
+                authority,
+                metadata_address,
+            } => initialize_metadata_pointer(token_program_id, mint, *authority, *metadata_address),
+            MintExtension::MintCloseAuthority { close_authority } => {
+                initialize_mint_close_authority(token_program_id, mint, close_authority.as_ref())
+            }
+            MintExtension::NonTransferable => {
+                initialize_non_transferable_mint(token_program_id, mint)
+            }
+            MintExtension::TransferHook {
+                authority,
+                program_id,
+            } => initialize_transfer_hook(token_program_id, mint, *authority, *program_id),
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use {
+    crate::{
+        get_account_data,
+        spl_token_2022::{
+            extension::{
+                cpi_guard::CpiGuard,
+                default_account_state::DefaultAccountState,
+                group_member_pointer::GroupMemberPointer,
+                group_pointer::GroupPointer,
+                immutable_owner::ImmutableOwner,
+                interest_bearing_mint::InterestBearingConfig,
+                memo_transfer::MemoTransfer,
+                metadata_pointer::MetadataPointer,
+                mint_close_authority::MintCloseAuthority,
+                non_transferable::{NonTransferable, NonTransferableAccount},
+                pausable::{PausableAccount, PausableConfig},
+                permanent_delegate::PermanentDelegate,
+                scaled_ui_amount::ScaledUiAmountConfig,
+                transfer_fee::{TransferFeeAmount, TransferFeeConfig},
+                transfer_hook::{TransferHook, TransferHookAccount},
+                BaseState, BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+            },
+            state::{Account, Mint},
+        },
+    },
+    litesvm::{types::FailedTransactionMetadata, LiteSVM},
+    solana_address::Address,
+    solana_program_error::ProgramError,
+    solana_program_pack::Pack,
+    spl_token_metadata_interface::state::TokenMetadata,
+};
+
+/// A parsed Token-2022 extension of a mint or token account.
+///
+/// Extensions without a dedicated variant, such as the confidential ones, are
+/// reported as [`ExtensionState::Other`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExtensionState {
+    TransferFeeConfig(TransferFeeConfig),
+    TransferFeeAmount(TransferFeeAmount),
+    MintCloseAuthority(MintCloseAuthority),
+    DefaultAccountState(DefaultAccountState),
+    ImmutableOwner(ImmutableOwner),
+    MemoTransfer(MemoTransfer),
+    NonTransferable(NonTransferable),
+    NonTransferableAccount(NonTransferableAccount),
+    InterestBearingConfig(InterestBearingConfig),
+    CpiGuard(CpiGuard),
+    PermanentDelegate(PermanentDelegate),
+    TransferHook(TransferHook),
+    TransferHookAccount(TransferHookAccount),
+    MetadataPointer(MetadataPointer),
+    TokenMetadata(TokenMetadata),
+    GroupPointer(GroupPointer),
+    GroupMemberPointer(GroupMemberPointer),
+    ScaledUiAmount(ScaledUiAmountConfig),
+    Pausable(PausableConfig),
+    PausableAccount(PausableAccount),
+    Other(ExtensionType),
+}
+
+/// The base state of a mint or token account with its parsed extensions, in
+/// account order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WithExtensions<S> {
+    pub base: S,
+    pub extensions: Vec<ExtensionState>,
+}
+
+impl<S> WithExtensions<S> {
+    /// Returns the types of the extensions.
+    pub fn extension_types(&self) -> Vec<ExtensionType> {
+        self.extensions
+            .iter()
+            .map(|extension| match extension {
+                ExtensionState::TransferFeeConfig(_) => ExtensionType::TransferFeeConfig,
+                ExtensionState::TransferFeeAmount(_) => ExtensionType::TransferFeeAmount,
+                ExtensionState::MintCloseAuthority(_) => ExtensionType::MintCloseAuthority,
+                ExtensionState::DefaultAccountState(_) => ExtensionType::DefaultAccountState,
+                ExtensionState::ImmutableOwner(_) => ExtensionType::ImmutableOwner,
+                ExtensionState::MemoTransfer(_) => ExtensionType::MemoTransfer,
+                ExtensionState::NonTransferable(_) => ExtensionType::NonTransferable,
+                ExtensionState::NonTransferableAccount(_) => ExtensionType::NonTransferableAccount,
+                ExtensionState::InterestBearingConfig(_) => ExtensionType::InterestBearingConfig,
+                ExtensionState::CpiGuard(_) => ExtensionType::CpiGuard,
+                ExtensionState::PermanentDelegate(_) => ExtensionType::PermanentDelegate,
+                ExtensionState::TransferHook(_) => ExtensionType::TransferHook,
+                ExtensionState::TransferHookAccount(_) => ExtensionType::TransferHookAccount,
+                ExtensionState::MetadataPointer(_) => ExtensionType::MetadataPointer,
+                ExtensionState::TokenMetadata(_) => ExtensionType::TokenMetadata,
+                ExtensionState::GroupPointer(_) => ExtensionType::GroupPointer,
+                ExtensionState::GroupMemberPointer(_) => ExtensionType::GroupMemberPointer,
+                ExtensionState::ScaledUiAmount(_) => ExtensionType::ScaledUiAmount,
+                ExtensionState::Pausable(_) => ExtensionType::Pausable,
+                ExtensionState::PausableAccount(_) => ExtensionType::PausableAccount,
+                ExtensionState::Other(extension_type) => *extension_type,
+            })
+            .collect()
+    }
+}
+
+/// Reads a mint of either token program with its Token-2022 extensions.
+pub fn get_mint_with_extensions(
+    svm: &LiteSVM,
+    mint: &Address,
+) -> Result<WithExtensions<Mint>, FailedTransactionMetadata> {
+    get_with_extensions(svm, mint)
+}
+
+/// Reads a token account of either token program with its Token-2022 extensions.
+pub fn get_token_account_with_extensions(
+    svm: &LiteSVM,
+    account: &Address,
+) -> Result<WithExtensions<Account>, FailedTransactionMetadata> {
+    get_with_extensions(svm, account)
+}
+
+fn get_with_extensions<S: BaseState + Pack>(
+    svm: &LiteSVM,
+    address: &Address,
+) -> Result<WithExtensions<S>, FailedTransactionMetadata> {
+    let data = get_account_data(svm, address)?;
+    let state = StateWithExtensions::<S>::unpack(&data)?;
+    let extensions = state
+        .get_extension_types()?
+        .into_iter()
+        .map(|extension_type| parse_extension(&state, extension_type))
+        .collect::<Result<_, _>>()?;
+
+    Ok(WithExtensions {
+        base: state.base,
+        extensions,
+    })
+}
+
+fn parse_extension<S: BaseState + Pack>(
+    state: &StateWithExtensions<S>,
+    extension_type: ExtensionType,
+) -> Result<ExtensionState, ProgramError> {
+    Ok(match extension_type {
+        ExtensionType::TransferFeeConfig => {
+            ExtensionState::TransferFeeConfig(*state.get_extension()?)
+        }
+        ExtensionType::TransferFeeAmount => {
+            ExtensionState::TransferFeeAmount(*state.get_extension()?)
+        }
+        ExtensionType::MintCloseAuthority => {
+            ExtensionState::MintCloseAuthority(*state.get_extension()?)
+        }
+        ExtensionType::DefaultAccountState => {
+            ExtensionState::DefaultAccountState(*state.get_extension()?)
+        }
+        ExtensionType::ImmutableOwner => ExtensionState::ImmutableOwner(*state.get_extension()?),
+        ExtensionType::MemoTransfer => ExtensionState::MemoTransfer(*state.get_extension()?),
+        ExtensionType::NonTransferable => ExtensionState::NonTransferable(*state.get_extension()?),
+        ExtensionType::NonTransferableAccount => {
+            ExtensionState::NonTransferableAccount(*state.get_extension()?)
+        }
+        ExtensionType::InterestBearingConfig => {
+            ExtensionState::InterestBearingConfig(*state.get_extension()?)
+        }
+        ExtensionType::CpiGuard => ExtensionState::CpiGuard(*state.get_extension()?),
+        ExtensionType::PermanentDelegate => {
+            ExtensionState::PermanentDelegate(*state.get_extension()?)
+        }
+        ExtensionType::TransferHook => ExtensionState::TransferHook(*state.get_extension()?),
+        ExtensionType::TransferHookAccount => {
+            ExtensionState::TransferHookAccount(*state.get_extension()?)
+        }
+        ExtensionType::MetadataPointer => ExtensionState::MetadataPointer(*state.get_extension()?),
+        ExtensionType::TokenMetadata => {
+            ExtensionState::TokenMetadata(state.get_variable_len_extension::<TokenMetadata>()?)
+        }
+        ExtensionType::GroupPointer => ExtensionState::GroupPointer(*state.get_extension()?),
+        ExtensionType::GroupMemberPointer => {
+            ExtensionState::GroupMemberPointer(*state.get_extension()?)
+        }
+        ExtensionType::ScaledUiAmount => ExtensionState::ScaledUiAmount(*state.get_extension()?),
+        ExtensionType::Pausable => ExtensionState::Pausable(*state.get_extension()?),
+        ExtensionType::PausableAccount => ExtensionState::PausableAccount(*state.get_extension()?),
+        extension_type => ExtensionState::Other(extension_type),
+    })
+}
@@ -0,0 +1,231 @@
+use {
+    crate::{
+        get_account_data,
+        spl_token_2022::{
+            extension::{BaseStateWithExtensions, StateWithExtensions},
+            state::Mint,
+        },
+        TokenInstructions, TOKEN_2022_ID,
+    },
+    litesvm::{types::FailedTransactionMetadata, LiteSVM},
+    solana_address::Address,
+    solana_instruction::Instruction,
+    solana_keypair::Keypair,
+    solana_signer::Signer,
+    solana_system_interface::instruction::transfer,
+    spl_token_metadata_interface::{
+        instruction::{initialize, update_field},
+        state::{Field, TokenMetadata},
+    },
+};
+
+/// ### Description
+/// Builder for the [`initialize`] token metadata instruction, storing the
+/// metadata in the mint itself.
+///
+/// The mint must have a [`MetadataPointer`](crate::MintExtension::MetadataPointer)
+/// to itself. The rent for the larger mint is paid by `payer`.
+///
+/// ### Optional fields
+/// - `mint_authority`: `payer` by default.
+/// - `update_authority`: `mint_authority` by default.
+/// - `token_program_id`: [`TOKEN_2022_ID`] by default.
+pub struct InitializeTokenMetadata<'a> {
+    svm: &'a mut LiteSVM,
+    payer: &'a Keypair,
+    mint: &'a Address,
+    name: &'a str,
+    symbol: &'a str,
+    uri: &'a str,
+    mint_authority: Option<&'a Keypair>,
+    update_authority: Option<&'a Address>,
+    token_program_id: Option<&'a Address>,
+}
+
+impl<'a> InitializeTokenMetadata<'a> {
+    /// Creates a new instance of [`initialize`] instruction.
+    pub fn new(
+        svm: &'a mut LiteSVM,
+        payer: &'a Keypair,
+        mint: &'a Address,
+        name: &'a str,
+        symbol: &'a str,
+        uri: &'a str,
+    ) -> Self {
+        InitializeTokenMetadata {
+            svm,
+            payer,
+            mint,
+            name,
+            symbol,
+            uri,
+            mint_authority: None,
+            update_authority: None,
+            token_program_id: None,
+        }
+    }
+
+    /// Sets the mint authority signing the instruction.
+    pub fn mint_authority(mut self, mint_authority: &'a Keypair) -> Self {
+        self.mint_authority = Some(mint_authority);
+        self
+    }
+
+    /// Sets the update authority of the metadata.
+    pub fn update_authority(mut self, update_authority: &'a Address) -> Self {
+        self.update_authority = Some(update_authority);
+        self
+    }
+
+    /// Sets the token program id for the instruction.
+    pub fn token_program_id(mut self, program_id: &'a Address) -> Self {
+        self.token_program_id = Some(program_id);
+        self
+    }
+
+    /// Returns the instructions and their signers without sending them.
+    pub fn instructions(self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        self.build()
+    }
+
+    /// Sends the transaction.
+    pub fn send(self) -> Result<(), FailedTransactionMetadata> {
+        self.build()?.send(self.svm, self.payer)
+    }
+
+    fn build(&self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        let payer_pk = self.payer.pubkey();
+        let token_program_id = self.token_program_id.unwrap_or(&TOKEN_2022_ID);
+        let mint_authority = self.mint_authority.unwrap_or(self.payer);
+        let mint_authority_pk = mint_authority.pubkey();
+        let update_authority = self.update_authority.unwrap_or(&mint_authority_pk);
+
+        let metadata = TokenMetadata {
+            update_authority: (*update_authority).into(),
+            mint: *self.mint,
+            name: self.name.to_string(),
+            symbol: self.symbol.to_string(),
+            uri: self.uri.to_string(),
+            additional_metadata: vec![],
+        };
+
+        let mut ixs = top_up_rent(self.svm, &payer_pk, self.mint, &metadata)?;
+        ixs.push(initialize(
+            token_program_id,
+            self.mint,
+            update_authority,
+            self.mint,
+            &mint_authority_pk,
+            metadata.name,
+            metadata.symbol,
+            metadata.uri,
+        ));
+
+        Ok(TokenInstructions::new(ixs, self.payer, &[mint_authority]))
+    }
+}
+
+/// ### Description
+/// Builder for the [`update_field`] token metadata instruction on metadata
+/// stored in the mint.
+///
+/// The rent for a larger mint is paid by `payer`.
+///
+/// ### Optional fields
+/// - `update_authority`: `payer` by default.
+/// - `token_program_id`: [`TOKEN_2022_ID`] by default.
+pub struct UpdateTokenMetadataField<'a> {
+    svm: &'a mut LiteSVM,
+    payer: &'a Keypair,
+    mint: &'a Address,
+    field: Field,
+    value: &'a str,
+    update_authority: Option<&'a Keypair>,
+    token_program_id: Option<&'a Address>,
+}
+
+impl<'a> UpdateTokenMetadataField<'a> {
+    /// Creates a new instance of [`update_field`] instruction.
+    pub fn new(
+        svm: &'a mut LiteSVM,
+        payer: &'a Keypair,
+        mint: &'a Address,
+        field: Field,
+        value: &'a str,
+    ) -> Self {
+        UpdateTokenMetadataField {
+            svm,
+            payer,
+            mint,
+            field,
+            value,
+            update_authority: None,
+            token_program_id: None,
+        }
+    }
+
+    /// Sets the update authority of the metadata.
+    pub fn update_authority(mut self, update_authority: &'a Keypair) -> Self {
+        self.update_authority = Some(update_authority);
+        self
+    }
+
+    /// Sets the token program id for the instruction.
+    pub fn token_program_id(mut self, program_id: &'a Address) -> Self {
+        self.token_program_id = Some(program_id);
+        self
+    }
+
+    /// Returns the instructions and their signers without sending them.
+    pub fn instructions(self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        self.build()
+    }
+
+    /// Sends the transaction.
+    pub fn send(self) -> Result<(), FailedTransactionMetadata> {
+        self.build()?.send(self.svm, self.payer)
+    }
+
+    fn build(&self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        let payer_pk = self.payer.pubkey();
+        let token_program_id = self.token_program_id.unwrap_or(&TOKEN_2022_ID);
+        let update_authority = self.update_authority.unwrap_or(self.payer);
+
+        let data = get_account_data(self.svm, self.mint)?;
+        let mut metadata = StateWithExtensions::<Mint>::unpack(&data)?
+            .get_variable_len_extension::<TokenMetadata>()?;
+        metadata.update(self.field.clone(), self.value.to_string());
+
+        let mut ixs = top_up_rent(self.svm, &payer_pk, self.mint, &metadata)?;
+        ixs.push(update_field(
+            token_program_id,
+            self.mint,
+            &update_authority.pubkey(),
+            self.field.clone(),
+            self.value.to_string(),
+        ));
+
+        Ok(TokenInstructions::new(ixs, self.payer, &[update_authority]))
+    }
+}
+
+/// Returns the transfer keeping `mint` rent exempt once it holds `metadata`, if any.
+fn top_up_rent(
+    svm: &LiteSVM,
+    payer: &Address,
+    mint: &Address,
+    metadata: &TokenMetadata,
+) -> Result<Vec<Instruction>, FailedTransactionMetadata> {
+    let data = get_account_data(svm, mint)?;
+    let new_len = StateWithExtensions::<Mint>::unpack(&data)?
+        .try_get_new_account_len_for_variable_len_extension(metadata)?;
+    let lamports = svm
+        .minimum_balance_for_rent_exemption(new_len)
+        .saturating_sub(svm.get_balance(mint).unwrap_or_default());
+
+    Ok(if lamports > 0 {
+        vec![transfer(payer, mint, lamports)]
+    } else {
+        vec![]
+    })
+}
@@ -0,0 +1,506 @@
+use {
+    crate::{
+        add_transfer_hook_accounts, get_account_data, get_multisig_signers,
+        spl_token_2022::{
+            extension::{
+                transfer_fee::{
+                    instruction::{
+                        harvest_withheld_tokens_to_mint, set_transfer_fee,
+                        transfer_checked_with_fee, withdraw_withheld_tokens_from_accounts,
+                        withdraw_withheld_tokens_from_mint,
+                    },
+                    TransferFeeConfig,
+                },
+                BaseStateWithExtensions, StateWithExtensions,
+            },
+            state::Mint,
+        },
+        TokenInstructions, TOKEN_2022_ID,
+    },
+    litesvm::{types::FailedTransactionMetadata, LiteSVM},
+    smallvec::{smallvec, SmallVec},
+    solana_address::Address,
+    solana_clock::Clock,
+    solana_keypair::Keypair,
+    solana_program_error::ProgramError,
+    solana_signer::{signers::Signers, Signer},
+};
+
+/// ### Description
+/// Builder for the [`transfer_checked_with_fee`] instruction.
+///
+/// The accounts required by the transfer hook of the mint are appended with
+/// [`add_transfer_hook_accounts`].
+///
+/// ### Optional fields
+/// - `source`: associated token account of the `owner` by default.
+/// - `owner`: `payer` by default.
+/// - `decimals`: `mint` decimals by default.
+/// - `fee`: fee of the current epoch by default.
+/// - `token_program_id`: [`TOKEN_2022_ID`] by default.
+pub struct TransferCheckedWithFee<'a> {
+    svm: &'a mut LiteSVM,
+    payer: &'a Keypair,
+    mint: &'a Address,
+    source: Option<&'a Address>,
+    destination: &'a Address,
+    token_program_id: Option<&'a Address>,
+    amount: u64,
+    decimals: Option<u8>,
+    fee: Option<u64>,
+    signers: SmallVec<[&'a Keypair; 1]>,
+    owner: Option<Address>,
+}
+
+impl<'a> TransferCheckedWithFee<'a> {
+    /// Creates a new instance of [`transfer_checked_with_fee`] instruction.
+    pub fn new(
+        svm: &'a mut LiteSVM,
+        payer: &'a Keypair,
+        mint: &'a Address,
+        destination: &'a Address,
+        amount: u64,
+    ) -> Self {
+        TransferCheckedWithFee {
+            svm,
+            payer,
+            mint,
+            source: None,
+            destination,
+            token_program_id: None,
+            amount,
+            decimals: None,
+            fee: None,
+            owner: None,
+            signers: smallvec![payer],
+        }
+    }
+
+    /// Sets the token program id for the instruction.
+    pub fn token_program_id(mut self, program_id: &'a Address) -> Self {
+        self.token_program_id = Some(program_id);
+        self
+    }
+
+    /// Sets the decimals of the transfer.
+    pub fn decimals(mut self, value: u8) -> Self {
+        self.decimals = Some(value);
+        self
+    }
+
+    /// Sets the expected fee of the transfer.
+    pub fn fee(mut self, value: u64) -> Self {
+        self.fee = Some(value);
+        self
+    }
+
+    /// Sets the token account source.
+    pub fn source(mut self, source: &'a Address) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// Sets the owner of the account with single owner.
+    pub fn owner(mut self, owner: &'a Keypair) -> Self {
+        self.owner = Some(owner.pubkey());
+        self.signers = smallvec![owner];
+        self
+    }
+
+    /// Sets the owner of the account with multisig owner.
+    pub fn multisig(mut self, multisig: &'a Address, signers: &'a [&'a Keypair]) -> Self {
+        self.owner = Some(*multisig);
+        self.signers = SmallVec::from(signers);
+        self
+    }
+
+    /// Returns the instructions and their signers without sending them.
+    pub fn instructions(self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        self.build()
+    }
+
+    /// Sends the transaction.
+    pub fn send(self) -> Result<(), FailedTransactionMetadata> {
+        self.build()?.send(self.svm, self.payer)
+    }
+
+    fn build(&self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        let payer_pk = self.payer.pubkey();
+        let token_program_id = self.token_program_id.unwrap_or(&TOKEN_2022_ID);
+
+        let authority = self.owner.unwrap_or(payer_pk);
+        let signing_keys = self.signers.pubkeys();
+        let signer_keys = get_multisig_signers(&authority, &signing_keys);
+
+        let source_pk = if let Some(source) = self.source {
+            *source
+        } else {
+            spl_associated_token_account_interface::address::get_associated_token_address_with_program_id(
+                &authority,
+                self.mint,
+                token_program_id,
+            )
+        };
+
+        let mint_data = get_account_data(self.svm, self.mint)?;
+        let mint = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+        let fee = match self.fee {
+            Some(fee) => fee,
+            None => {
+                let epoch = self.svm.get_sysvar::<Clock>().epoch;
+                mint.get_extension::<TransferFeeConfig>()?
+                    .calculate_epoch_fee(epoch, self.amount)
+                    .ok_or(ProgramError::ArithmeticOverflow)?
+            }
+        };
+
+        let mut ix = transfer_checked_with_fee(
+            token_program_id,
+            &source_pk,
+            self.mint,
+            self.destination,
+            &authority,
+            &signer_keys,
+            self.amount,
+            self.decimals.unwrap_or(mint.base.decimals),
+            fee,
+        )?;
+        add_transfer_hook_accounts(self.svm, &mut ix, self.amount)?;
+
+        Ok(TokenInstructions::new(
+            vec![ix],
+            self.payer,
+            self.signers.as_ref(),
+        ))
+    }
+}
+
+/// ### Description
+/// Builder for the [`set_transfer_fee`] instruction.
+///
+/// ### Optional fields
+/// - `authority`: `payer` by default.
+/// - `token_program_id`: [`TOKEN_2022_ID`] by default.
+pub struct SetTransferFee<'a> {
+    svm: &'a mut LiteSVM,
+    payer: &'a Keypair,
+    mint: &'a Address,
+    transfer_fee_basis_points: u16,
+    maximum_fee: u64,
+    token_program_id: Option<&'a Address>,
+    signers: SmallVec<[&'a Keypair; 1]>,
+    authority: Option<Address>,
+}
+
+impl<'a> SetTransferFee<'a> {
+    /// Creates a new instance of [`set_transfer_fee`] instruction.
+    pub fn new(
+        svm: &'a mut LiteSVM,
+        payer: &'a Keypair,
+        mint: &'a Address,
+        transfer_fee_basis_points: u16,
+        maximum_fee: u64,
+    ) -> Self {
+        SetTransferFee {
+            svm,
+            payer,
+            mint,
+            transfer_fee_basis_points,
+            maximum_fee,
+            token_program_id: None,
+            authority: None,
+            signers: smallvec![payer],
+        }
+    }
+
+    /// Sets the token program id for the instruction.
+    pub fn token_program_id(mut self, program_id: &'a Address) -> Self {
+        self.token_program_id = Some(program_id);
+        self
+    }
+
+    /// Sets the transfer fee config authority with single owner.
+    pub fn authority(mut self, authority: &'a Keypair) -> Self {
+        self.authority = Some(authority.pubkey());
+        self.signers = smallvec![authority];
+        self
+    }
+
+    /// Sets the transfer fee config authority with multisig owner.
+    pub fn multisig(mut self, multisig: &'a Address, signers: &'a [&'a Keypair]) -> Self {
+        self.authority = Some(*multisig);
+        self.signers = SmallVec::from(signers);
+        self
+    }
+
+    /// Returns the instructions and their signers without sending them.
+    pub fn instructions(self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        self.build()
+    }
+
+    /// Sends the transaction.
+    pub fn send(self) -> Result<(), FailedTransactionMetadata> {
+        self.build()?.send(self.svm, self.payer)
+    }
+
+    fn build(&self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        let payer_pk = self.payer.pubkey();
+        let token_program_id = self.token_program_id.unwrap_or(&TOKEN_2022_ID);
+
+        let authority = self.authority.unwrap_or(payer_pk);
+        let signing_keys = self.signers.pubkeys();
+        let signer_keys = get_multisig_signers(&authority, &signing_keys);
+
+        let ix = set_transfer_fee(
+            token_program_id,
+            self.mint,
+            &authority,
+            &signer_keys,
+            self.transfer_fee_basis_points,
+            self.maximum_fee,
+        )?;
+
+        Ok(TokenInstructions::new(
+            vec![ix],
+            self.payer,
+            self.signers.as_ref(),
+        ))
+    }
+}
+
+/// ### Description
+/// Builder for the [`harvest_withheld_tokens_to_mint`] instruction.
+///
+/// ### Optional fields
+/// - `token_program_id`: [`TOKEN_2022_ID`] by default.
+pub struct HarvestWithheldTokensToMint<'a> {
+    svm: &'a mut LiteSVM,
+    payer: &'a Keypair,
+    mint: &'a Address,
+    sources: &'a [&'a Address],
+    token_program_id: Option<&'a Address>,
+}
+
+impl<'a> HarvestWithheldTokensToMint<'a> {
+    /// Creates a new instance of [`harvest_withheld_tokens_to_mint`] instruction.
+    pub fn new(
+        svm: &'a mut LiteSVM,
+        payer: &'a Keypair,
+        mint: &'a Address,
+        sources: &'a [&'a Address],
+    ) -> Self {
+        HarvestWithheldTokensToMint {
+            svm,
+            payer,
+            mint,
+            sources,
+            token_program_id: None,
+        }
+    }
+
+    /// Sets the token program id for the instruction.
+    pub fn token_program_id(mut self, program_id: &'a Address) -> Self {
+        self.token_program_id = Some(program_id);
+        self
+    }
+
+    /// Returns the instructions and their signers without sending them.
+    pub fn instructions(self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        self.build()
+    }
+
+    /// Sends the transaction.
+    pub fn send(self) -> Result<(), FailedTransactionMetadata> {
+        self.build()?.send(self.svm, self.payer)
+    }
+
+    fn build(&self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        let token_program_id = self.token_program_id.unwrap_or(&TOKEN_2022_ID);
+
+        let ix = harvest_withheld_tokens_to_mint(token_program_id, self.mint, self.sources)?;
+
+        Ok(TokenInstructions::new(vec![ix], self.payer, &[]))
+    }
+}
+
+/// ### Description
+/// Builder for the [`withdraw_withheld_tokens_from_mint`] instruction.
+///
+/// ### Optional fields
+/// - `authority`: `payer` by default.
+/// - `token_program_id`: [`TOKEN_2022_ID`] by default.
+pub struct WithdrawWithheldTokensFromMint<'a> {
+    svm: &'a mut LiteSVM,
+    payer: &'a Keypair,
+    mint: &'a Address,
+    destination: &'a Address,
+    token_program_id: Option<&'a Address>,
+    signers: SmallVec<[&'a Keypair; 1]>,
+    authority: Option<Address>,
+}
+
+impl<'a> WithdrawWithheldTokensFromMint<'a> {
+    /// Creates a new instance of [`withdraw_withheld_tokens_from_mint`] instruction.
+    pub fn new(
+        svm: &'a mut LiteSVM,
+        payer: &'a Keypair,
+        mint: &'a Address,
+        destination: &'a Address,
+    ) -> Self {
+        WithdrawWithheldTokensFromMint {
+            svm,
+            payer,
+            mint,
+            destination,
+            token_program_id: None,
+            authority: None,
+            signers: smallvec![payer],
+        }
+    }
+
+    /// Sets the token program id for the instruction.
+    pub fn token_program_id(mut self, program_id: &'a Address) -> Self {
+        self.token_program_id = Some(program_id);
+        self
+    }
+
+    /// Sets the withdraw withheld authority with single owner.
+    pub fn authority(mut self, authority: &'a Keypair) -> Self {
+        self.authority = Some(authority.pubkey());
+        self.signers = smallvec![authority];
+        self
+    }
+
+    /// Sets the withdraw withheld authority with multisig owner.
+    pub fn multisig(mut self, multisig: &'a Address, signers: &'a [&'a Keypair]) -> Self {
+        self.authority = Some(*multisig);
+        self.signers = SmallVec::from(signers);
+        self
+    }
+
+    /// Returns the instructions and their signers without sending them.
+    pub fn instructions(self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        self.build()
+    }
+
+    /// Sends the transaction.
+    pub fn send(self) -> Result<(), FailedTransactionMetadata> {
+        self.build()?.send(self.svm, self.payer)
+    }
+
+    fn build(&self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        let payer_pk = self.payer.pubkey();
+        let token_program_id = self.token_program_id.unwrap_or(&TOKEN_2022_ID);
+
+        let authority = self.authority.unwrap_or(payer_pk);
+        let signing_keys = self.signers.pubkeys();
+        let signer_keys = get_multisig_signers(&authority, &signing_keys);
+
+        let ix = withdraw_withheld_tokens_from_mint(
+            token_program_id,
+            self.mint,
+            self.destination,
+            &authority,
+            &signer_keys,
+        )?;
+
+        Ok(TokenInstructions::new(
+            vec![ix],
+            self.payer,
+            self.signers.as_ref(),
+        ))
+    }
+}
+
+/// ### Description
+/// Builder for the [`withdraw_withheld_tokens_from_accounts`] instruction.
+///
+/// ### Optional fields
+/// - `authority`: `payer` by default.
+/// - `token_program_id`: [`TOKEN_2022_ID`] by default.
+pub struct WithdrawWithheldTokensFromAccounts<'a> {
+    svm: &'a mut LiteSVM,
+    payer: &'a Keypair,
+    mint: &'a Address,
+    destination: &'a Address,
+    sources: &'a [&'a Address],
+    token_program_id: Option<&'a Address>,
+    signers: SmallVec<[&'a Keypair; 1]>,
+    authority: Option<Address>,
+}
+
+impl<'a> WithdrawWithheldTokensFromAccounts<'a> {
+    /// Creates a new instance of [`withdraw_withheld_tokens_from_accounts`] instruction.
+    pub fn new(
+        svm: &'a mut LiteSVM,
+        payer: &'a Keypair,
+        mint: &'a Address,
+        destination: &'a Address,
+        sources: &'a [&'a Address],
+    ) -> Self {
+        WithdrawWithheldTokensFromAccounts {
+            svm,
+            payer,
+            mint,
+            destination,
+            sources,
+            token_program_id: None,
+            authority: None,
+            signers: smallvec![payer],
+        }
+    }
+
+    /// Sets the token program id for the instruction.
+    pub fn token_program_id(mut self, program_id: &'a Address) -> Self {
+        self.token_program_id = Some(program_id);
+        self
+    }
+
+    /// Sets the withdraw withheld authority with single owner.
+    pub fn authority(mut self, authority: &'a Keypair) -> Self {
+        self.authority = Some(authority.pubkey());
+        self.signers = smallvec![authority];
+        self
+    }
+
+    /// Sets the withdraw withheld authority with multisig owner.
+    pub fn multisig(mut self, multisig: &'a Address, signers: &'a [&'a Keypair]) -> Self {
+        self.authority = Some(*multisig);
+        self.signers = SmallVec::from(signers);
+        self
+    }
+
+    /// Returns the instructions and their signers without sending them.
+    pub fn instructions(self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        self.build()
+    }
+
+    /// Sends the transaction.
+    pub fn send(self) -> Result<(), FailedTransactionMetadata> {
+        self.build()?.send(self.svm, self.payer)
+    }
+
+    fn build(&self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        let payer_pk = self.payer.pubkey();
+        let token_program_id = self.token_program_id.unwrap_or(&TOKEN_2022_ID);
+
+        let authority = self.authority.unwrap_or(payer_pk);
+        let signing_keys = self.signers.pubkeys();
+        let signer_keys = get_multisig_signers(&authority, &signing_keys);
+
+        let ix = withdraw_withheld_tokens_from_accounts(
+            token_program_id,
+            self.mint,
+            self.destination,
+            &authority,
+            &signer_keys,
+            self.sources,
+        )?;
+
+        Ok(TokenInstructions::new(
+            vec![ix],
+            self.payer,
+            self.signers.as_ref(),
+        ))
+    }
+}
@@ -0,0 +1,89 @@
+pub use spl_transfer_hook_interface::get_extra_account_metas_address;
+use {
+    crate::{
+        get_account_data,
+        spl_token_2022::{
+            extension::{
+                transfer_hook::TransferHook, BaseStateWithExtensions, StateWithExtensions,
+            },
+            state::Mint,
+        },
+    },
+    litesvm::{types::FailedTransactionMetadata, LiteSVM},
+    solana_address::Address,
+    solana_instruction::Instruction,
+    solana_program_error::ProgramError,
+    spl_transfer_hook_interface::offchain::add_extra_account_metas_for_execute,
+    std::{
+        future::{self, Future},
+        pin::pin,
+        task::{Context, Poll, Waker},
+    },
+};
+
+/// Returns the transfer hook program of `mint`, or `None` if the mint has no
+/// transfer hook program.
+pub fn get_transfer_hook_program_id(
+    svm: &LiteSVM,
+    mint: &Address,
+) -> Result<Option<Address>, FailedTransactionMetadata> {
+    let data = get_account_data(svm, mint)?;
+    let state = StateWithExtensions::<Mint>::unpack(&data)?;
+
+    Ok(state
+        .get_extension::<TransferHook>()
+        .ok()
+        .and_then(|transfer_hook| transfer_hook.program_id.into()))
+}
+
+/// Appends the accounts required by the transfer hook of the mint to a
+/// Token-2022 transfer instruction, such as `transfer_checked` or
+/// `transfer_checked_with_fee`.
+///
+/// The first four accounts of `instruction` must be the source, mint,
+/// destination and authority. The extra accounts are resolved from the
+/// `ExtraAccountMetaList` account of the hook program, including the ones
+/// derived from instruction data or from other accounts, and are followed by the
+/// hook program and the `ExtraAccountMetaList` account.
+///
+/// Does nothing if the mint has no transfer hook program.
+pub fn add_transfer_hook_accounts(
+    svm: &LiteSVM,
+    instruction: &mut Instruction,
+    amount: u64,
+) -> Result<(), FailedTransactionMetadata> {
+    let [source, mint, destination, authority] = match instruction.accounts.get(..4) {
+        Some([source, mint, destination, authority]) => [
+            source.pubkey,
+            mint.pubkey,
+            destination.pubkey,
+            authority.pubkey,
+        ],
+        _ => return Err(ProgramError::NotEnoughAccountKeys.into()),
+    };
+    let Some(program_id) = get_transfer_hook_program_id(svm, &mint)? else {
+        return Ok(());
+    };
+
+    let resolve = add_extra_account_metas_for_execute(
+        instruction,
+        &program_id,
+        &source,
+        &mint,
+        &destination,
+        &authority,
+        amount,
+        |address| future::ready(Ok(svm.get_account(&address).map(|account| account.data))),
+    );
+    // Account data is read synchronously, so the resolution completes on the
+    // first poll.
+    let Poll::Ready(result) = pin!(resolve).poll(&mut Context::from_waker(Waker::noop())) else {
+        unreachable!("transfer hook accounts are resolved without waiting");
+    };
+    result.map_err(|err| {
+        err.downcast::<ProgramError>()
+            .map_or(ProgramError::InvalidAccountData, |err| *err)
+    })?;
+
+    Ok(())
+}
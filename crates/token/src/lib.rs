@@ -10,10 +10,9 @@ mod create_ata;
 mod create_ata_idempotent;
 mod create_mint;
 mod create_multisig;
-#[cfg(not(feature = "token-2022"))]
 mod create_native_mint;
-#[cfg(feature = "token-2022")]
-mod create_native_mint_2022;
+mod deal;
+mod extension;
 mod freeze_account;
 mod mint_to;
 mod mint_to_checked;
@@ -24,8 +23,6 @@ mod thaw_account;
 mod transfer;
 mod transfer_checked;
 
-#[cfg(feature = "token-2022")]
-use create_native_mint_2022 as create_native_mint;
 #[cfg(feature = "token-2022")]
 pub use spl_token_2022_interface as spl_token;
 #[cfg(not(feature = "token-2022"))]
@@ -33,34 +30,116 @@ pub use spl_token_interface as spl_token;
 pub use {
     approve::*, approve_checked::*, burn::*, burn_checked::*, close_account::*, create_account::*,
     create_ata::*, create_ata_idempotent::*, create_mint::*, create_multisig::*,
-    create_native_mint::*, freeze_account::*, mint_to::*, mint_to_checked::*, revoke::*,
-    set_authority::*, sync_native::*, thaw_account::*, transfer::*, transfer_checked::*,
+    create_native_mint::*, deal::*, extension::*, freeze_account::*, mint_to::*,
+    mint_to_checked::*, revoke::*, set_authority::*, spl_token_2022_interface as spl_token_2022,
+    spl_token_metadata_interface, spl_transfer_hook_interface, sync_native::*, thaw_account::*,
+    transfer::*, transfer_checked::*,
 };
 use {
     litesvm::{types::FailedTransactionMetadata, LiteSVM},
     solana_address::Address,
+    solana_instruction::Instruction,
+    solana_keypair::Keypair,
+    solana_program_error::ProgramError,
     solana_program_pack::{IsInitialized, Pack},
+    solana_signer::Signer,
+    solana_transaction::Transaction,
     solana_transaction_error::TransactionError,
 };
 
+/// The SPL Token program id.
+pub const SPL_TOKEN_ID: Address = spl_token_interface::ID;
+
+/// The Token-2022 program id.
+pub const TOKEN_2022_ID: Address = spl_token_2022::ID;
+
+/// The token program used by the builders when none is set: [`SPL_TOKEN_ID`], or
+/// [`TOKEN_2022_ID`] with the `token-2022` feature.
 pub const TOKEN_ID: Address = spl_token::ID;
 
+/// The instructions built by a builder, with the keypairs that must sign them.
+///
+/// Returned by the `instructions` method of the builders to compose token
+/// operations with other instructions in a single transaction.
+#[derive(Debug)]
+pub struct TokenInstructions {
+    pub instructions: Vec<Instruction>,
+    /// The keypairs given to or generated by the builder that are required
+    /// signers of `instructions`. The fee payer is only included when one of
+    /// the instructions requires its signature.
+    pub signers: Vec<Keypair>,
+}
+
+impl TokenInstructions {
+    fn new(instructions: Vec<Instruction>, payer: &Keypair, signers: &[&Keypair]) -> Self {
+        let mut required_signers: Vec<Keypair> = vec![];
+        for keypair in std::iter::once(payer).chain(signers.iter().copied()) {
+            let pubkey = keypair.pubkey();
+            let is_signer = instructions.iter().any(|ix| {
+                ix.accounts
+                    .iter()
+                    .any(|meta| meta.is_signer && meta.pubkey == pubkey)
+            });
+            if is_signer && required_signers.iter().all(|kp| kp.pubkey() != pubkey) {
+                required_signers.push(keypair.insecure_clone());
+            }
+        }
+
+        TokenInstructions {
+            instructions,
+            signers: required_signers,
+        }
+    }
+
+    fn send(self, svm: &mut LiteSVM, payer: &Keypair) -> Result<(), FailedTransactionMetadata> {
+        let block_hash = svm.latest_blockhash();
+        let mut tx = Transaction::new_with_payer(&self.instructions, Some(&payer.pubkey()));
+        tx.partial_sign(&[payer], block_hash);
+        tx.partial_sign(&self.signers.iter().collect::<Vec<_>>(), block_hash);
+
+        svm.send_transaction(tx)?;
+
+        Ok(())
+    }
+}
+
 pub fn get_spl_account<T: Pack + IsInitialized>(
     svm: &LiteSVM,
     account: &Address,
 ) -> Result<T, FailedTransactionMetadata> {
-    let account = T::unpack(
-        &svm.get_account(account)
-            .ok_or(FailedTransactionMetadata {
-                err: TransactionError::AccountNotFound,
-                meta: Default::default(),
-            })?
-            .data[..T::LEN],
-    )?;
+    let account = T::unpack(&get_account_data(svm, account)?[..T::LEN])?;
 
     Ok(account)
 }
 
+fn get_account_data(
+    svm: &LiteSVM,
+    account: &Address,
+) -> Result<Vec<u8>, FailedTransactionMetadata> {
+    Ok(svm
+        .get_account(account)
+        .ok_or(FailedTransactionMetadata {
+            err: TransactionError::AccountNotFound,
+            meta: Default::default(),
+        })?
+        .data)
+}
+
+/// Builds an instruction shared by both token programs for `token_program_id`.
+///
+/// Token-2022 keeps the SPL Token instruction layout, so the Token-2022 builder
+/// is used and its program id replaced.
+fn token_instruction(
+    token_program_id: &Address,
+    build: impl FnOnce(&Address) -> Result<Instruction, ProgramError>,
+) -> Result<Instruction, ProgramError> {
+    spl_token_2022::check_spl_token_program_account(token_program_id)?;
+    let mut ix = build(&TOKEN_2022_ID)?;
+    ix.program_id = *token_program_id;
+
+    Ok(ix)
+}
+
 fn get_multisig_signers<'a>(
     authority: &Address,
     signing_pubkeys: &'a [Address],
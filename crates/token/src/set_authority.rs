@@ -1,17 +1,39 @@
 use {
     super::{
         get_multisig_signers,
-        spl_token::instruction::{set_authority, AuthorityType},
-        TOKEN_ID,
+        spl_token_2022::instruction::{set_authority, AuthorityType},
+        token_instruction, TokenInstructions, TOKEN_ID,
     },
     litesvm::{types::FailedTransactionMetadata, LiteSVM},
     smallvec::{smallvec, SmallVec},
     solana_address::Address,
     solana_keypair::Keypair,
     solana_signer::{signers::Signers, Signer},
-    solana_transaction::Transaction,
+    spl_token_interface::instruction::AuthorityType as SplTokenAuthorityType,
 };
 
+/// The authority type of a [`SetAuthority`], given as the `AuthorityType` of
+/// either SPL Token or Token-2022.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenAuthorityType(AuthorityType);
+
+impl From<AuthorityType> for TokenAuthorityType {
+    fn from(authority_type: AuthorityType) -> Self {
+        TokenAuthorityType(authority_type)
+    }
+}
+
+impl From<SplTokenAuthorityType> for TokenAuthorityType {
+    fn from(authority_type: SplTokenAuthorityType) -> Self {
+        TokenAuthorityType(match authority_type {
+            SplTokenAuthorityType::MintTokens => AuthorityType::MintTokens,
+            SplTokenAuthorityType::FreezeAccount => AuthorityType::FreezeAccount,
+            SplTokenAuthorityType::AccountOwner => AuthorityType::AccountOwner,
+            SplTokenAuthorityType::CloseAccount => AuthorityType::CloseAccount,
+        })
+    }
+}
+
 /// ### Description
 /// Builder for the [`set_authority`] instruction.
 ///
@@ -35,13 +57,13 @@ impl<'a> SetAuthority<'a> {
         svm: &'a mut LiteSVM,
         payer: &'a Keypair,
         account: &'a Address,
-        authority_type: AuthorityType,
+        authority_type: impl Into<TokenAuthorityType>,
     ) -> Self {
         SetAuthority {
             svm,
             payer,
             owner: None,
-            authority_type,
+            authority_type: authority_type.into().0,
             account,
             new_authority: None,
             token_program_id: None,
@@ -75,8 +97,17 @@ impl<'a> SetAuthority<'a> {
         self
     }
 
+    /// Returns the instructions and their signers without sending them.
+    pub fn instructions(self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        self.build()
+    }
+
     /// Sends the transaction.
     pub fn send(self) -> Result<(), FailedTransactionMetadata> {
+        self.build()?.send(self.svm, self.payer)
+    }
+
+    fn build(&self) -> Result<TokenInstructions, FailedTransactionMetadata> {
         let token_program_id = self.token_program_id.unwrap_or(&TOKEN_ID);
         let payer_pk = self.payer.pubkey();
 
@@ -84,22 +115,21 @@ impl<'a> SetAuthority<'a> {
         let signing_keys = self.signers.pubkeys();
         let signer_keys = get_multisig_signers(&authority, &signing_keys);
 
-        let ix = set_authority(
-            token_program_id,
-            self.account,
-            self.new_authority,
-            self.authority_type,
-            &authority,
-            &signer_keys,
-        )?;
-
-        let block_hash = self.svm.latest_blockhash();
-        let mut tx = Transaction::new_with_payer(&[ix], Some(&payer_pk));
-        tx.partial_sign(&[self.payer], block_hash);
-        tx.partial_sign(self.signers.as_ref(), block_hash);
-
-        self.svm.send_transaction(tx)?;
+        let ix = token_instruction(token_program_id, |program_id| {
+            set_authority(
+                program_id,
+                self.account,
+                self.new_authority,
+                self.authority_type.clone(),
+                &authority,
+                &signer_keys,
+            )
+        })?;
 
-        Ok(())
+        Ok(TokenInstructions::new(
+            vec![ix],
+            self.payer,
+            self.signers.as_ref(),
+        ))
     }
 }
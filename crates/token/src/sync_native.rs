@@ -1,10 +1,10 @@
 use {
-    super::{spl_token::instruction::sync_native, TOKEN_ID},
+    super::{
+        spl_token_2022::instruction::sync_native, token_instruction, TokenInstructions, TOKEN_ID,
+    },
     litesvm::{types::FailedTransactionMetadata, LiteSVM},
     solana_address::Address,
     solana_keypair::Keypair,
-    solana_signer::Signer,
-    solana_transaction::Transaction,
 };
 
 /// ### Description
@@ -36,21 +36,23 @@ impl<'a> SyncNative<'a> {
         self
     }
 
+    /// Returns the instructions and their signers without sending them.
+    pub fn instructions(self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        self.build()
+    }
+
     /// Sends the transaction.
     pub fn send(self) -> Result<(), FailedTransactionMetadata> {
-        let token_program_id = self.token_program_id.unwrap_or(&TOKEN_ID);
+        self.build()?.send(self.svm, self.payer)
+    }
 
-        let ix = sync_native(token_program_id, self.account)?;
+    fn build(&self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        let token_program_id = self.token_program_id.unwrap_or(&TOKEN_ID);
 
-        let block_hash = self.svm.latest_blockhash();
-        let tx = Transaction::new_signed_with_payer(
-            &[ix],
-            Some(&self.payer.pubkey()),
-            &[self.payer],
-            block_hash,
-        );
-        self.svm.send_transaction(tx)?;
+        let ix = token_instruction(token_program_id, |program_id| {
+            sync_native(program_id, self.account)
+        })?;
 
-        Ok(())
+        Ok(TokenInstructions::new(vec![ix], self.payer, &[]))
     }
 }
@@ -1,13 +1,12 @@
-#[cfg_attr(feature = "token-2022", allow(deprecated))]
-use super::{spl_token::instruction::transfer, TOKEN_ID};
+#[allow(deprecated)]
+use super::{spl_token_2022::instruction::transfer, token_instruction, TOKEN_ID};
 use {
-    super::get_multisig_signers,
+    super::{get_multisig_signers, TokenInstructions},
     litesvm::{types::FailedTransactionMetadata, LiteSVM},
     smallvec::{smallvec, SmallVec},
     solana_address::Address,
     solana_keypair::Keypair,
     solana_signer::{signers::Signers, Signer},
-    solana_transaction::Transaction,
 };
 
 /// ### Description
@@ -77,8 +76,17 @@ impl<'a> Transfer<'a> {
         self
     }
 
+    /// Returns the instructions and their signers without sending them.
+    pub fn instructions(self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        self.build()
+    }
+
     /// Sends the transaction.
     pub fn send(self) -> Result<(), FailedTransactionMetadata> {
+        self.build()?.send(self.svm, self.payer)
+    }
+
+    fn build(&self) -> Result<TokenInstructions, FailedTransactionMetadata> {
         let payer_pk = self.payer.pubkey();
         let token_program_id = self.token_program_id.unwrap_or(&TOKEN_ID);
 
@@ -96,23 +104,22 @@ impl<'a> Transfer<'a> {
             )
         };
 
-        #[cfg_attr(feature = "token-2022", allow(deprecated))]
-        let ix = transfer(
-            token_program_id,
-            &source_pk,
-            self.destination,
-            &authority,
-            &signer_keys,
-            self.amount,
-        )?;
-
-        let block_hash = self.svm.latest_blockhash();
-        let mut tx = Transaction::new_with_payer(&[ix], Some(&payer_pk));
-        tx.partial_sign(&[self.payer], block_hash);
-        tx.partial_sign(self.signers.as_ref(), block_hash);
-
-        self.svm.send_transaction(tx)?;
+        #[allow(deprecated)]
+        let ix = token_instruction(token_program_id, |program_id| {
+            transfer(
+                program_id,
+                &source_pk,
+                self.destination,
+                &authority,
+                &signer_keys,
+                self.amount,
+            )
+        })?;
 
-        Ok(())
+        Ok(TokenInstructions::new(
+            vec![ix],
+            self.payer,
+            self.signers.as_ref(),
+        ))
     }
 }
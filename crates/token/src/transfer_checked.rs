@@ -1,20 +1,22 @@
 use {
     super::{
-        get_multisig_signers, get_spl_account,
-        spl_token::{instruction::transfer_checked, state::Mint},
-        TOKEN_ID,
+        add_transfer_hook_accounts, get_multisig_signers, get_spl_account,
+        spl_token_2022::{instruction::transfer_checked, state::Mint},
+        token_instruction, TokenInstructions, TOKEN_ID,
     },
     litesvm::{types::FailedTransactionMetadata, LiteSVM},
     smallvec::{smallvec, SmallVec},
     solana_address::Address,
     solana_keypair::Keypair,
     solana_signer::{signers::Signers, Signer},
-    solana_transaction::Transaction,
 };
 
 /// ### Description
 /// Builder for the [`transfer_checked`] instruction.
 ///
+/// The accounts required by the transfer hook of the mint are appended with
+/// [`add_transfer_hook_accounts`].
+///
 /// ### Optional fields
 /// - `source`: associated token account of the `owner` by default.
 /// - `owner`: `payer` by default.
@@ -88,8 +90,17 @@ impl<'a> TransferChecked<'a> {
         self
     }
 
+    /// Returns the instructions and their signers without sending them.
+    pub fn instructions(self) -> Result<TokenInstructions, FailedTransactionMetadata> {
+        self.build()
+    }
+
     /// Sends the transaction.
     pub fn send(self) -> Result<(), FailedTransactionMetadata> {
+        self.build()?.send(self.svm, self.payer)
+    }
+
+    fn build(&self) -> Result<TokenInstructions, FailedTransactionMetadata> {
         let payer_pk = self.payer.pubkey();
         let token_program_id = self.token_program_id.unwrap_or(&TOKEN_ID);
 
@@ -108,24 +119,24 @@ impl<'a> TransferChecked<'a> {
         };
 
         let mint: Mint = get_spl_account(self.svm, self.mint)?;
-        let ix = transfer_checked(
-            token_program_id,
-            &source_pk,
-            self.mint,
-            self.destination,
-            &authority,
-            &signer_keys,
-            self.amount,
-            self.decimals.unwrap_or(mint.decimals),
-        )?;
-
-        let block_hash = self.svm.latest_blockhash();
-        let mut tx = Transaction::new_with_payer(&[ix], Some(&payer_pk));
-        tx.partial_sign(&[self.payer], block_hash);
-        tx.partial_sign(self.signers.as_ref(), block_hash);
-
-        self.svm.send_transaction(tx)?;
+        let mut ix = token_instruction(token_program_id, |program_id| {
+            transfer_checked(
+                program_id,
+                &source_pk,
+                self.mint,
+                self.destination,
+                &authority,
+                &signer_keys,
+                self.amount,
+                self.decimals.unwrap_or(mint.decimals),
+            )
+        })?;
+        add_transfer_hook_accounts(self.svm, &mut ix, self.amount)?;
 
-        Ok(())
+        Ok(TokenInstructions::new(
+            vec![ix],
+            self.payer,
+            self.signers.as_ref(),
+        ))
     }
 }
@@ -0,0 +1,170 @@
+use {
+    litesvm::LiteSVM,
+    litesvm_token::{
+        deal, get_spl_account, get_token_account_with_extensions, set_mint,
+        spl_token_2022::{
+            self,
+            extension::ExtensionType,
+            state::{Account, Mint},
+        },
+        CreateMint, CreateNativeMint, MintExtension, MintTo, Transfer, TransferCheckedWithFee,
+        SPL_TOKEN_ID, TOKEN_2022_ID,
+    },
+    solana_address::{address, Address},
+    solana_keypair::Keypair,
+    solana_native_token::LAMPORTS_PER_SOL,
+    solana_program_option::COption,
+    solana_signer::Signer,
+    spl_associated_token_account_interface::address::get_associated_token_address_with_program_id,
+};
+
+const USDC: Address = address!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
+
+fn setup() -> (LiteSVM, Keypair) {
+    let mut svm = LiteSVM::new();
+    let payer_kp = Keypair::new();
+    svm.airdrop(&payer_kp.pubkey(), LAMPORTS_PER_SOL * 10)
+        .unwrap();
+
+    (svm, payer_kp)
+}
+
+fn test_deal(token_program_id: &Address) {
+    let (svm, payer_kp) = &mut setup();
+    let owner_kp = Keypair::new();
+    let owner_pk = owner_kp.pubkey();
+
+    let mint_pk = CreateMint::new(svm, payer_kp)
+        .token_program_id(token_program_id)
+        .send()
+        .unwrap();
+
+    let ata_pk = deal(svm, &mint_pk, &owner_pk, 1_000).unwrap();
+    assert_eq!(
+        ata_pk,
+        get_associated_token_address_with_program_id(&owner_pk, &mint_pk, token_program_id)
+    );
+    let account: Account = get_spl_account(svm, &ata_pk).unwrap();
+    assert_eq!(account.amount, 1_000);
+    assert_eq!(account.owner, owner_pk);
+    let mint: Mint = get_spl_account(svm, &mint_pk).unwrap();
+    assert_eq!(mint.supply, 1_000);
+
+    deal(svm, &mint_pk, &owner_pk, 400).unwrap();
+    let mint: Mint = get_spl_account(svm, &mint_pk).unwrap();
+    assert_eq!(mint.supply, 400);
+
+    // The token program accepts the written state.
+    let payer_ata_pk = deal(svm, &mint_pk, &payer_kp.pubkey(), 0).unwrap();
+    Transfer::new(svm, payer_kp, &mint_pk, &payer_ata_pk, 150)
+        .owner(&owner_kp)
+        .token_program_id(token_program_id)
+        .send()
+        .unwrap();
+    let account: Account = get_spl_account(svm, &ata_pk).unwrap();
+    assert_eq!(account.amount, 250);
+}
+
+#[test]
+fn test_deal_spl_token() {
+    test_deal(&SPL_TOKEN_ID);
+}
+
+#[test]
+fn test_deal_token_2022() {
+    test_deal(&TOKEN_2022_ID);
+}
+
+#[test]
+fn test_forge_mint() {
+    let (svm, payer_kp) = &mut setup();
+    let payer_pk = payer_kp.pubkey();
+
+    let mint = Mint {
+        mint_authority: COption::Some(payer_pk),
+        decimals: 6,
+        is_initialized: true,
+        ..Mint::default()
+    };
+    set_mint(svm, &USDC, mint, &SPL_TOKEN_ID).unwrap();
+    assert_eq!(svm.get_account(&USDC).unwrap().owner, SPL_TOKEN_ID);
+
+    let ata_pk = deal(svm, &USDC, &payer_pk, 0).unwrap();
+    MintTo::new(svm, payer_kp, &USDC, &ata_pk, 1_000_000)
+        .token_program_id(&SPL_TOKEN_ID)
+        .send()
+        .unwrap();
+
+    let account: Account = get_spl_account(svm, &ata_pk).unwrap();
+    assert_eq!(account.amount, 1_000_000);
+    let mint: Mint = get_spl_account(svm, &USDC).unwrap();
+    assert_eq!(mint.supply, 1_000_000);
+    assert_eq!(mint.decimals, 6);
+}
+
+#[test]
+fn test_deal_with_extensions() {
+    let (svm, payer_kp) = &mut setup();
+    let owner_kp = Keypair::new();
+
+    let mint_pk = CreateMint::new(svm, payer_kp)
+        .extension(MintExtension::TransferFeeConfig {
+            transfer_fee_config_authority: None,
+            withdraw_withheld_authority: None,
+            transfer_fee_basis_points: 100,
+            maximum_fee: 1_000,
+        })
+        .send()
+        .unwrap();
+
+    let source_pk = deal(svm, &mint_pk, &owner_kp.pubkey(), 10_000).unwrap();
+    let destination_pk = deal(svm, &mint_pk, &payer_kp.pubkey(), 0).unwrap();
+
+    let account = get_token_account_with_extensions(svm, &source_pk).unwrap();
+    assert_eq!(
+        account.extension_types(),
+        vec![
+            ExtensionType::ImmutableOwner,
+            ExtensionType::TransferFeeAmount
+        ]
+    );
+
+    // Dealing keeps the extensions of an existing account.
+    deal(svm, &mint_pk, &owner_kp.pubkey(), 5_000).unwrap();
+    let account = get_token_account_with_extensions(svm, &source_pk).unwrap();
+    assert_eq!(account.base.amount, 5_000);
+    assert_eq!(account.extensions.len(), 2);
+
+    TransferCheckedWithFee::new(svm, payer_kp, &mint_pk, &destination_pk, 1_000)
+        .source(&source_pk)
+        .owner(&owner_kp)
+        .send()
+        .unwrap();
+    let account: Account = get_spl_account(svm, &destination_pk).unwrap();
+    assert_eq!(account.amount, 990);
+}
+
+#[test]
+fn test_deal_native_mint() {
+    let (svm, payer_kp) = &mut setup();
+    let owner_pk = Address::new_unique();
+
+    CreateNativeMint::new(svm, payer_kp)
+        .token_program_id(&TOKEN_2022_ID)
+        .send()
+        .unwrap();
+
+    let ata_pk = deal(
+        svm,
+        &spl_token_2022::native_mint::ID,
+        &owner_pk,
+        LAMPORTS_PER_SOL,
+    )
+    .unwrap();
+
+    let account: Account = get_spl_account(svm, &ata_pk).unwrap();
+    let rent = svm.minimum_balance_for_rent_exemption(svm.get_account(&ata_pk).unwrap().data.len());
+    assert_eq!(account.amount, LAMPORTS_PER_SOL);
+    assert_eq!(account.is_native, COption::Some(rent));
+    assert_eq!(svm.get_balance(&ata_pk), Some(rent + LAMPORTS_PER_SOL));
+}
@@ -0,0 +1,418 @@
+use {
+    litesvm::LiteSVM,
+    litesvm_token::{
+        get_mint_with_extensions, get_spl_account, get_token_account_with_extensions,
+        spl_token_2022::{
+            extension::{
+                cpi_guard::CpiGuard, default_account_state::DefaultAccountState,
+                interest_bearing_mint::InterestBearingConfig, memo_transfer::MemoTransfer,
+                transfer_fee::TransferFeeAmount, BaseStateWithExtensions, ExtensionType,
+                StateWithExtensions,
+            },
+            state::{Account, AccountState, Mint},
+        },
+        spl_token_metadata_interface::state::{Field, TokenMetadata},
+        CloseAccount, CreateAccount, CreateAssociatedTokenAccount, CreateMint, DisableCpiGuard,
+        EnableCpiGuard, EnableRequiredTransferMemos, ExtensionState, HarvestWithheldTokensToMint,
+        InitializeTokenMetadata, MintExtension, MintTo, SetTransferFee, TransferChecked,
+        TransferCheckedWithFee, UpdateDefaultAccountState, UpdateInterestRate,
+        UpdateTokenMetadataField, WithdrawWithheldTokensFromMint, TOKEN_2022_ID,
+    },
+    solana_address::Address,
+    solana_keypair::Keypair,
+    solana_native_token::LAMPORTS_PER_SOL,
+    solana_signer::Signer,
+};
+
+fn setup() -> (LiteSVM, Keypair) {
+    let mut svm = LiteSVM::new();
+    let payer_kp = Keypair::new();
+    svm.airdrop(&payer_kp.pubkey(), LAMPORTS_PER_SOL * 10)
+        .unwrap();
+
+    (svm, payer_kp)
+}
+
+fn get_account_data(svm: &LiteSVM, address: &Address) -> Vec<u8> {
+    svm.get_account(address).unwrap().data
+}
+
+#[test]
+fn test_transfer_fee() {
+    let (svm, payer_kp) = &mut setup();
+    let payer_pk = payer_kp.pubkey();
+
+    let mint_pk = CreateMint::new(svm, payer_kp)
+        .extension(MintExtension::TransferFeeConfig {
+            transfer_fee_config_authority: Some(payer_pk),
+            withdraw_withheld_authority: Some(payer_pk),
+            transfer_fee_basis_points: 100,
+            maximum_fee: 1_000,
+        })
+        .send()
+        .unwrap();
+
+    let source_pk = CreateAssociatedTokenAccount::new(svm, payer_kp, &mint_pk)
+        .token_program_id(&TOKEN_2022_ID)
+        .send()
+        .unwrap();
+    let destination_pk = CreateAccount::new(svm, payer_kp, &mint_pk)
+        .token_program_id(&TOKEN_2022_ID)
+        .send()
+        .unwrap();
+
+    MintTo::new(svm, payer_kp, &mint_pk, &source_pk, 100_000)
+        .token_program_id(&TOKEN_2022_ID)
+        .send()
+        .unwrap();
+    TransferCheckedWithFee::new(svm, payer_kp, &mint_pk, &destination_pk, 10_000)
+        .send()
+        .unwrap();
+
+    let data = get_account_data(svm, &destination_pk);
+    let account = StateWithExtensions::<Account>::unpack(&data).unwrap();
+    assert_eq!(account.base.amount, 9_900);
+    let withheld_amount: u64 = account
+        .get_extension::<TransferFeeAmount>()
+        .unwrap()
+        .withheld_amount
+        .into();
+    assert_eq!(withheld_amount, 100);
+
+    HarvestWithheldTokensToMint::new(svm, payer_kp, &mint_pk, &[&destination_pk])
+        .send()
+        .unwrap();
+    WithdrawWithheldTokensFromMint::new(svm, payer_kp, &mint_pk, &source_pk)
+        .send()
+        .unwrap();
+
+    let account: Account = get_spl_account(svm, &source_pk).unwrap();
+    assert_eq!(account.amount, 90_100);
+
+    SetTransferFee::new(svm, payer_kp, &mint_pk, 200, 2_000)
+        .send()
+        .unwrap();
+}
+
+#[test]
+fn test_interest_bearing_mint() {
+    let (svm, payer_kp) = &mut setup();
+    let payer_pk = payer_kp.pubkey();
+
+    let mint_pk = CreateMint::new(svm, payer_kp)
+        .extension(MintExtension::InterestBearingConfig {
+            rate_authority: Some(payer_pk),
+            rate: 500,
+        })
+        .send()
+        .unwrap();
+
+    UpdateInterestRate::new(svm, payer_kp, &mint_pk, 1_000)
+        .send()
+        .unwrap();
+
+    let data = get_account_data(svm, &mint_pk);
+    let mint = StateWithExtensions::<Mint>::unpack(&data).unwrap();
+    let rate: i16 = mint
+        .get_extension::<InterestBearingConfig>()
+        .unwrap()
+        .current_rate
+        .into();
+    assert_eq!(rate, 1_000);
+}
+
+#[test]
+fn test_permanent_delegate() {
+    let (svm, payer_kp) = &mut setup();
+    let delegate_kp = Keypair::new();
+    let owner_kp = Keypair::new();
+
+    let mint_pk = CreateMint::new(svm, payer_kp)
+        .extension(MintExtension::PermanentDelegate {
+            delegate: delegate_kp.pubkey(),
+        })
+        .send()
+        .unwrap();
+
+    let source_pk = CreateAccount::new(svm, payer_kp, &mint_pk)
+        .owner(&owner_kp.pubkey())
+        .token_program_id(&TOKEN_2022_ID)
+        .send()
+        .unwrap();
+    let destination_pk = CreateAccount::new(svm, payer_kp, &mint_pk)
+        .token_program_id(&TOKEN_2022_ID)
+        .send()
+        .unwrap();
+
+    MintTo::new(svm, payer_kp, &mint_pk, &source_pk, 1_000)
+        .token_program_id(&TOKEN_2022_ID)
+        .send()
+        .unwrap();
+    TransferChecked::new(svm, payer_kp, &mint_pk, &destination_pk, 1_000)
+        .source(&source_pk)
+        .owner(&delegate_kp)
+        .token_program_id(&TOKEN_2022_ID)
+        .send()
+        .unwrap();
+
+    let account: Account = get_spl_account(svm, &destination_pk).unwrap();
+    assert_eq!(account.amount, 1_000);
+}
+
+#[test]
+fn test_default_account_state() {
+    let (svm, payer_kp) = &mut setup();
+    let payer_pk = payer_kp.pubkey();
+
+    let mint_pk = CreateMint::new(svm, payer_kp)
+        .freeze_authority(&payer_pk)
+        .extension(MintExtension::DefaultAccountState {
+            state: AccountState::Frozen,
+        })
+        .send()
+        .unwrap();
+
+    let account_pk = CreateAccount::new(svm, payer_kp, &mint_pk)
+        .token_program_id(&TOKEN_2022_ID)
+        .send()
+        .unwrap();
+    let account: Account = get_spl_account(svm, &account_pk).unwrap();
+    assert!(account.is_frozen());
+
+    UpdateDefaultAccountState::new(svm, payer_kp, &mint_pk, AccountState::Initialized)
+        .send()
+        .unwrap();
+
+    let data = get_account_data(svm, &mint_pk);
+    let mint = StateWithExtensions::<Mint>::unpack(&data).unwrap();
+    let state = mint.get_extension::<DefaultAccountState>().unwrap().state;
+    assert_eq!(state, AccountState::Initialized as u8);
+}
+
+#[test]
+fn test_memo_transfer_and_cpi_guard() {
+    let (svm, payer_kp) = &mut setup();
+
+    let mint_pk = CreateMint::new(svm, payer_kp)
+        .token_program_id(&TOKEN_2022_ID)
+        .send()
+        .unwrap();
+    let source_pk = CreateAssociatedTokenAccount::new(svm, payer_kp, &mint_pk)
+        .token_program_id(&TOKEN_2022_ID)
+        .send()
+        .unwrap();
+    let destination_pk = CreateAccount::new(svm, payer_kp, &mint_pk)
+        .token_program_id(&TOKEN_2022_ID)
+        .send()
+        .unwrap();
+
+    MintTo::new(svm, payer_kp, &mint_pk, &source_pk, 1_000)
+        .token_program_id(&TOKEN_2022_ID)
+        .send()
+        .unwrap();
+
+    EnableRequiredTransferMemos::new(svm, payer_kp, &destination_pk)
+        .send()
+        .unwrap();
+    EnableCpiGuard::new(svm, payer_kp, &source_pk)
+        .send()
+        .unwrap();
+
+    let data = get_account_data(svm, &destination_pk);
+    let account = StateWithExtensions::<Account>::unpack(&data).unwrap();
+    let memo_transfer = account.get_extension::<MemoTransfer>().unwrap();
+    assert!(bool::from(memo_transfer.require_incoming_transfer_memos));
+
+    let data = get_account_data(svm, &source_pk);
+    let account = StateWithExtensions::<Account>::unpack(&data).unwrap();
+    assert!(bool::from(
+        account.get_extension::<CpiGuard>().unwrap().lock_cpi
+    ));
+
+    let result = TransferChecked::new(svm, payer_kp, &mint_pk, &destination_pk, 1_000)
+        .token_program_id(&TOKEN_2022_ID)
+        .send();
+    assert!(result.is_err());
+
+    DisableCpiGuard::new(svm, payer_kp, &source_pk)
+        .send()
+        .unwrap();
+}
+
+#[test]
+fn test_token_metadata() {
+    let (svm, payer_kp) = &mut setup();
+    let payer_pk = payer_kp.pubkey();
+    let mint_kp = Keypair::new();
+    let mint_pk = mint_kp.pubkey();
+
+    CreateMint::new(svm, payer_kp)
+        .mint_kp(mint_kp)
+        .extension(MintExtension::MetadataPointer {
+            authority: Some(payer_pk),
+            metadata_address: Some(mint_pk),
+        })
+        .send()
+        .unwrap();
+
+    InitializeTokenMetadata::new(svm, payer_kp, &mint_pk, "Token", "TKN", "https://token")
+        .send()
+        .unwrap();
+    UpdateTokenMetadataField::new(
+        svm,
+        payer_kp,
+        &mint_pk,
+        Field::Uri,
+        "https://a-much-longer-token-uri",
+    )
+    .send()
+    .unwrap();
+    UpdateTokenMetadataField::new(
+        svm,
+        payer_kp,
+        &mint_pk,
+        Field::Key("color".to_string()),
+        "blue",
+    )
+    .send()
+    .unwrap();
+
+    let data = get_account_data(svm, &mint_pk);
+    let mint = StateWithExtensions::<Mint>::unpack(&data).unwrap();
+    let metadata = mint.get_variable_len_extension::<TokenMetadata>().unwrap();
+    assert_eq!(metadata.name, "Token");
+    assert_eq!(metadata.symbol, "TKN");
+    assert_eq!(metadata.uri, "https://a-much-longer-token-uri");
+    assert_eq!(
+        metadata.additional_metadata,
+        vec![("color".to_string(), "blue".to_string())]
+    );
+}
+
+#[test]
+fn test_mint_close_authority() {
+    let (svm, payer_kp) = &mut setup();
+    let payer_pk = payer_kp.pubkey();
+
+    let mint_pk = CreateMint::new(svm, payer_kp)
+        .extension(MintExtension::MintCloseAuthority {
+            close_authority: Some(payer_pk),
+        })
+        .send()
+        .unwrap();
+
+    CloseAccount::new(svm, payer_kp, &mint_pk, &payer_pk)
+        .token_program_id(&TOKEN_2022_ID)
+        .send()
+        .unwrap();
+
+    assert!(svm.get_account(&mint_pk).is_none());
+}
+
+#[test]
+fn test_non_transferable() {
+    let (svm, payer_kp) = &mut setup();
+
+    let mint_pk = CreateMint::new(svm, payer_kp)
+        .extension(MintExtension::NonTransferable)
+        .send()
+        .unwrap();
+
+    let source_pk = CreateAssociatedTokenAccount::new(svm, payer_kp, &mint_pk)
+        .token_program_id(&TOKEN_2022_ID)
+        .send()
+        .unwrap();
+    let destination_pk = CreateAccount::new(svm, payer_kp, &mint_pk)
+        .token_program_id(&TOKEN_2022_ID)
+        .send()
+        .unwrap();
+
+    MintTo::new(svm, payer_kp, &mint_pk, &source_pk, 1_000)
+        .token_program_id(&TOKEN_2022_ID)
+        .send()
+        .unwrap();
+
+    let result = TransferChecked::new(svm, payer_kp, &mint_pk, &destination_pk, 1_000)
+        .token_program_id(&TOKEN_2022_ID)
+        .send();
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_read_extensions() {
+    let (svm, payer_kp) = &mut setup();
+    let payer_pk = payer_kp.pubkey();
+    let mint_kp = Keypair::new();
+    let mint_pk = mint_kp.pubkey();
+
+    CreateMint::new(svm, payer_kp)
+        .mint_kp(mint_kp)
+        .extension(MintExtension::TransferFeeConfig {
+            transfer_fee_config_authority: None,
+            withdraw_withheld_authority: None,
+            transfer_fee_basis_points: 100,
+            maximum_fee: 1_000,
+        })
+        .extension(MintExtension::MetadataPointer {
+            authority: None,
+            metadata_address: Some(mint_pk),
+        })
+        .send()
+        .unwrap();
+    InitializeTokenMetadata::new(svm, payer_kp, &mint_pk, "Token", "TKN", "https://token")
+        .send()
+        .unwrap();
+
+    let source_pk = CreateAssociatedTokenAccount::new(svm, payer_kp, &mint_pk)
+        .token_program_id(&TOKEN_2022_ID)
+        .send()
+        .unwrap();
+    MintTo::new(svm, payer_kp, &mint_pk, &source_pk, 10_000)
+        .token_program_id(&TOKEN_2022_ID)
+        .send()
+        .unwrap();
+    let destination_pk = CreateAccount::new(svm, payer_kp, &mint_pk)
+        .token_program_id(&TOKEN_2022_ID)
+        .send()
+        .unwrap();
+    TransferCheckedWithFee::new(svm, payer_kp, &mint_pk, &destination_pk, 10_000)
+        .send()
+        .unwrap();
+
+    let mint = get_mint_with_extensions(svm, &mint_pk).unwrap();
+    assert_eq!(mint.base.supply, 10_000);
+    assert_eq!(
+        mint.extension_types(),
+        vec![
+            ExtensionType::TransferFeeConfig,
+            ExtensionType::MetadataPointer,
+            ExtensionType::TokenMetadata,
+        ]
+    );
+    let Some(ExtensionState::TokenMetadata(metadata)) = mint.extensions.last() else {
+        panic!("missing token metadata");
+    };
+    assert_eq!(metadata.name, "Token");
+
+    let account = get_token_account_with_extensions(svm, &destination_pk).unwrap();
+    assert_eq!(account.base.amount, 9_900);
+    let [ExtensionState::TransferFeeAmount(transfer_fee_amount)] = account.extensions.as_slice()
+    else {
+        panic!("unexpected extensions: {:?}", account.extensions);
+    };
+    assert_eq!(u64::from(transfer_fee_amount.withheld_amount), 100);
+
+    let account = get_token_account_with_extensions(svm, &source_pk).unwrap();
+    assert_eq!(
+        account.extension_types(),
+        vec![
+            ExtensionType::ImmutableOwner,
+            ExtensionType::TransferFeeAmount
+        ]
+    );
+
+    let spl_mint_pk = CreateMint::new(svm, payer_kp).send().unwrap();
+    let spl_mint = get_mint_with_extensions(svm, &spl_mint_pk).unwrap();
+    assert_eq!(spl_mint.base.mint_authority, Some(payer_pk).into());
+    assert!(spl_mint.extensions.is_empty());
+}
@@ -0,0 +1,100 @@
+use {
+    litesvm::LiteSVM,
+    litesvm_token::{
+        get_spl_account, spl_token::state::Account, CreateAssociatedTokenAccount, CreateMint,
+        MintTo, Transfer, TOKEN_ID,
+    },
+    solana_keypair::Keypair,
+    solana_native_token::LAMPORTS_PER_SOL,
+    solana_signer::Signer,
+    solana_system_interface::instruction::transfer,
+    solana_transaction::Transaction,
+    spl_associated_token_account_interface::address::get_associated_token_address_with_program_id,
+};
+
+#[test]
+fn test_compose_instructions() {
+    let svm = &mut LiteSVM::new();
+
+    let payer_kp = Keypair::new();
+    let payer_pk = payer_kp.pubkey();
+    let recipient_pk = Keypair::new().pubkey();
+
+    svm.airdrop(&payer_pk, LAMPORTS_PER_SOL * 10).unwrap();
+
+    let mint_kp = Keypair::new();
+    let mint_pk = mint_kp.pubkey();
+    let ata_pk = get_associated_token_address_with_program_id(&payer_pk, &mint_pk, &TOKEN_ID);
+
+    let create_mint = CreateMint::new(svm, &payer_kp)
+        .mint_kp(mint_kp)
+        .instructions()
+        .unwrap();
+    let create_ata = CreateAssociatedTokenAccount::new(svm, &payer_kp, &mint_pk)
+        .instructions()
+        .unwrap();
+    let mint_to = MintTo::new(svm, &payer_kp, &mint_pk, &ata_pk, 1_000)
+        .instructions()
+        .unwrap();
+
+    assert_eq!(create_mint.instructions.len(), 2);
+    assert_eq!(
+        create_mint
+            .signers
+            .iter()
+            .map(Signer::pubkey)
+            .collect::<Vec<_>>(),
+        [payer_pk, mint_pk]
+    );
+    assert!(svm.get_account(&mint_pk).is_none());
+
+    let mut ixs = vec![];
+    ixs.extend(create_mint.instructions);
+    ixs.extend(create_ata.instructions);
+    ixs.extend(mint_to.instructions);
+    ixs.push(transfer(&payer_pk, &recipient_pk, LAMPORTS_PER_SOL));
+
+    let tx = Transaction::new_signed_with_payer(
+        &ixs,
+        Some(&payer_pk),
+        &create_mint.signers.iter().collect::<Vec<_>>(),
+        svm.latest_blockhash(),
+    );
+    svm.send_transaction(tx).unwrap();
+
+    let account: Account = get_spl_account(svm, &ata_pk).unwrap();
+    assert_eq!(account.amount, 1_000);
+    assert_eq!(svm.get_balance(&recipient_pk), Some(LAMPORTS_PER_SOL));
+}
+
+#[test]
+fn test_signers_exclude_fee_payer() {
+    let svm = &mut LiteSVM::new();
+
+    let payer_kp = Keypair::new();
+    let owner_kp = Keypair::new();
+    let owner_pk = owner_kp.pubkey();
+
+    svm.airdrop(&payer_kp.pubkey(), LAMPORTS_PER_SOL * 10)
+        .unwrap();
+
+    let mint_pk = CreateMint::new(svm, &payer_kp).send().unwrap();
+    let destination_pk = CreateAssociatedTokenAccount::new(svm, &payer_kp, &mint_pk)
+        .send()
+        .unwrap();
+
+    let transfer = Transfer::new(svm, &payer_kp, &mint_pk, &destination_pk, 10)
+        .owner(&owner_kp)
+        .instructions()
+        .unwrap();
+
+    assert_eq!(transfer.instructions.len(), 1);
+    assert_eq!(
+        transfer
+            .signers
+            .iter()
+            .map(Signer::pubkey)
+            .collect::<Vec<_>>(),
+        [owner_pk]
+    );
+}
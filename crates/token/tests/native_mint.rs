@@ -1,16 +1,19 @@
 use {
     litesvm::LiteSVM,
     litesvm_token::{
-        get_spl_account, spl_token::state::Mint, CreateAssociatedTokenAccount, CreateNativeMint,
-        SyncNative,
+        get_spl_account, spl_token::state::Mint, spl_token_2022, CreateAssociatedTokenAccount,
+        CreateNativeMint, SyncNative, SPL_TOKEN_ID, TOKEN_2022_ID,
     },
+    solana_address::Address,
+    solana_instruction::error::InstructionError,
     solana_keypair::Keypair,
     solana_native_token::LAMPORTS_PER_SOL,
+    solana_program_error::ProgramError,
     solana_signer::Signer,
+    solana_transaction_error::TransactionError,
 };
 
-#[test]
-fn test() {
+fn test_native_mint(token_program_id: &Address, native_mint: &Address) {
     let svm = &mut LiteSVM::new();
 
     let payer_kp = Keypair::new();
@@ -18,56 +21,63 @@ fn test() {
 
     svm.airdrop(&payer_pk, LAMPORTS_PER_SOL * 10).unwrap();
 
-    CreateNativeMint::new(svm, &payer_kp).send().unwrap();
+    CreateNativeMint::new(svm, &payer_kp)
+        .token_program_id(token_program_id)
+        .send()
+        .unwrap();
 
-    let mint: Mint = get_spl_account(svm, &spl_token_2022_interface::native_mint::ID).unwrap();
+    let mint: Mint = get_spl_account(svm, native_mint).unwrap();
 
     assert_eq!(mint.decimals, 9);
     assert_eq!(mint.supply, 0);
     assert!(mint.mint_authority.is_none());
     assert!(mint.is_initialized);
     assert_eq!(mint.freeze_authority, None.into());
+    assert_eq!(
+        svm.get_account(native_mint).unwrap().owner,
+        *token_program_id
+    );
 
-    let account_pk = CreateAssociatedTokenAccount::new(
-        svm,
-        &payer_kp,
-        &spl_token_2022_interface::native_mint::ID,
-    )
-    .send()
-    .unwrap();
+    let account_pk = CreateAssociatedTokenAccount::new(svm, &payer_kp, native_mint)
+        .token_program_id(token_program_id)
+        .send()
+        .unwrap();
 
-    SyncNative::new(svm, &payer_kp, &account_pk).send().unwrap();
+    SyncNative::new(svm, &payer_kp, &account_pk)
+        .token_program_id(token_program_id)
+        .send()
+        .unwrap();
+}
+
+#[test]
+fn test() {
+    test_native_mint(&SPL_TOKEN_ID, &spl_token_interface::native_mint::ID);
 }
 
 #[test]
 fn test_token_program_id() {
-    let svm = &mut LiteSVM::new();
+    test_native_mint(&TOKEN_2022_ID, &spl_token_2022::native_mint::ID);
+}
 
+#[test]
+fn spl_token_native_mint_has_no_instructions() {
+    let svm = &mut LiteSVM::new();
     let payer_kp = Keypair::new();
-    let payer_pk = payer_kp.pubkey();
-
-    svm.airdrop(&payer_pk, LAMPORTS_PER_SOL * 10).unwrap();
 
-    CreateNativeMint::new(svm, &payer_kp)
-        .token_program_id(&spl_token_2022_interface::ID)
-        .send()
+    let err = CreateNativeMint::new(svm, &payer_kp)
+        .token_program_id(&SPL_TOKEN_ID)
+        .instructions()
+        .err()
         .unwrap();
 
-    let mint: Mint = get_spl_account(svm, &spl_token_2022_interface::native_mint::ID).unwrap();
-
-    assert_eq!(mint.decimals, 9);
-    assert_eq!(mint.supply, 0);
-    assert!(mint.mint_authority.is_none());
-    assert!(mint.is_initialized);
-    assert_eq!(mint.freeze_authority, None.into());
-
-    let account_pk = CreateAssociatedTokenAccount::new(
-        svm,
-        &payer_kp,
-        &spl_token_2022_interface::native_mint::ID,
-    )
-    .send()
-    .unwrap();
-
-    SyncNative::new(svm, &payer_kp, &account_pk).send().unwrap();
+    assert_eq!(
+        err.err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(u64::from(ProgramError::IncorrectProgramId) as u32)
+        )
+    );
+    assert!(svm
+        .get_account(&spl_token_interface::native_mint::ID)
+        .is_none());
 }
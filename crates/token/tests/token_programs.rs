@@ -0,0 +1,118 @@
+use {
+    litesvm::LiteSVM,
+    litesvm_token::{
+        get_spl_account,
+        spl_token::state::{Account, Mint},
+        spl_token_2022::instruction::AuthorityType,
+        Burn, CreateAccount, CreateAssociatedTokenAccount, CreateMint, CreateMultisig,
+        FreezeAccount, MintTo, SetAuthority, ThawAccount, TransferChecked, SPL_TOKEN_ID,
+        TOKEN_2022_ID,
+    },
+    solana_address::Address,
+    solana_keypair::Keypair,
+    solana_native_token::LAMPORTS_PER_SOL,
+    solana_program_pack::Pack,
+    solana_signer::Signer,
+};
+
+fn test_token_program(token_program_id: &Address, token_account_len: usize, ata_len: usize) {
+    let svm = &mut LiteSVM::new();
+
+    let payer_kp = Keypair::new();
+    let payer_pk = payer_kp.pubkey();
+
+    svm.airdrop(&payer_pk, LAMPORTS_PER_SOL * 10).unwrap();
+
+    let mint_pk = CreateMint::new(svm, &payer_kp)
+        .freeze_authority(&payer_pk)
+        .token_program_id(token_program_id)
+        .send()
+        .unwrap();
+    let mint_account = svm.get_account(&mint_pk).unwrap();
+    assert_eq!(mint_account.owner, *token_program_id);
+    assert_eq!(mint_account.data.len(), Mint::LEN);
+
+    let ata_pk = CreateAssociatedTokenAccount::new(svm, &payer_kp, &mint_pk)
+        .token_program_id(token_program_id)
+        .send()
+        .unwrap();
+    assert_eq!(svm.get_account(&ata_pk).unwrap().data.len(), ata_len);
+
+    let account_pk = CreateAccount::new(svm, &payer_kp, &mint_pk)
+        .token_program_id(token_program_id)
+        .send()
+        .unwrap();
+    let account = svm.get_account(&account_pk).unwrap();
+    assert_eq!(account.owner, *token_program_id);
+    assert_eq!(account.data.len(), token_account_len);
+
+    MintTo::new(svm, &payer_kp, &mint_pk, &ata_pk, 1_000)
+        .token_program_id(token_program_id)
+        .send()
+        .unwrap();
+    TransferChecked::new(svm, &payer_kp, &mint_pk, &account_pk, 400)
+        .token_program_id(token_program_id)
+        .send()
+        .unwrap();
+    Burn::new(svm, &payer_kp, &mint_pk, &ata_pk, 100)
+        .token_program_id(token_program_id)
+        .send()
+        .unwrap();
+
+    let account: Account = get_spl_account(svm, &ata_pk).unwrap();
+    assert_eq!(account.amount, 500);
+    let account: Account = get_spl_account(svm, &account_pk).unwrap();
+    assert_eq!(account.amount, 400);
+
+    FreezeAccount::new(svm, &payer_kp, &mint_pk)
+        .token_program_id(token_program_id)
+        .send()
+        .unwrap();
+    let account: Account = get_spl_account(svm, &ata_pk).unwrap();
+    assert!(account.is_frozen());
+    ThawAccount::new(svm, &payer_kp, &mint_pk)
+        .token_program_id(token_program_id)
+        .send()
+        .unwrap();
+
+    SetAuthority::new(svm, &payer_kp, &mint_pk, AuthorityType::MintTokens)
+        .token_program_id(token_program_id)
+        .send()
+        .unwrap();
+    let mint: Mint = get_spl_account(svm, &mint_pk).unwrap();
+    assert!(mint.mint_authority.is_none());
+
+    let multisig_pk = CreateMultisig::new(svm, &payer_kp, &[&payer_pk], 1)
+        .token_program_id(token_program_id)
+        .send()
+        .unwrap();
+    assert_eq!(
+        svm.get_account(&multisig_pk).unwrap().owner,
+        *token_program_id
+    );
+}
+
+#[test]
+fn test_spl_token() {
+    test_token_program(&SPL_TOKEN_ID, Account::LEN, Account::LEN);
+}
+
+#[test]
+fn test_token_2022() {
+    // Token-2022 associated token accounts carry the immutable owner extension.
+    test_token_program(&TOKEN_2022_ID, Account::LEN, 170);
+}
+
+#[test]
+fn test_unknown_token_program() {
+    let svm = &mut LiteSVM::new();
+
+    let payer_kp = Keypair::new();
+    svm.airdrop(&payer_kp.pubkey(), LAMPORTS_PER_SOL * 10)
+        .unwrap();
+
+    let result = CreateMint::new(svm, &payer_kp)
+        .token_program_id(&Address::new_unique())
+        .send();
+    assert!(result.is_err());
+}
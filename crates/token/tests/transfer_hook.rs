@@ -0,0 +1,213 @@
+use {
+    litesvm::LiteSVM,
+    litesvm_token::{
+        deal, get_extra_account_metas_address, get_spl_account, get_transfer_hook_program_id,
+        spl_token_2022::state::Account,
+        spl_transfer_hook_interface::instruction::ExecuteInstruction, CreateMint, MintExtension,
+        TransferChecked, TOKEN_2022_ID,
+    },
+    solana_account::Account as SolanaAccount,
+    solana_address::Address,
+    solana_instruction::AccountMeta,
+    solana_keypair::Keypair,
+    solana_native_token::LAMPORTS_PER_SOL,
+    solana_signer::Signer,
+    solana_transaction::Transaction,
+    spl_tlv_account_resolution::{
+        account::ExtraAccountMeta, pubkey_data::PubkeyData, seeds::Seed,
+        state::ExtraAccountMetaList,
+    },
+};
+
+/// A program that logs its input and succeeds, standing in for a transfer hook.
+const HOOK_PROGRAM: &[u8] =
+    include_bytes!("../../node-litesvm/program_bytes/spl_example_logging.so");
+
+struct Setup {
+    svm: LiteSVM,
+    payer_kp: Keypair,
+    owner_kp: Keypair,
+    mint_pk: Address,
+    source_pk: Address,
+    destination_pk: Address,
+    hook_program_id: Address,
+    fixed_pk: Address,
+}
+
+fn setup() -> Setup {
+    let mut svm = LiteSVM::new();
+    let payer_kp = Keypair::new();
+    let owner_kp = Keypair::new();
+    let hook_program_id = Address::new_unique();
+    let fixed_pk = Address::new_unique();
+
+    svm.airdrop(&payer_kp.pubkey(), LAMPORTS_PER_SOL * 10)
+        .unwrap();
+    svm.add_program(hook_program_id, HOOK_PROGRAM).unwrap();
+
+    let mint_pk = CreateMint::new(&mut svm, &payer_kp)
+        .decimals(6)
+        .extension(MintExtension::TransferHook {
+            authority: None,
+            program_id: Some(hook_program_id),
+        })
+        .send()
+        .unwrap();
+
+    let extra_metas = [
+        ExtraAccountMeta::new_with_pubkey(&fixed_pk, false, false).unwrap(),
+        // Seeded by a literal and the destination.
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: b"counter".to_vec(),
+                },
+                Seed::AccountKey { index: 2 },
+            ],
+            false,
+            true,
+        )
+        .unwrap(),
+        // Seeded by the amount in the execute instruction data.
+        ExtraAccountMeta::new_with_seeds(
+            &[Seed::InstructionData {
+                index: 8,
+                length: 8,
+            }],
+            false,
+            false,
+        )
+        .unwrap(),
+        // Seeded by the owner stored in the source account.
+        ExtraAccountMeta::new_with_seeds(
+            &[Seed::AccountData {
+                account_index: 0,
+                data_index: 32,
+                length: 32,
+            }],
+            false,
+            false,
+        )
+        .unwrap(),
+        // The owner stored in the source account.
+        ExtraAccountMeta::new_with_pubkey_data(
+            &PubkeyData::AccountData {
+                account_index: 0,
+                data_index: 32,
+            },
+            false,
+            false,
+        )
+        .unwrap(),
+    ];
+    let mut data = vec![0; ExtraAccountMetaList::size_of(extra_metas.len()).unwrap()];
+    ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &extra_metas).unwrap();
+    svm.set_account(
+        get_extra_account_metas_address(&mint_pk, &hook_program_id),
+        SolanaAccount {
+            lamports: svm.minimum_balance_for_rent_exemption(data.len()),
+            data,
+            owner: hook_program_id,
+            executable: false,
+            rent_epoch: 0,
+        },
+    )
+    .unwrap();
+
+    let source_pk = deal(&mut svm, &mint_pk, &owner_kp.pubkey(), 1_000).unwrap();
+    let destination_pk = deal(&mut svm, &mint_pk, &payer_kp.pubkey(), 0).unwrap();
+
+    Setup {
+        svm,
+        payer_kp,
+        owner_kp,
+        mint_pk,
+        source_pk,
+        destination_pk,
+        hook_program_id,
+        fixed_pk,
+    }
+}
+
+#[test]
+fn test_resolve_extra_accounts() {
+    let Setup {
+        mut svm,
+        payer_kp,
+        owner_kp,
+        mint_pk,
+        source_pk,
+        destination_pk,
+        hook_program_id,
+        fixed_pk,
+    } = setup();
+    let owner_pk = owner_kp.pubkey();
+
+    assert_eq!(
+        get_transfer_hook_program_id(&svm, &mint_pk).unwrap(),
+        Some(hook_program_id)
+    );
+
+    let ixs = TransferChecked::new(&mut svm, &payer_kp, &mint_pk, &destination_pk, 250)
+        .source(&source_pk)
+        .owner(&owner_kp)
+        .token_program_id(&TOKEN_2022_ID)
+        .instructions()
+        .unwrap();
+
+    let pda = |seeds: &[&[u8]]| Address::find_program_address(seeds, &hook_program_id).0;
+    assert_eq!(
+        ixs.instructions[0].accounts[4..],
+        [
+            AccountMeta::new_readonly(fixed_pk, false),
+            AccountMeta::new(pda(&[b"counter", destination_pk.as_ref()]), false),
+            AccountMeta::new_readonly(pda(&[&250u64.to_le_bytes()]), false),
+            AccountMeta::new_readonly(pda(&[owner_pk.as_ref()]), false),
+            AccountMeta::new_readonly(owner_pk, false),
+            AccountMeta::new_readonly(hook_program_id, false),
+            AccountMeta::new_readonly(
+                get_extra_account_metas_address(&mint_pk, &hook_program_id),
+                false
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_transfer_with_hook() {
+    let Setup {
+        mut svm,
+        payer_kp,
+        owner_kp,
+        mint_pk,
+        source_pk,
+        destination_pk,
+        ..
+    } = setup();
+
+    // Token-2022 rejects the transfer without the hook accounts.
+    let mut ixs = TransferChecked::new(&mut svm, &payer_kp, &mint_pk, &destination_pk, 250)
+        .source(&source_pk)
+        .owner(&owner_kp)
+        .token_program_id(&TOKEN_2022_ID)
+        .instructions()
+        .unwrap();
+    ixs.instructions[0].accounts.truncate(4);
+    let tx = Transaction::new_signed_with_payer(
+        &ixs.instructions,
+        Some(&payer_kp.pubkey()),
+        &[&payer_kp, &owner_kp],
+        svm.latest_blockhash(),
+    );
+    assert!(svm.send_transaction(tx).is_err());
+
+    TransferChecked::new(&mut svm, &payer_kp, &mint_pk, &destination_pk, 250)
+        .source(&source_pk)
+        .owner(&owner_kp)
+        .token_program_id(&TOKEN_2022_ID)
+        .send()
+        .unwrap();
+
+    let account: Account = get_spl_account(&svm, &destination_pk).unwrap();
+    assert_eq!(account.amount, 250);
+}